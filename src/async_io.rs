@@ -0,0 +1,461 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Read and write Hjson with the `AsyncRead` and `AsyncWrite` traits of the
+//! futures crate, without blocking the task. Needs the `async` feature.
+//!
+//! Serde deserializes synchronously, so the input is first passed through an
+//! [`events::PushParser`], a chunk at a time as the reader has it ready, to
+//! find where each value ends. Once all of a value has been read, it is
+//! deserialized without waiting for anything. Only the input of the value
+//! being read is held in memory.
+//!
+//! [`events::PushParser`]: ../events/struct.PushParser.html
+//!
+//! Tokio's `AsyncRead` and `AsyncWrite` are different traits, which the
+//! `compat` module of the tokio-util crate converts to these.
+//!
+//! ```rust
+//! # #[macro_use]
+//! # extern crate serde_derive;
+//! #
+//! # extern crate futures;
+//! # extern crate serde_hjson;
+//! #
+//! use futures::executor::block_on;
+//! use futures::stream::StreamExt;
+//! use serde_hjson::AsyncStreamDeserializer;
+//!
+//! #[derive(Deserialize)]
+//! struct Message {
+//!     id: u64,
+//! }
+//!
+//! # fn main() {
+//! let input: &[u8] = b"{id: 1}\n{id: 2}\n";
+//! let ids: Vec<u64> = block_on(
+//!     AsyncStreamDeserializer::<_, Message>::new(input)
+//!         .map(|message| message.unwrap().id)
+//!         .collect(),
+//! );
+//! assert_eq!(ids, [1, 2]);
+//! # }
+//! ```
+
+use std::future::Future;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+use futures_io::{AsyncRead, AsyncWrite};
+use serde::de;
+use serde::ser;
+
+use de::{Deserializer, StreamDeserializer};
+use error::{Error, Result};
+use events::{Event, PushParser};
+use read::ChunkRead;
+use ser::to_writer;
+
+/// How many bytes to read or write at a time.
+const CHUNK_SIZE: usize = 8 * 1024;
+
+/// Deserialize an instance of type `T` from an `AsyncRead` of Hjson text.
+///
+/// Like `from_reader`, the input has to end after the value, apart from
+/// whitespace and comments. A data error is reported as soon as the value is
+/// complete though, without reading the rest.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// #
+/// # extern crate futures;
+/// # extern crate serde_hjson;
+/// #
+/// #[derive(Deserialize)]
+/// struct Config {
+///     port: u16,
+/// }
+///
+/// # fn main() {
+/// let input: &[u8] = b"port: 8080";
+/// let config: Config = futures::executor::block_on(serde_hjson::from_async_reader(input)).unwrap();
+/// assert_eq!(config.port, 8080);
+/// # }
+/// ```
+pub fn from_async_reader<R, T>(reader: R) -> FromAsyncReader<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    FromAsyncReader { input: Input::new(reader, false) }
+}
+
+/// Serialize the given data structure as Hjson into an `AsyncWrite`.
+///
+/// The whole data structure is serialized when the future is first polled,
+/// since serialization cannot stop halfway to wait for the writer. The output
+/// is written as it comes, a chunk at a time, until the writer is not ready.
+/// From then on the rest of the output is held in memory and written by the
+/// following polls, so with a slow writer up to all of the output is held at
+/// once, like with `to_vec`. Like `to_writer`, it does not flush the writer.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+pub fn to_async_writer<'a, W, T: ?Sized>(writer: W, value: &'a T) -> ToAsyncWriter<'a, W, T>
+where
+    W: AsyncWrite + Unpin,
+    T: ser::Serialize,
+{
+    ToAsyncWriter {
+        writer: writer,
+        value: Some(value),
+        pending: Vec::new(),
+    }
+}
+
+/// Future returned by `from_async_reader`.
+pub struct FromAsyncReader<R, T> {
+    input: Input<R, T>,
+}
+
+impl<R, T> Future for FromAsyncReader<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    type Output = Result<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<T>> {
+        match self.get_mut().input.poll_next(cx) {
+            Poll::Ready(Some((result, _))) => Poll::Ready(result),
+            Poll::Ready(None) => panic!("`FromAsyncReader` polled after completion"),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Future returned by `to_async_writer`.
+pub struct ToAsyncWriter<'a, W, T: ?Sized + 'a> {
+    writer: W,
+    /// The data structure until it is serialized.
+    value: Option<&'a T>,
+    /// Output that the writer has not taken yet.
+    pending: Vec<u8>,
+}
+
+impl<'a, W, T: ?Sized> Future for ToAsyncWriter<'a, W, T>
+where
+    W: AsyncWrite + Unpin,
+    T: ser::Serialize,
+{
+    type Output = Result<()>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        let this = self.get_mut();
+        if let Some(value) = this.value.take() {
+            let output = Output {
+                writer: &mut this.writer,
+                cx: cx,
+                pending: &mut this.pending,
+                blocked: false,
+            };
+            if let Err(err) = to_writer(output, value) {
+                return Poll::Ready(Err(err));
+            }
+        }
+        match write_pending(&mut this.writer, cx, &mut this.pending) {
+            Poll::Ready(Ok(())) => Poll::Ready(Ok(())),
+            Poll::Ready(Err(err)) => Poll::Ready(Err(Error::io(err))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// Passes the output of the serializer on to the writer a chunk at a time,
+/// until the writer is not ready for more.
+struct Output<'a, 'b: 'a, W: 'a> {
+    writer: &'a mut W,
+    cx: &'a mut Context<'b>,
+    pending: &'a mut Vec<u8>,
+    /// Whether the writer was not ready, so the rest of the output has to wait
+    /// for the next poll.
+    blocked: bool,
+}
+
+impl<'a, 'b, W> io::Write for Output<'a, 'b, W>
+where
+    W: AsyncWrite + Unpin,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.pending.extend_from_slice(buf);
+        if !self.blocked && self.pending.len() >= CHUNK_SIZE {
+            match write_pending(self.writer, self.cx, self.pending) {
+                Poll::Ready(Ok(())) => {}
+                Poll::Ready(Err(err)) => return Err(err),
+                Poll::Pending => self.blocked = true,
+            }
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Writes as much of `pending` as the writer takes, removing it.
+fn write_pending<W>(writer: &mut W, cx: &mut Context, pending: &mut Vec<u8>) -> Poll<io::Result<()>>
+where
+    W: AsyncWrite + Unpin,
+{
+    let mut written = 0;
+    let result = loop {
+        if written == pending.len() {
+            break Poll::Ready(Ok(()));
+        }
+        match Pin::new(&mut *writer).poll_write(cx, &pending[written..]) {
+            Poll::Ready(Ok(0)) => {
+                let err = io::Error::new(io::ErrorKind::WriteZero, "failed to write whole buffer");
+                break Poll::Ready(Err(err));
+            }
+            Poll::Ready(Ok(len)) => written += len,
+            Poll::Ready(Err(ref err)) if err.kind() == io::ErrorKind::Interrupted => {}
+            Poll::Ready(Err(err)) => break Poll::Ready(Err(err)),
+            Poll::Pending => break Poll::Pending,
+        }
+    };
+    pending.drain(..written);
+    result
+}
+
+/// Stream that deserializes a sequence of Hjson values from an `AsyncRead`,
+/// like `StreamDeserializer`.
+///
+/// The values need to be self-delineating, or be followed by whitespace or a
+/// self-delineating value. Root objects need braces, and a quoteless string
+/// runs to the end of its line. The stream ends after an error.
+pub struct AsyncStreamDeserializer<R, T> {
+    input: Input<R, T>,
+}
+
+impl<R, T> AsyncStreamDeserializer<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    /// Create a stream deserializer reading from `reader`.
+    pub fn new(reader: R) -> Self {
+        AsyncStreamDeserializer { input: Input::new(reader, true) }
+    }
+
+    /// Returns the number of bytes so far deserialized into a successful `T`,
+    /// or skipped over as whitespace and comments before it.
+    pub fn byte_offset(&self) -> usize {
+        self.input.offset
+    }
+}
+
+impl<R, T> Stream for AsyncStreamDeserializer<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    type Item = Result<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Result<T>>> {
+        match self.get_mut().input.poll_next(cx) {
+            Poll::Ready(next) => Poll::Ready(next.map(|(result, _)| result)),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+/// The reader, and the push parser that tells when the deserializer has all
+/// of the next value.
+struct Input<R, T> {
+    reader: R,
+    chunk: Vec<u8>,
+    parser: PushParser,
+    /// The same input as the parser, from the start of the next value to
+    /// deserialize on.
+    read: ChunkRead,
+    /// Whether the input is a stream of values rather than one document.
+    multiple: bool,
+    /// How deep the parser is in objects and arrays.
+    depth: usize,
+    /// Root values that the parser has read all of but that have not been
+    /// deserialized yet.
+    complete: usize,
+    /// How the events ended, once they did: at the end of the input or with a
+    /// syntax error.
+    end: Option<Result<()>>,
+    /// The value of a single document, until the rest of the input is read.
+    value: Option<T>,
+    /// Whether the reader is at the end of the input.
+    eof: bool,
+    done: bool,
+    /// Byte offset of the end of the last value deserialized.
+    offset: usize,
+}
+
+// The value is only moved out, never pinned.
+impl<R, T> Unpin for Input<R, T>
+where
+    R: Unpin,
+{
+}
+
+impl<R, T> Input<R, T>
+where
+    R: AsyncRead + Unpin,
+    T: de::DeserializeOwned,
+{
+    fn new(reader: R, multiple: bool) -> Self {
+        Input {
+            reader: reader,
+            chunk: vec![0; CHUNK_SIZE],
+            parser: if multiple { PushParser::stream() } else { PushParser::new() },
+            read: ChunkRead::new(),
+            multiple: multiple,
+            depth: 0,
+            complete: 0,
+            end: None,
+            value: None,
+            eof: false,
+            done: false,
+            offset: 0,
+        }
+    }
+
+    /// Returns the next value, reading input until the parser has all of it.
+    fn poll_next(&mut self, cx: &mut Context) -> Poll<Option<(Result<T>, usize)>> {
+        loop {
+            if self.done {
+                return Poll::Ready(None);
+            }
+            if self.complete > 0 || self.end.is_some() {
+                if let Some(next) = self.deserialize() {
+                    return Poll::Ready(next);
+                }
+            }
+            if self.complete == 0 && self.end.is_none() {
+                match self.parser.next_event() {
+                    Ok(Some((event, _))) => {
+                        match event {
+                            Event::StartObject | Event::StartArray => self.depth += 1,
+                            Event::EndObject | Event::EndArray => {
+                                self.depth -= 1;
+                                if self.depth == 0 {
+                                    self.complete += 1;
+                                }
+                            }
+                            Event::String(_) | Event::Number(_) | Event::Bool(_) | Event::Null => {
+                                if self.depth == 0 {
+                                    self.complete += 1;
+                                }
+                            }
+                            Event::Key(_) | Event::Comment(_) | Event::__Nonexhaustive => {}
+                        }
+                        continue;
+                    }
+                    Ok(None) if self.eof => {
+                        self.end = Some(Ok(()));
+                        continue;
+                    }
+                    Ok(None) => {}
+                    Err(err) => {
+                        self.end = Some(Err(err));
+                        continue;
+                    }
+                }
+            }
+
+            match Pin::new(&mut self.reader).poll_read(cx, &mut self.chunk) {
+                Poll::Ready(Ok(0)) => {
+                    self.eof = true;
+                    self.parser.finish();
+                    self.read.finish();
+                }
+                Poll::Ready(Ok(len)) => {
+                    self.parser.feed(&self.chunk[..len]);
+                    self.read.feed(&self.chunk[..len]);
+                }
+                Poll::Ready(Err(ref err)) if err.kind() == io::ErrorKind::Interrupted => {}
+                Poll::Ready(Err(err)) => {
+                    self.done = true;
+                    return Poll::Ready(Some((Err(Error::io(err)), self.offset)));
+                }
+                Poll::Pending => return Poll::Pending,
+            }
+        }
+    }
+
+    /// Deserializes what the parser has read all of, or returns `None` if the
+    /// deserializer needs more input than that.
+    ///
+    /// The deserializer reads the input again rather than the events, so that
+    /// its errors are the same as with `from_reader`. A syntax error of the
+    /// parser is only reported if the deserializer does not find one.
+    fn deserialize(&mut self) -> Option<Option<(Result<T>, usize)>> {
+        let checkpoint = self.read.checkpoint();
+        let next = if self.multiple {
+            let mut values = StreamDeserializer::<_, T>::new(&mut self.read);
+            let next = values.next();
+            self.offset = values.byte_offset();
+            next
+        } else if self.value.is_none() {
+            match T::deserialize(&mut Deserializer::new(&mut self.read)) {
+                Ok(value) => {
+                    self.value = Some(value);
+                    None
+                }
+                Err(err) => Some(Err(err)),
+            }
+        } else {
+            match Deserializer::new(&mut self.read).end() {
+                Ok(()) => None,
+                Err(err) => Some(Err(err)),
+            }
+        };
+
+        if self.read.starved() {
+            self.read.rewind(checkpoint);
+            return match self.end.take() {
+                Some(Err(err)) => {
+                    self.done = true;
+                    Some(Some((Err(err), self.offset)))
+                }
+                _ => None,
+            };
+        }
+        self.complete = self.complete.saturating_sub(1);
+        match next {
+            Some(Ok(value)) => Some(Some((Ok(value), self.offset))),
+            Some(Err(err)) => {
+                self.done = true;
+                Some(Some((Err(err), self.offset)))
+            }
+            None => {
+                if self.value.is_some() && self.end.is_none() {
+                    // The rest of the input has to be read first.
+                    return None;
+                }
+                self.done = true;
+                match self.end.take() {
+                    Some(Err(err)) => Some(Some((Err(err), self.offset))),
+                    _ => Some(self.value.take().map(|value| (Ok(value), self.offset))),
+                }
+            }
+        }
+    }
+}
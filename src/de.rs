@@ -8,17 +8,27 @@
 
 //! Deserialize JSON data to a Rust data structure.
 
-use std::{i32, u64};
+use std::{cmp, i32, result, str, u64, vec};
+use std::collections::HashMap;
+use std::collections::hash_map::Entry;
 use std::io;
 use std::marker::PhantomData;
+use std::mem;
+use std::ops::Range;
 
-use serde::de::{self, Expected, Unexpected};
+use serde::de::{self, Deserialize, Expected, Unexpected};
 
-use super::error::{Error, ErrorCode, Result};
+use super::error::{Error, ErrorCode, ErrorKind, Result, push_path_key};
 
-use read::{self, Reference};
+use read;
+use source_map::Span;
+use spanned;
+use syntax::TokenKind;
+use map::Map;
+use value::Value;
+use warning::{Warning, WarningKind};
 
-pub use read::{Read, IoRead, SliceRead, StrRead};
+pub use read::{Read, IoRead, Position, Reference, SliceRead, StrRead};
 
 //////////////////////////////////////////////////////////////////////////////
 
@@ -26,8 +36,113 @@ pub use read::{Read, IoRead, SliceRead, StrRead};
 pub struct Deserializer<R> {
     read: R,
     str_buf: Vec<u8>,
-    remaining_depth: u8,
+    /// How many more levels of arrays and objects may be entered, or `None`
+    /// if the recursion limit is disabled.
+    remaining_depth: Option<usize>,
     capture: bool,
+    /// Whether the next value is the root of the document, which Hjson allows
+    /// to be an object without braces.
+    root: bool,
+    profile: Profile,
+    duplicate_keys: Option<DuplicateKeys>,
+    /// Where the most recent number, literal or quoteless string ended, which
+    /// is before the current position if whitespace or comments following it
+    /// have been consumed already.
+    value_end: Option<usize>,
+    /// The warnings found so far, if they are being collected.
+    warnings: Option<Vec<Warning>>,
+    /// The path of the current value and the ignored members found so far, if
+    /// they are being collected.
+    ignored: Option<Ignored>,
+    /// Byte offset of the value or punctuation most recently found by
+    /// `parse_whitespace`, where the byte range of an error starts, and
+    /// whether it may be a number, literal or quoteless string.
+    token: (usize, bool),
+    /// The comments, member names and values found so far with their byte
+    /// ranges, if they are being recorded for the `syntax` module.
+    tokens: Option<Vec<(TokenKind, Range<usize>)>>,
+}
+
+/// The Hjson extensions to JSON that a `Deserializer` accepts.
+///
+/// Every extension is allowed by default. Input using an extension that is
+/// turned off fails with an error naming the extension, for example
+/// "comments are not allowed".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Profile {
+    /// `#`, `//` and `/* */` comments.
+    pub comments: bool,
+    /// Strings and member names without quotes.
+    pub quoteless_strings: bool,
+    /// Strings quoted with `'` instead of `"`.
+    pub single_quoted_strings: bool,
+    /// Multiline strings quoted with `'''`.
+    pub multiline_strings: bool,
+    /// Newlines instead of commas between array elements and object members.
+    pub optional_commas: bool,
+    /// A comma after the last array element or object member.
+    pub trailing_commas: bool,
+    /// A root object without `{` and `}`.
+    pub omitted_root_braces: bool,
+}
+
+impl Profile {
+    /// Accepts all of Hjson.
+    pub fn hjson() -> Self {
+        Profile {
+            comments: true,
+            quoteless_strings: true,
+            single_quoted_strings: true,
+            multiline_strings: true,
+            optional_commas: true,
+            trailing_commas: true,
+            omitted_root_braces: true,
+        }
+    }
+
+    /// Accepts strict JSON only.
+    pub fn json() -> Self {
+        Profile {
+            comments: false,
+            quoteless_strings: false,
+            single_quoted_strings: false,
+            multiline_strings: false,
+            optional_commas: false,
+            trailing_commas: false,
+            omitted_root_braces: false,
+        }
+    }
+
+    /// Accepts JSON with comments and trailing commas, as used by many editor
+    /// configuration files.
+    pub fn jsonc() -> Self {
+        Profile {
+            comments: true,
+            trailing_commas: true,
+            ..Profile::json()
+        }
+    }
+}
+
+impl Default for Profile {
+    fn default() -> Self {
+        Profile::hjson()
+    }
+}
+
+/// What a `Deserializer` does with an object that has the same key more than
+/// once.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DuplicateKeys {
+    /// Fail with an error pointing at both occurrences of the key.
+    Error,
+    /// Use the value of the first occurrence and skip the others.
+    KeepFirst,
+    /// Use the value of the last occurrence, in the place of the first one.
+    KeepLast,
+    /// Collect the values of all occurrences into an array, in the place of
+    /// the first one. Keys that occur only once keep their value as is.
+    Collect,
 }
 
 impl<'de, R> Deserializer<R>
@@ -46,8 +161,107 @@ where
         Deserializer {
             read: read,
             str_buf: Vec::with_capacity(128),
-            remaining_depth: 128,
+            remaining_depth: Some(128),
             capture: false,
+            root: true,
+            profile: Profile::hjson(),
+            duplicate_keys: None,
+            value_end: None,
+            warnings: None,
+            ignored: None,
+            token: (0, false),
+            tokens: None,
+        }
+    }
+
+    /// Sets which Hjson extensions are accepted. See `Profile` for details.
+    pub fn set_profile(&mut self, profile: Profile) {
+        self.profile = profile;
+    }
+
+    /// Sets how objects with duplicate keys are handled.
+    ///
+    /// By default every member is passed on as it is, so maps end up with the
+    /// last value of a key while structs fail with a "duplicate field" error.
+    /// The `KeepLast` and `Collect` policies read each object into a `Value`
+    /// before handing it out, so borrowed strings cannot be deserialized from
+    /// objects under those policies.
+    pub fn set_duplicate_keys(&mut self, policy: DuplicateKeys) {
+        self.duplicate_keys = Some(policy);
+    }
+
+    /// Sets how deeply arrays and objects may be nested, 128 levels by
+    /// default. Deeper input fails with a "recursion limit exceeded" error
+    /// instead of overflowing the stack.
+    pub fn set_recursion_limit(&mut self, limit: usize) {
+        self.remaining_depth = Some(limit);
+    }
+
+    /// Parses arrays and objects nested arbitrarily deep.
+    ///
+    /// Every level of nesting uses some stack space, so without a limit a
+    /// deeply nested document can overflow the stack. Enable the
+    /// `unbounded_depth` feature to have the deserializer grow the stack on
+    /// the heap as needed. Note that the `Deserialize` and `Drop`
+    /// implementations of the output type may recurse as well; this only
+    /// protects the deserializer itself.
+    pub fn disable_recursion_limit(&mut self) {
+        self.remaining_depth = None;
+    }
+
+    /// Starts recording input that is valid but probably not what was meant,
+    /// like a quoteless string `1.0.0` that looks like a number. See
+    /// `WarningKind` for what is recorded.
+    pub fn collect_warnings(&mut self) {
+        if self.warnings.is_none() {
+            self.warnings = Some(Vec::new());
+        }
+    }
+
+    /// The warnings recorded so far, in the order they were found.
+    pub fn warnings(&self) -> &[Warning] {
+        match self.warnings {
+            Some(ref warnings) => warnings,
+            None => &[],
+        }
+    }
+
+    /// Returns the warnings recorded so far and forgets them.
+    pub fn take_warnings(&mut self) -> Vec<Warning> {
+        match self.warnings {
+            Some(ref mut warnings) => mem::replace(warnings, Vec::new()),
+            None => Vec::new(),
+        }
+    }
+
+    /// Starts recording the object members that are skipped because the type
+    /// being deserialized has no field for them. Each is recorded with its
+    /// path, in the format of `Error::path`, and the span of its name.
+    pub fn collect_ignored(&mut self) {
+        if self.ignored.is_none() {
+            self.ignored = Some(Ignored {
+                path: String::new(),
+                member: None,
+                keys: Vec::new(),
+                buffering: 0,
+                buffered: HashMap::new(),
+            });
+        }
+    }
+
+    /// The ignored members recorded so far, in the order they were found.
+    pub fn ignored(&self) -> &[(String, Span)] {
+        match self.ignored {
+            Some(ref ignored) => &ignored.keys,
+            None => &[],
+        }
+    }
+
+    /// Returns the ignored members recorded so far and forgets them.
+    pub fn take_ignored(&mut self) -> Vec<(String, Span)> {
+        match self.ignored {
+            Some(ref mut ignored) => mem::replace(&mut ignored.keys, Vec::new()),
+            None => Vec::new(),
         }
     }
 }
@@ -120,14 +334,64 @@ impl<'de, R: Read<'de>> Deserializer<R> {
         }
     }
 
+    /// Parses a `Value`, carrying on after syntax errors to find all of them
+    /// at once.
+    ///
+    /// After an error the parser skips ahead to the next newline, `,` or
+    /// closing bracket and resumes from there. Values that could not be parsed
+    /// are `Value::Null` in the result, and the JSON Pointers to them are
+    /// returned to tell them apart from nulls in the input. Members whose name
+    /// could not be parsed are left out. Parsing stops at the first IO error.
+    ///
+    /// ```rust
+    /// use serde_hjson::Deserializer;
+    ///
+    /// let mut de = Deserializer::from_str("
+    ///     name: example
+    ///     ports: [80,, 443]
+    ///     debug: true
+    ///     timeout: {seconds 30}
+    /// ");
+    /// let recovered = de.parse_value_recovering();
+    ///
+    /// let value = &recovered.value;
+    /// assert_eq!(value["name"], "example");
+    /// assert_eq!(value["ports"][1], 443);
+    /// assert_eq!(value["debug"], true);
+    /// assert!(value["timeout"].as_object().unwrap().is_empty());
+    ///
+    /// let errors: Vec<_> = recovered.errors.iter().map(ToString::to_string).collect();
+    /// assert_eq!(
+    ///     errors,
+    ///     vec!["extra comma at line 3 column 16", "expected `:` at line 5 column 23"]
+    /// );
+    /// ```
+    pub fn parse_value_recovering(&mut self) -> Recovered {
+        let mut recovery = Recovery {
+            errors: Vec::new(),
+            closers: Vec::new(),
+            failed: false,
+            pointer: String::new(),
+            placeholders: Vec::new(),
+        };
+        let value = self.recover_root(&mut recovery);
+        Recovered {
+            value: value,
+            errors: recovery.errors,
+            placeholders: recovery.placeholders,
+        }
+    }
+
     /// Turn a JSON deserializer into an iterator over values of type T.
-    pub fn into_iter<T>(self) -> StreamDeserializer<'de, R, T>
+    pub fn into_iter<T>(mut self) -> StreamDeserializer<'de, R, T>
     where
         T: de::Deserialize<'de>,
     {
         // This cannot be an implementation of std::iter::IntoIterator because
         // we need the caller to choose what T is.
         let offset = self.read.byte_offset();
+        // A braceless root object would swallow every following value.
+        self.root = false;
         StreamDeserializer {
             de: self,
             offset: offset,
@@ -168,88 +432,57 @@ impl<'de, R: Read<'de>> Deserializer<R> {
         Ok(try!(self.next_char()).unwrap_or(b'\x00'))
     }
 
-    /// Error caused by a byte from next_char().
+    /// Error caused by a byte from next_char(). Its byte range spans the
+    /// value or punctuation that was being parsed up to that byte.
     #[cold]
     fn error(&self, reason: ErrorCode) -> Error {
         let pos = self.read.position();
-        Error::syntax(reason, pos.line, pos.column)
+        let (start, scalar) = self.token;
+        let end = match self.value_end {
+            // Leave out whitespace and comments that were consumed after a
+            // number, literal or quoteless string.
+            Some(end) if scalar && start <= end && end <= pos.end => end,
+            _ => pos.end,
+        };
+        // The error may be found before the first byte of the value has been
+        // consumed, like an array where a number was expected.
+        Error::syntax_at(reason, pos, start..cmp::max(end, start + 1))
     }
 
-    /// Error caused by a byte from peek().
+    /// Error caused by a byte from peek(). Its byte range spans that byte's
+    /// character, or is empty at the end of the input.
     #[cold]
     fn peek_error(&self, reason: ErrorCode) -> Error {
         let pos = self.read.peek_position();
-        Error::syntax(reason, pos.line, pos.column)
+        let offset = self.read.byte_offset();
+        if pos.end > offset {
+            Error::syntax_at(reason, pos, pos.start..pos.end)
+        } else {
+            Error::syntax_at(reason, pos, offset..offset)
+        }
     }
 
     /// Returns the first non-whitespace byte without consuming it, or `None` if
     /// EOF is encountered.
     fn parse_whitespace(&mut self) -> Result<Option<u8>> {
         debug!(parse_whitespace);
-        let mut line_comment = false;
-        let mut multiline_comment = false;
-
         loop {
-            if line_comment {
-                match try!(self.peek()) {
-                    Some(b'\n') | Some(b'\r') => {
-                        self.eat_char();
-                        line_comment = false;
-                    }
-                    Some(_) => {
-                        self.eat_char();
-                    }
-                    None => {
-                        return Ok(None);
-                    }
-                }
-            } else if multiline_comment {
-                match try!(self.peek()) {
-                    Some(b'*') => {
-                        self.eat_char();
-
-                        if let Some(b'/') = try!(self.peek()) {
-                            self.eat_char();
-                            multiline_comment = false;
-                        }
-                    }
-                    Some(_) => {
-                        self.eat_char();
-                    }
-                    None => {
-                        return Ok(None);
-                    }
+            match try!(self.peek()) {
+                Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
+                    self.eat_char();
                 }
-            } else {
-                match try!(self.peek()) {
-                    Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') => {
-                        self.eat_char();
-                    }
-                    Some(b'#') => {
-                        self.eat_char();
-                        line_comment = true;
-                    }
-                    Some(b'/') => {
-                        self.eat_char();
-
-                        match try!(self.peek()) {
-                            Some(b'/') => {
-                                self.eat_char();
-                                line_comment = true;
-                            }
-                            Some(b'*') => {
-                                self.eat_char();
-                                multiline_comment = true;
-                            }
-                            other => {
-                                return Ok(other);
-                            }
-                        }
-                    }
-                    other => {
+                Some(b'#') | Some(b'/') => {
+                    if let Some(other) = try!(self.skip_comment()) {
                         return Ok(other);
                     }
                 }
+                Some(other) => {
+                    self.token = (self.read.byte_offset(), other != b'[' && other != b'{');
+                    return Ok(Some(other));
+                }
+                None => {
+                    return Ok(None);
+                }
             }
         }
     }
@@ -260,74 +493,23 @@ impl<'de, R: Read<'de>> Deserializer<R> {
     /// reference parameter
     fn parse_whitespace_get_newline(&mut self, had_newline: &mut bool) -> Result<Option<u8>> {
         debug!(parse_whitespace_get_newline);
-        let mut line_comment = false;
-        let mut multiline_comment = false;
-
         loop {
-            if line_comment {
-                match try!(self.peek()) {
-                    Some(b'\n') | Some(b'\r') => {
-                        self.eat_char();
-                        line_comment = false;
-                    }
-                    Some(_) => {
-                        self.eat_char();
-                    }
-                    None => {
-                        return Ok(None);
-                    }
-                }
-            } else if multiline_comment {
-                match try!(self.peek()) {
-                    Some(b'*') => {
-                        self.eat_char();
-
-                        if let Some(b'/') = try!(self.peek()) {
-                            self.eat_char();
-                            multiline_comment = false;
-                        }
-                    }
-                    Some(_) => {
-                        self.eat_char();
-                    }
-                    None => {
-                        return Ok(None);
-                    }
+            match try!(self.peek()) {
+                Some(b' ') | Some(b'\t') => {
+                    self.eat_char();
+                },
+                Some(b'\n') | Some(b'\r') => {
+                    self.eat_char();
+                    *had_newline = true;
                 }
-            } else {
-                match try!(self.peek()) {
-                    Some(b' ') | Some(b'\t') => {
-                        self.eat_char();
-                    },
-                    Some(b'\n') | Some(b'\r') => {
-                        self.eat_char();
-                        *had_newline = true;
-                    }
-                    Some(b'#') => {
-                        self.eat_char();
-                        line_comment = true;
-                    }
-                    Some(b'/') => {
-                        self.eat_char();
-
-                        match try!(self.peek()) {
-                            Some(b'/') => {
-                                self.eat_char();
-                                line_comment = true;
-                            }
-                            Some(b'*') => {
-                                self.eat_char();
-                                multiline_comment = true;
-                            }
-                            other => {
-                                return Ok(other);
-                            }
-                        }
-                    }
-                    other => {
+                Some(b'#') | Some(b'/') => {
+                    if let Some(other) = try!(self.skip_comment()) {
                         return Ok(other);
                     }
                 }
+                other => {
+                    return Ok(other);
+                }
             }
         }
     }
@@ -338,79 +520,303 @@ impl<'de, R: Read<'de>> Deserializer<R> {
     /// reference parameter
     fn parse_whitespace_until_newline(&mut self, had_newline: &mut bool) -> Result<Option<u8>> {
         debug!(parse_whitespace_until_newline);
-        let mut line_comment = false;
-        let mut multiline_comment = false;
-
+        self.value_end = Some(self.read.byte_offset());
         let mut prev = None;
 
         loop {
-            if line_comment {
-                match try!(self.peek()) {
-                    Some(b'\n') | Some(b'\r') => {
-                        *had_newline = true;
-                        return Ok(prev);
-                    }
-                    Some(_) => {
-                        self.eat_char();
-                    }
-                    None => {
-                        return Ok(None);
+            match try!(self.peek()) {
+                Some(b' ') | Some(b'\t') => {
+                    self.eat_char();
+                },
+                Some(b'\n') | Some(b'\r') => {
+                    *had_newline = true;
+                    return Ok(prev);
+                }
+                Some(b'#') | Some(b'/') => {
+                    if let Some(other) = try!(self.skip_comment()) {
+                        return Ok(other);
                     }
                 }
-            } else if multiline_comment {
+                other => {
+                    return Ok(other);
+                }
+            }
+
+            prev = try!(self.peek());
+        }
+    }
+
+    /// Skips the comment starting with the `#` or `/` that is the next byte. A
+    /// line comment ends before its newline, and a block comment without an
+    /// end runs to the end of the input.
+    ///
+    /// A `/` that does not start a comment is consumed all the same, and the
+    /// byte after it is returned.
+    fn skip_comment(&mut self) -> Result<Option<Option<u8>>> {
+        let start = self.read.byte_offset();
+        let block = match try!(self.peek()) {
+            Some(b'/') => {
+                self.eat_char();
                 match try!(self.peek()) {
-                    Some(b'*') => {
-                        self.eat_char();
+                    Some(b'/') => false,
+                    Some(b'*') => true,
+                    other => return Ok(Some(other)),
+                }
+            }
+            _ => false,
+        };
+        try!(self.check_comment());
+        self.eat_char();
 
+        if block {
+            loop {
+                match try!(self.next_char()) {
+                    Some(b'*') => {
                         if let Some(b'/') = try!(self.peek()) {
                             self.eat_char();
-                            multiline_comment = false;
+                            self.record(TokenKind::Comment, start..self.read.byte_offset());
+                            return Ok(None);
                         }
                     }
-                    Some(_) => {
-                        self.eat_char();
-                    }
+                    Some(_) => {}
                     None => {
+                        self.record(TokenKind::Error, start..self.read.byte_offset());
                         return Ok(None);
                     }
                 }
-            } else {
-                match try!(self.peek()) {
-                    Some(b' ') | Some(b'\t') => {
-                        self.eat_char();
-                    },
-                    Some(b'\n') | Some(b'\r') => {
-                        *had_newline = true;
-                        return Ok(prev);
-                    }
-                    Some(b'#') => {
-                        self.eat_char();
-                        line_comment = true;
-                    }
-                    Some(b'/') => {
-                        self.eat_char();
+            }
+        }
 
-                        match try!(self.peek()) {
-                            Some(b'/') => {
-                                self.eat_char();
-                                line_comment = true;
-                            }
-                            Some(b'*') => {
-                                self.eat_char();
-                                multiline_comment = true;
-                            }
-                            other => {
-                                return Ok(other);
-                            }
-                        }
-                    }
-                    other => {
-                        return Ok(other);
-                    }
+        loop {
+            match try!(self.peek()) {
+                Some(b'\n') | Some(b'\r') | None => {
+                    self.record(TokenKind::Comment, start..self.read.byte_offset());
+                    return Ok(None);
                 }
+                Some(_) => self.eat_char(),
             }
+        }
+    }
 
-            prev = try!(self.peek());
+    /// Records a token while tokens are being recorded for the `syntax`
+    /// module.
+    fn record(&mut self, kind: TokenKind, range: Range<usize>) {
+        if let Some(ref mut tokens) = self.tokens {
+            tokens.push((kind, range));
+        }
+    }
+
+    /// Records a value that is not an array or object, which started at
+    /// `start`. Strings are recorded as quoteless, the `syntax` module tells
+    /// them apart by their quotes.
+    fn record_scalar(&mut self, start: usize, value: &Value) {
+        if self.tokens.is_none() {
+            return;
+        }
+        let kind = match *value {
+            Value::Null | Value::Bool(_) => TokenKind::Literal,
+            Value::Number(_) => TokenKind::Number,
+            _ => TokenKind::QuotelessString,
+        };
+        // Leave out whitespace and comments that were consumed after a
+        // number, literal or quoteless string.
+        let end = match self.value_end {
+            Some(end) if end >= start => end,
+            _ => self.read.byte_offset(),
+        };
+        self.record(kind, start..end);
+    }
+
+    /// Fails unless the profile allows comments.
+    fn check_comment(&self) -> Result<()> {
+        if self.profile.comments {
+            Ok(())
+        } else {
+            Err(self.peek_error(ErrorCode::CommentNotAllowed))
+        }
+    }
+
+    /// Fails unless the profile allows quoteless strings.
+    fn check_quoteless(&self) -> Result<()> {
+        if self.profile.quoteless_strings {
+            Ok(())
+        } else {
+            Err(self.peek_error(ErrorCode::QuotelessStringNotAllowed))
+        }
+    }
+
+    /// Fails if the comma that was just consumed is directly followed by
+    /// `end` and the profile does not allow trailing commas. `end` is `None`
+    /// for a root object without braces.
+    fn check_trailing_comma(&mut self, end: Option<u8>) -> Result<()> {
+        if !self.profile.trailing_commas && try!(self.parse_whitespace()) == end {
+            Err(self.peek_error(ErrorCode::TrailingCommaNotAllowed))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Whether a value starting with `peek` has to be checked for being a
+    /// root object without braces.
+    fn at_braceless_root(&self, peek: u8) -> bool {
+        self.root && self.profile.omitted_root_braces &&
+            match peek {
+                b'{' | b'[' => false,
+                b'"' | b'\'' => true,
+                _ => self.profile.quoteless_strings,
+            }
+    }
+
+    /// Parses a quoteless string until the end of the line.
+    fn parse_none_str<'s>(&'s mut self) -> Result<Reference<'de, 's, str>> {
+        try!(self.check_quoteless());
+        self.str_buf.clear();
+        let start = self.read.byte_offset();
+        if self.warnings.is_some() {
+            return self.parse_none_str_warning(start);
+        }
+        let s = try!(self.read.parse_none_str(&mut self.str_buf));
+        // Trailing whitespace is not part of the string.
+        self.value_end = Some(start + s.len());
+        Ok(s)
+    }
+
+    /// Like `parse_none_str` while collecting warnings. A string copied into
+    /// the scratch space is moved out of it so that the reader can tell how
+    /// much whitespace was dropped.
+    fn parse_none_str_warning<'s>(&'s mut self, start: usize) -> Result<Reference<'de, 's, str>> {
+        let position = self.read.peek_position();
+        let (borrowed, copied) = match try!(self.read.parse_none_str(&mut self.str_buf)) {
+            Reference::Borrowed(b) => (Some(b), String::new()),
+            Reference::Copied(c) => (None, String::from(c)),
+        };
+        let s = borrowed.unwrap_or(&copied);
+        self.value_end = Some(start + s.len());
+        let len = self.read.byte_offset() - start;
+        self.warn_quoteless(s, len, position);
+        self.str_reference(borrowed, copied)
+    }
+
+    /// The string to return from parsing while collecting warnings, which was
+    /// either borrowed from the input or copied.
+    fn str_reference<'s>(
+        &'s mut self,
+        borrowed: Option<&'de str>,
+        copied: String,
+    ) -> Result<Reference<'de, 's, str>> {
+        match borrowed {
+            Some(b) => Ok(Reference::Borrowed(b)),
+            None => {
+                self.str_buf = copied.into_bytes();
+                // The bytes came from a str.
+                Ok(Reference::Copied(unsafe { str::from_utf8_unchecked(&self.str_buf) }))
+            }
+        }
+    }
+
+    /// Records the warnings about a quoteless string on a single line, which
+    /// spans `len` bytes from `position` before its trailing whitespace was
+    /// removed.
+    fn warn_quoteless(&mut self, s: &str, len: usize, position: Position) {
+        if self.profile.comments {
+            // Only a `#` or `//` after whitespace looks like it was meant to
+            // start a comment, unlike in `C#` or `http://`.
+            let bytes = s.as_bytes();
+            let comment = (1..bytes.len()).find(|&i| {
+                (bytes[i - 1] == b' ' || bytes[i - 1] == b'\t') &&
+                    (bytes[i] == b'#' || bytes[i..].starts_with(b"//"))
+            });
+            if let Some(i) = comment {
+                let kind = WarningKind::CommentInQuotelessString;
+                self.warn(kind, position.line, position.column + i);
+            }
+        }
+        if len > s.len() {
+            let kind = WarningKind::TrailingWhitespace;
+            self.warn(kind, position.line, position.column + s.len());
+        }
+    }
+
+    fn warn(&mut self, kind: WarningKind, line: usize, column: usize) {
+        if let Some(ref mut warnings) = self.warnings {
+            warnings.push(Warning::new(kind, line, column));
+        }
+    }
+
+    /// Parses a quoteless member name.
+    fn parse_member_name<'s>(&'s mut self) -> Result<Reference<'de, 's, str>> {
+        try!(self.check_quoteless());
+        self.str_buf.clear();
+        self.read.parse_member_name(&mut self.str_buf)
+    }
+
+    /// Assumes the next byte is an apostrophe. Parses a single quoted or a
+    /// multiline string.
+    fn parse_single_str<'s>(&'s mut self) -> Result<Reference<'de, 's, str>> {
+        self.eat_char();
+        self.str_buf.clear();
+        // Column of the apostrophe counted from 0.
+        let col = self.read.position().column - 1;
+        let start = self.read.position();
+        let two_apostrophes = try!(self.peek()) == Some(b'\'');
+
+        if self.warnings.is_some() {
+            return self.parse_single_str_warning(col, start, two_apostrophes);
+        }
+        let string = try!(self.read.parse_single_str(&mut self.str_buf, col));
+        match single_str_error(&self.profile, two_apostrophes, string.is_empty()) {
+            Some(code) => Err(Error::syntax_at(code, start, start.start..start.end)),
+            None => Ok(string),
+        }
+    }
+
+    /// Like `parse_single_str` while collecting warnings. A string copied
+    /// into the scratch space is moved out of it so that the reader can tell
+    /// whether its indentation had tabs.
+    fn parse_single_str_warning<'s>(
+        &'s mut self,
+        col: usize,
+        start: Position,
+        two_apostrophes: bool,
+    ) -> Result<Reference<'de, 's, str>> {
+        let (borrowed, copied) = match try!(self.read.parse_single_str(&mut self.str_buf, col)) {
+            Reference::Borrowed(b) => (Some(b), String::new()),
+            Reference::Copied(c) => (None, String::from(c)),
+        };
+        let empty = borrowed.unwrap_or(&copied).is_empty();
+        if let Some(code) = single_str_error(&self.profile, two_apostrophes, empty) {
+            return Err(Error::syntax_at(code, start, start.start..start.end));
+        }
+        if let Some(tab) = self.read.take_indent_tab() {
+            self.warn(WarningKind::TabInMultilineIndent, tab.line, tab.column);
+        }
+        self.str_reference(borrowed, copied)
+    }
+
+    /// Like `parse_single_str`, but returns the raw bytes.
+    fn parse_single_str_raw<'s>(&'s mut self) -> Result<Reference<'de, 's, [u8]>> {
+        self.eat_char();
+        self.str_buf.clear();
+        // Column of the apostrophe counted from 0.
+        let col = self.read.position().column - 1;
+        let start = self.read.position();
+        let two_apostrophes = try!(self.peek()) == Some(b'\'');
+
+        let bytes = try!(self.read.parse_single_str_raw(&mut self.str_buf, col));
+        match single_str_error(&self.profile, two_apostrophes, bytes.is_empty()) {
+            Some(code) => Err(Error::syntax_at(code, start, start.start..start.end)),
+            None => Ok(bytes),
+        }
+    }
+
+    /// Assumes the next byte is an apostrophe. Parses a single quoted or a
+    /// multiline string but discards the data.
+    fn ignore_single_str(&mut self) -> Result<()> {
+        if self.profile.single_quoted_strings && self.profile.multiline_strings {
+            self.eat_char();
+            self.read.ignore_single_str()
+        } else {
+            self.parse_single_str().map(|_| ())
         }
     }
 
@@ -461,13 +867,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 }
             }
             b'\'' => {
-                self.eat_char();
-                self.str_buf.clear();
-                let col;
-                {
-                    col = self.read.peek_position().column - 1;
-                }
-                match self.read.parse_single_str(&mut self.str_buf, col) {
+                match self.parse_single_str() {
                     Ok(s) => de::Error::invalid_type(Unexpected::Str(&s), exp),
                     Err(err) => return err,
                 }
@@ -503,8 +903,7 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
             b'0'...b'9' => try!(self.parse_integer(true)).visit(visitor),
             _ => {
-                self.str_buf.clear();
-                match try!(self.read.parse_none_str(&mut self.str_buf)) {
+                match try!(self.parse_none_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
@@ -517,11 +916,60 @@ impl<'de, R: Read<'de>> Deserializer<R> {
         }
     }
 
+    /// Finishes a quoteless string whose start was captured while trying to
+    /// parse it as a literal or number.
+    fn visit_quoteless_rest<V>(&mut self, captured: String, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if self.warnings.is_none() {
+            return match try!(self.parse_none_str()) {
+                Reference::Borrowed(s) => visitor.visit_str(&(captured + s)),
+                Reference::Copied(s) => visitor.visit_str(&(captured + &s)),
+            };
+        }
+
+        let position = self.read.peek_position();
+        let count = self.warnings().len();
+        let mut s = captured;
+        let len = s.len();
+        s.push_str(&try!(self.parse_none_str()));
+        // The position is on the next line if the string ends with the
+        // captured part, which then cannot be located.
+        if looks_like_literal(&s) && position.column > len {
+            let warning = Warning::new(
+                WarningKind::QuotelessLiteral,
+                position.line,
+                position.column - len,
+            );
+            // Keep the warnings in order of position.
+            if let Some(ref mut warnings) = self.warnings {
+                warnings.insert(count, warning);
+            }
+        }
+        visitor.visit_str(&s)
+    }
+
     #[cold]
     fn fix_position(&self, err: Error) -> Error {
         err.fix_position(move |code| self.error(code))
     }
 
+    /// Like `fix_position` for an error about the member or variant name that
+    /// was just read from the bytes in `range`, like an unknown field.
+    #[cold]
+    fn fix_key_position(&self, err: Error, range: Range<usize>) -> Error {
+        err.fix_position(move |code| Error::syntax_at(code, self.read.position(), range))
+    }
+
+    /// Reads a member or variant name like `parse_key`, along with the byte
+    /// range it was read from.
+    fn parse_key_range(&mut self) -> Result<(ParsedKey<'de>, Range<usize>)> {
+        let start = self.read.byte_offset();
+        let key = try!(self.parse_key());
+        Ok((key, start..self.read.byte_offset()))
+    }
+
     fn parse_ident(&mut self, ident: &[u8]) -> Result<()> {
         debug!(parse_ident);
         for c in ident {
@@ -530,6 +978,12 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
         }
 
+        self.parse_value_end()
+    }
+
+    /// Makes sure that a literal or a number is the whole value rather than
+    /// the start of a quoteless string.
+    fn parse_value_end(&mut self) -> Result<()> {
         // Consume until newline, comma, or eof
         let mut had_newline = false;
 
@@ -541,7 +995,8 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             match c {
                 Some(b',') |
                 Some(b']') |
-                Some(b'}') => Ok(()),
+                Some(b'}') |
+                None => Ok(()),
                 _ => Err(self.error(ErrorCode::UnexpectedCharacter)),
             }
         }
@@ -640,21 +1095,8 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
         });
 
-        // Consume until newline, comma, or eof
-        let mut had_newline = false;
-
-        let c = try!(self.parse_whitespace_until_newline(&mut had_newline));
-
-        if had_newline {
-            ret
-        } else {
-            match c {
-                Some(b',') |
-                Some(b']') |
-                Some(b'}') => ret,
-                _ => Err(self.error(ErrorCode::UnexpectedCharacter)),
-            }
-        }
+        try!(self.parse_value_end());
+        ret
     }
 
     fn parse_decimal(
@@ -816,7 +1258,10 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 
     fn end_seq(&mut self) -> Result<()> {
         debug!(end_seq);
+        // Errors about the array as a whole span all of it.
+        let token = self.token;
         let ch = try!(self.parse_whitespace());
+        self.token = token;
         match ch {
             Some(b']') => {
                 self.eat_char();
@@ -824,7 +1269,9 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
             Some(b',') => {
                 self.eat_char();
-                match self.parse_whitespace() {
+                let ch = self.parse_whitespace();
+                self.token = token;
+                match ch {
                     Ok(Some(b']')) => Err(self.peek_error(ErrorCode::ExtraComma)),
                     _ => Err(self.peek_error(ErrorCode::TrailingCharacters)), // This shouldn't be possible
                 }
@@ -836,7 +1283,11 @@ impl<'de, R: Read<'de>> Deserializer<R> {
 
     fn end_map(&mut self) -> Result<()> {
         debug!(end_map);
-        match try!(self.parse_whitespace()) {
+        // Errors about the object as a whole span all of it.
+        let token = self.token;
+        let ch = try!(self.parse_whitespace());
+        self.token = token;
+        match ch {
             Some(b'}') => {
                 self.eat_char();
                 Ok(())
@@ -847,6 +1298,307 @@ impl<'de, R: Read<'de>> Deserializer<R> {
         }
     }
 
+    /// Calls `f` one level of nesting deeper, failing if that would exceed
+    /// the recursion limit.
+    fn recurse<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        let token = self.token;
+        let ret = match self.remaining_depth {
+            Some(0) => {
+                return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
+            }
+            Some(depth) => {
+                self.remaining_depth = Some(depth - 1);
+                let ret = f(self);
+                self.remaining_depth = Some(depth);
+                ret
+            }
+            None => self.grow_stack(f),
+        };
+
+        // Errors about the array or object as a whole span all of it.
+        self.token = (token.0, false);
+        ret
+    }
+
+    #[cfg(feature = "unbounded_depth")]
+    fn grow_stack<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        // Make sure there is at least 64KiB of stack left before going deeper,
+        // allocating 1MiB more otherwise.
+        stacker::maybe_grow(64 * 1024, 1024 * 1024, move || f(self))
+    }
+
+    #[cfg(not(feature = "unbounded_depth"))]
+    fn grow_stack<T, F>(&mut self, f: F) -> Result<T>
+    where
+        F: FnOnce(&mut Self) -> Result<T>,
+    {
+        f(self)
+    }
+
+    /// Parses the first value of a document that does not start with a
+    /// bracket. Hjson allows the braces around the root object to be omitted,
+    /// which is detected by a member name followed by a `:`.
+    fn deserialize_root<V>(&mut self, peek: u8, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        debug!(deserialize_root);
+        self.root = false;
+
+        let position = self.read.peek_position();
+        let (key, range) = match peek {
+            b'"' | b'\'' => try!(self.parse_key_range()),
+            _ => {
+                return self.deserialize_root_quoteless(position, visitor);
+            }
+        };
+
+        match try!(self.parse_whitespace()) {
+            Some(b':') => self.visit_root_map(key, position, range, visitor),
+            _ => {
+                match key {
+                    ParsedKey::Borrowed(s) => visitor.visit_borrowed_str(s),
+                    ParsedKey::Owned(s) => visitor.visit_string(s),
+                }
+            }
+        }
+    }
+
+    /// Like `deserialize_root` for a document starting with a member name or a
+    /// quoteless value.
+    fn deserialize_root_quoteless<V>(&mut self, position: Position, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        debug!(deserialize_root_quoteless);
+        match try!(self.parse_root_start()) {
+            RootWord::Name(name) => {
+                let range = position.start..position.start + name.len();
+                self.visit_root_map(ParsedKey::Owned(name), position, range, visitor)
+            }
+            RootWord::Value { start, position, captured, word_len } => {
+                self.visit_root_quoteless(start, position, captured, word_len, visitor)
+            }
+        }
+    }
+
+    /// Reads the leading word of a document starting with a member name or a
+    /// quoteless value.
+    ///
+    /// Like in hjson-js, a document that starts with a member name but fails
+    /// to parse as an object without braces is a single value instead, like
+    /// `http://example.com` or `a:`. That is only possible if the input can be
+    /// read again, see `Read::mark`.
+    fn parse_root_start(&mut self) -> Result<RootWord> {
+        if !self.read.mark() {
+            return self.parse_root_word(true);
+        }
+        let word = match self.parse_root_word(true) {
+            Ok(RootWord::Name(name)) => self.check_root_name(name),
+            word => word,
+        };
+        self.read.unmark();
+        word
+    }
+
+    /// Finds out whether the member name that was read from the mark on
+    /// really starts an object without braces, and reads the leading word
+    /// again accordingly.
+    fn check_root_name(&mut self, name: String) -> Result<RootWord> {
+        // A document of more than one line can only be an object. Skipping
+        // the rest of the line fails at the end of the input, which is fine.
+        let _ = self.read.ignore_none_str();
+        let single_line = match self.parse_whitespace() {
+            Ok(None) => true,
+            _ => false,
+        };
+        self.read.rewind();
+        if !single_line {
+            return self.parse_root_word(true);
+        }
+
+        // Only input that is no Hjson at all is read another way, not one
+        // that goes beyond what the deserializer is set up to accept.
+        let object = match self.try_braceless_root(name) {
+            Ok(()) => true,
+            Err(err) => {
+                match err.kind() {
+                    ErrorKind::RecursionLimitExceeded | ErrorKind::DuplicateKey => true,
+                    kind => kind.code() < 100 || kind.code() >= 300,
+                }
+            }
+        };
+        self.read.rewind();
+        self.parse_root_word(object)
+    }
+
+    /// Parses the document from the mark on as an object without braces and
+    /// throws it away, leaving no trace in the warnings and the other things
+    /// collected along the way.
+    fn try_braceless_root(&mut self, name: String) -> Result<()> {
+        let warnings = self.warnings.take();
+        let ignored = self.ignored.take();
+        let tokens = self.tokens.take();
+
+        let position = self.read.peek_position();
+        let range = position.start..position.start + name.len();
+        let result = self.parse_root_word(true).and_then(|_| {
+            let visitor = de::IgnoredAny;
+            self.visit_root_map(ParsedKey::Owned(name), position, range, visitor)
+        });
+        let result = result.and_then(|_| self.end());
+
+        self.warnings = warnings;
+        self.ignored = ignored;
+        self.tokens = tokens;
+        result
+    }
+
+    /// Reads the leading word of a document starting with a member name or a
+    /// quoteless value. The word is captured so that it can still be
+    /// interpreted as a value if no `:` follows it, or if `names` is false.
+    fn parse_root_word(&mut self, names: bool) -> Result<RootWord> {
+        let start = self.read.byte_offset();
+        let position = self.read.peek_position();
+        self.str_buf.clear();
+        self.capture = true;
+
+        loop {
+            match try!(self.peek()) {
+                Some(b' ') | Some(b'\n') | Some(b'\t') | Some(b'\r') | Some(b':') | None => break,
+                Some(_) => self.eat_char(),
+            }
+        }
+        let word_len = self.str_buf.len();
+
+        while let Some(b' ') | Some(b'\t') = try!(self.peek()) {
+            self.eat_char();
+        }
+
+        self.capture = false;
+        let mut captured = match String::from_utf8(self.str_buf.split_off(0)) {
+            Ok(captured) => captured,
+            Err(_) => return Err(self.error(ErrorCode::InvalidUnicodeCodePoint)),
+        };
+
+        if names && word_len > 0 {
+            if let Some(b':') = try!(self.peek()) {
+                captured.truncate(word_len);
+                return Ok(RootWord::Name(captured));
+            }
+        }
+        Ok(RootWord::Value {
+            start: start,
+            position: position,
+            captured: captured,
+            word_len: word_len,
+        })
+    }
+
+    /// Finishes a root value that starts with a word that is not followed by a
+    /// `:`.
+    fn visit_root_quoteless<V>(
+        &mut self,
+        start: usize,
+        position: Position,
+        captured: String,
+        word_len: usize,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let rest = String::from(&*try!(self.read.parse_none_str(&mut self.str_buf)));
+
+        // A literal or a number may only be followed by a comment, anything
+        // else makes the whole line a quoteless string.
+        if rest.is_empty() ||
+            self.profile.comments &&
+                (rest.starts_with('#') || rest.starts_with("//") || rest.starts_with("/*"))
+        {
+            self.value_end = Some(start + word_len);
+            match &captured[..word_len] {
+                "true" => return visitor.visit_bool(true),
+                "false" => return visitor.visit_bool(false),
+                "null" => return visitor.visit_unit(),
+                word => {
+                    if let Some(number) = parse_number_str(word) {
+                        return number.visit(visitor);
+                    }
+                }
+            }
+        }
+
+        let mut line = captured;
+        line.push_str(&rest);
+        let len = line.trim_end().len();
+        line.truncate(len);
+        self.value_end = Some(start + len);
+        if self.warnings.is_some() {
+            if looks_like_literal(&line) {
+                self.warn(WarningKind::QuotelessLiteral, position.line, position.column);
+            }
+            let raw_len = self.read.byte_offset() - start;
+            self.warn_quoteless(&line, raw_len, position);
+        }
+        visitor.visit_string(line)
+    }
+
+    /// Passes the location of the next value to the visitor of a `Spanned`,
+    /// along with the value itself.
+    fn deserialize_spanned<V>(&mut self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        debug!(deserialize_spanned);
+        if try!(self.parse_whitespace()).is_none() {
+            return Err(self.peek_error(ErrorCode::EofWhileParsingValue));
+        }
+
+        let start = self.read.byte_offset();
+        let position = self.read.peek_position();
+        visitor.visit_map(SpannedAccess {
+            de: self,
+            field: 0,
+            start: start,
+            position: position,
+            end: start,
+        })
+    }
+
+    fn visit_root_map<V>(
+        &mut self,
+        key: ParsedKey<'de>,
+        position: Position,
+        range: Range<usize>,
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        debug!(visit_root_map);
+        self.recurse(move |de| visitor.visit_map(MapAccess::braceless(de, key, position, range)))
+    }
+
+    /// Reads a member name in any of the quoting styles.
+    fn parse_key(&mut self) -> Result<ParsedKey<'de>> {
+        match try!(self.peek_or_null()) {
+            b'"' => {
+                self.eat_char();
+                self.str_buf.clear();
+                Ok(ParsedKey::from(try!(self.read.parse_double_str(&mut self.str_buf))))
+            }
+            b'\'' => Ok(ParsedKey::from(try!(self.parse_single_str()))),
+            _ => Ok(ParsedKey::from(try!(self.parse_member_name()))),
+        }
+    }
+
     fn ignore_value(&mut self) -> Result<()> {
         debug!(ignore_value);
         let peek = match try!(self.parse_whitespace()) {
@@ -856,6 +1608,43 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
         };
 
+        match peek {
+            b'n' | b't' | b'f' | b'-' | b'0'...b'9' => {
+                // Like a value that is kept, a word that turns out not to be a
+                // literal or a number is the start of a quoteless string.
+                match self.ignore_literal_or_number(peek) {
+                    Err(_) if self.profile.quoteless_strings => self.read.ignore_none_str(),
+                    ret => ret,
+                }
+            }
+            b'"' => {
+                self.eat_char();
+                self.read.ignore_double_str()
+            }
+            b'\'' => {
+                self.ignore_single_str()
+            }
+            b'[' => {
+                self.recurse(|de| {
+                    de.eat_char();
+                    de.ignore_seq()
+                })
+            }
+            b'{' => {
+                self.recurse(|de| {
+                    de.eat_char();
+                    de.ignore_map()
+                })
+            }
+            _ => {
+                try!(self.check_quoteless());
+                self.read.ignore_none_str()
+            }
+        }
+    }
+
+    fn ignore_literal_or_number(&mut self, peek: u8) -> Result<()> {
+        debug!(ignore_literal_or_number);
         match peek {
             b'n' => {
                 self.eat_char();
@@ -871,43 +1660,12 @@ impl<'de, R: Read<'de>> Deserializer<R> {
             }
             b'-' => {
                 self.eat_char();
-                self.ignore_integer()
-            }
-            b'0'...b'9' => {
-                self.ignore_integer()
-            }
-            b'"' => {
-                self.eat_char();
-                self.read.ignore_double_str()
-            }
-            b'\'' => {
-                self.eat_char();
-                self.read.ignore_single_str()
-            }
-            b'[' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let res = self.ignore_seq();
-                self.remaining_depth += 1;
-                res
-            }
-            b'{' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let res = self.ignore_map();
-                self.remaining_depth += 1;
-                res
+                try!(self.ignore_integer());
+                self.parse_value_end()
             }
             _ => {
-                self.read.ignore_none_str()
+                try!(self.ignore_integer());
+                self.parse_value_end()
             }
         }
     }
@@ -1005,8 +1763,11 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 Some(ch) => {
                     if ch == b',' {
                         self.eat_char();
+                        try!(self.check_trailing_comma(Some(b']')));
                     } else if ch != b']' && !had_newline {
                         return Err(self.peek_error(ErrorCode::ExpectedListCommaOrEnd));
+                    } else if ch != b']' && !self.profile.optional_commas {
+                        return Err(self.peek_error(ErrorCode::MissingComma));
                     }
                 }
                 None => {
@@ -1038,10 +1799,10 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                     try!(self.read.ignore_double_str());
                 }
                 Some(b'\'') => {
-                    self.eat_char();
-                    try!(self.read.ignore_single_str());
+                    try!(self.ignore_single_str());
                 }
                 Some(_) => {
+                    try!(self.check_quoteless());
                     try!(self.read.ignore_member_name());
                 }
                 None => {
@@ -1068,8 +1829,11 @@ impl<'de, R: Read<'de>> Deserializer<R> {
                 Some(ch) => {
                     if ch == b',' {
                         self.eat_char();
+                        try!(self.check_trailing_comma(Some(b'}')));
                     } else if ch != b'}' && !had_newline {
                         return Err(self.peek_error(ErrorCode::ExpectedListCommaOrEnd));
+                    } else if ch != b'}' && !self.profile.optional_commas {
+                        return Err(self.peek_error(ErrorCode::MissingComma));
                     }
                 }
                 None => {
@@ -1114,6 +1878,97 @@ static POW10: [f64; 309] =
      1e290, 1e291, 1e292, 1e293, 1e294, 1e295, 1e296, 1e297, 1e298, 1e299,
      1e300, 1e301, 1e302, 1e303, 1e304, 1e305, 1e306, 1e307, 1e308];
 
+/// Checks a parsed `'` or `'''` string against the profile. A string starting
+/// with two apostrophes is either empty or a multiline string.
+fn single_str_error(profile: &Profile, two_apostrophes: bool, empty: bool) -> Option<ErrorCode> {
+    if two_apostrophes && !empty {
+        if profile.multiline_strings {
+            None
+        } else {
+            Some(ErrorCode::MultilineStringNotAllowed)
+        }
+    } else if profile.single_quoted_strings {
+        None
+    } else {
+        Some(ErrorCode::SingleQuotedStringNotAllowed)
+    }
+}
+
+/// Parses a complete Hjson number, returning `None` if `s` contains anything
+/// else.
+fn parse_number_str(s: &str) -> Option<Number> {
+    let mut de = Deserializer::new(read::SliceRead::new(s.as_bytes()));
+    let number = match s.as_bytes().first() {
+        Some(&b'-') => {
+            de.eat_char();
+            de.parse_integer(false)
+        }
+        Some(&b'0'...b'9') => de.parse_integer(true),
+        _ => return None,
+    };
+
+    match number {
+        Ok(number) if de.read.byte_offset() == s.len() => Some(number),
+        _ => None,
+    }
+}
+
+// Not public API. Should be pub(crate).
+//
+// Like `parse_number_str`, for the `events` module.
+#[doc(hidden)]
+pub fn parse_number(s: &str) -> Option<::value::Number> {
+    match parse_number_str(s) {
+        Some(Number::F64(x)) => ::value::Number::from_f64(x),
+        Some(Number::U64(x)) => Some(x.into()),
+        Some(Number::I64(x)) => Some(x.into()),
+        None => None,
+    }
+}
+
+// Not public API. Should be pub(crate).
+//
+// Whether a document that starts with a word at the current position of `read`
+// is a root object without braces, as the deserializer decides it, for the
+// `events` module. The reader is left where it was, which is only possible if
+// it can go back. Otherwise the document is taken to be an object if a `:`
+// follows the word.
+#[doc(hidden)]
+pub fn starts_braceless_root<'de, R: Read<'de>>(read: &mut R) -> bool {
+    if !read.mark() {
+        return true;
+    }
+    let braceless = {
+        let mut de = Deserializer::new(&mut *read);
+        match de.parse_root_word(true) {
+            Ok(RootWord::Name(name)) => {
+                match de.check_root_name(name) {
+                    Ok(RootWord::Value { .. }) => false,
+                    _ => true,
+                }
+            }
+            Ok(RootWord::Value { .. }) => false,
+            Err(_) => true,
+        }
+    };
+    read.rewind();
+    read.unmark();
+    braceless
+}
+
+/// Whether a quoteless string starts like a number or a literal, such as
+/// `1.0.0` or `true story`.
+fn looks_like_literal(s: &str) -> bool {
+    let word = s.split(|ch: char| ch.is_whitespace() || ch == ',' || ch == ']' || ch == '}')
+        .next()
+        .unwrap_or("");
+    if word == "true" || word == "false" || word == "null" {
+        return true;
+    }
+    let digits = if word.starts_with('-') { &word[1..] } else { word };
+    digits.starts_with(|ch: char| ch.is_digit(10))
+}
+
 impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
     type Error = Error;
 
@@ -1130,6 +1985,13 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
             }
         };
 
+        if self.at_braceless_root(peek) {
+            return match self.deserialize_root(peek, visitor) {
+                Ok(value) => Ok(value),
+                Err(err) => Err(self.fix_position(err)),
+            };
+        }
+
         let value = match peek {
             b'n' => {
                 self.str_buf.clear();
@@ -1138,15 +2000,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 self.eat_char();
                 let ret = match self.parse_ident(b"ull") {
                     Ok(_) => visitor.visit_unit(),
-                    Err(_) => {
+                    Err(err) => {
                         let captured_chars = unsafe { String::from_utf8_unchecked(self.str_buf.clone()) };
 
                         self.capture = false;
-                        self.str_buf.clear();
-                        match try!(self.read.parse_none_str(&mut self.str_buf)) {
-                            Reference::Borrowed(s) => visitor.visit_str(&(captured_chars + s)),
-                            Reference::Copied(s) => visitor.visit_str(&(captured_chars + &s)),
+                        if !self.profile.quoteless_strings {
+                            return Err(err);
                         }
+                        self.visit_quoteless_rest(captured_chars, visitor)
                     }
                 };
 
@@ -1161,15 +2022,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 self.eat_char();
                 let ret = match self.parse_ident(b"rue") {
                     Ok(_) => visitor.visit_bool(true),
-                    Err(_) => {
+                    Err(err) => {
                         let captured_chars = unsafe { String::from_utf8_unchecked(self.str_buf.clone()) };
 
                         self.capture = false;
-                        self.str_buf.clear();
-                        match try!(self.read.parse_none_str(&mut self.str_buf)) {
-                            Reference::Borrowed(s) => visitor.visit_str(&(captured_chars + s)),
-                            Reference::Copied(s) => visitor.visit_str(&(captured_chars + &s)),
+                        if !self.profile.quoteless_strings {
+                            return Err(err);
                         }
+                        self.visit_quoteless_rest(captured_chars, visitor)
                     }
                 };
 
@@ -1184,15 +2044,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 self.eat_char();
                 let ret = match self.parse_ident(b"alse") {
                     Ok(_) => visitor.visit_bool(false),
-                    Err(_) => {
+                    Err(err) => {
                         let captured_chars = unsafe { String::from_utf8_unchecked(self.str_buf.clone()) };
 
                         self.capture = false;
-                        self.str_buf.clear();
-                        match try!(self.read.parse_none_str(&mut self.str_buf)) {
-                            Reference::Borrowed(s) => visitor.visit_str(&(captured_chars + s)),
-                            Reference::Copied(s) => visitor.visit_str(&(captured_chars + &s)),
+                        if !self.profile.quoteless_strings {
+                            return Err(err);
                         }
+                        self.visit_quoteless_rest(captured_chars, visitor)
                     }
                 };
 
@@ -1207,15 +2066,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 self.eat_char();
                 let ret = match self.parse_integer(false) {
                     Ok(num) => num.visit(visitor),
-                    Err(_) => {
+                    Err(err) => {
                         let captured_num = unsafe { String::from_utf8_unchecked(self.str_buf.clone()) };
 
                         self.capture = false;
-                        self.str_buf.clear();
-                        match try!(self.read.parse_none_str(&mut self.str_buf)) {
-                            Reference::Borrowed(s) => visitor.visit_str(&(captured_num + s)),
-                            Reference::Copied(s) => visitor.visit_str(&(captured_num + &s)),
+                        if !self.profile.quoteless_strings {
+                            return Err(err);
                         }
+                        self.visit_quoteless_rest(captured_num, visitor)
                     }
                 };
 
@@ -1229,15 +2087,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
                 let ret = match self.parse_integer(true) {
                     Ok(num) => num.visit(visitor),
-                    Err(_) => {
+                    Err(err) => {
                         let captured_num = unsafe { String::from_utf8_unchecked(self.str_buf.clone()) };
 
                         self.capture = false;
-                        self.str_buf.clear();
-                        match try!(self.read.parse_none_str(&mut self.str_buf)) {
-                            Reference::Borrowed(s) => visitor.visit_str(&(captured_num + s)),
-                            Reference::Copied(s) => visitor.visit_str(&(captured_num + &s)),
+                        if !self.profile.quoteless_strings {
+                            return Err(err);
                         }
+                        self.visit_quoteless_rest(captured_num, visitor)
                     }
                 };
 
@@ -1254,27 +2111,16 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             b'\'' => {
-                self.eat_char();
-                self.str_buf.clear();
-                let col;
-                {
-                    col = self.read.peek_position().column - 1;
-                }
-                match try!(self.read.parse_single_str(&mut self.str_buf, col)) {
+                match try!(self.parse_single_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
             }
             b'[' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_seq(SeqAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_seq(SeqAccess::new(de))
+                });
 
                 match (ret, self.end_seq()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1282,15 +2128,10 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             b'{' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_map(MapAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_map(MapAccess::new(de))
+                });
 
                 match (ret, self.end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1298,8 +2139,7 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             _ => {
-                self.str_buf.clear();
-                match try!(self.read.parse_none_str(&mut self.str_buf)) {
+                match try!(self.parse_none_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
@@ -1448,20 +2288,13 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             b'\'' => {
-                self.eat_char();
-                self.str_buf.clear();
-                let col;
-                {
-                    col = self.read.peek_position().column - 1;
-                }
-                match try!(self.read.parse_single_str(&mut self.str_buf, col)) {
+                match try!(self.parse_single_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
             }
             _ => {
-                self.str_buf.clear();
-                match try!(self.read.parse_none_str(&mut self.str_buf)) {
+                match try!(self.parse_none_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
@@ -1584,21 +2417,14 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             b'\'' => {
-                self.eat_char();
-                self.str_buf.clear();
-                let col;
-                {
-                    col = self.read.peek_position().column - 1;
-                }
-                match try!(self.read.parse_single_str_raw(&mut self.str_buf, col)) {
+                match try!(self.parse_single_str_raw()) {
                     Reference::Borrowed(b) => visitor.visit_borrowed_bytes(b),
                     Reference::Copied(b) => visitor.visit_bytes(b),
                 }
             }
             b'[' => self.deserialize_seq(visitor),
             _ => {
-                self.str_buf.clear();
-                match try!(self.read.parse_none_str(&mut self.str_buf)) {
+                match try!(self.parse_none_str()) {
                     Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
                     Reference::Copied(s) => visitor.visit_str(s),
                 }
@@ -1697,15 +2523,10 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
         let value = match peek {
             b'[' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_seq(SeqAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_seq(SeqAccess::new(de))
+                });
 
                 match (ret, self.end_seq()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1756,17 +2577,19 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
             }
         };
 
+        if self.at_braceless_root(peek) {
+            return match self.deserialize_root(peek, visitor) {
+                Ok(value) => Ok(value),
+                Err(err) => Err(self.fix_position(err)),
+            };
+        }
+
         let value = match peek {
             b'{' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_map(MapAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_map(MapAccess::new(de))
+                });
 
                 match (ret, self.end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1784,14 +2607,18 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
     fn deserialize_struct<V>(
         self,
-        _name: &'static str,
-        _fields: &'static [&'static str],
+        name: &'static str,
+        fields: &'static [&'static str],
         visitor: V
     ) -> Result<V::Value>
     where
         V: de::Visitor<'de>,
     {
         debug!(deserialize_struct);
+        if name == spanned::NAME && fields == spanned::FIELDS {
+            return self.deserialize_spanned(visitor);
+        }
+
         let peek = match try!(self.parse_whitespace()) {
             Some(b) => b,
             None => {
@@ -1799,17 +2626,19 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
             }
         };
 
+        if self.at_braceless_root(peek) {
+            return match self.deserialize_root(peek, visitor) {
+                Ok(value) => Ok(value),
+                Err(err) => Err(self.fix_position(err)),
+            };
+        }
+
         let value = match peek {
             b'[' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_seq(SeqAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_seq(SeqAccess::new(de))
+                });
 
                 match (ret, self.end_seq()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1817,15 +2646,10 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                 }
             }
             b'{' => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let ret = visitor.visit_map(MapAccess::new(self));
-
-                self.remaining_depth += 1;
+                let ret = self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_map(MapAccess::new(de))
+                });
 
                 match (ret, self.end_map()) {
                     (Ok(ret), Ok(())) => Ok(ret),
@@ -1856,15 +2680,10 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         debug!(deserialize_enum);
         match try!(self.parse_whitespace()) {
             Some(b'{') => {
-                self.remaining_depth -= 1;
-                if self.remaining_depth == 0 {
-                    return Err(self.peek_error(ErrorCode::RecursionLimitExceeded));
-                }
-
-                self.eat_char();
-                let value = try!(visitor.visit_enum(VariantAccess::new(self)));
-
-                self.remaining_depth += 1;
+                let value = try!(self.recurse(move |de| {
+                    de.eat_char();
+                    visitor.visit_enum(VariantAccess::new(de))
+                }));
 
                 match try!(self.parse_whitespace()) {
                     Some(b'}') => {
@@ -1875,8 +2694,11 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
                     None => Err(self.error(ErrorCode::EofWhileParsingObject)),
                 }
             }
-            Some(b'"') => visitor.visit_enum(UnitVariantAccess::new(self)),
-            Some(_) => Err(self.peek_error(ErrorCode::ExpectedSomeValue)),
+            // Quoted or quoteless, a string names a unit variant.
+            Some(b'[') | Some(b']') | Some(b'}') | Some(b',') | Some(b':') => {
+                Err(self.peek_error(ErrorCode::ExpectedSomeValue))
+            }
+            Some(_) => visitor.visit_enum(UnitVariantAccess::new(self)),
             None => Err(self.peek_error(ErrorCode::EofWhileParsingValue)),
         }
     }
@@ -1896,6 +2718,7 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
         V: de::Visitor<'de>,
     {
         debug!(deserialize_ignored_any);
+        self.record_ignored();
         try!(self.ignore_value());
         visitor.visit_unit()
     }
@@ -1903,12 +2726,16 @@ impl<'de, 'a, R: Read<'de>> de::Deserializer<'de> for &'a mut Deserializer<R> {
 
 struct SeqAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
+    /// The index of the next element, for the path of errors.
+    index: usize,
 }
 
 impl<'a, R: 'a> SeqAccess<'a, R> {
     fn new(de: &'a mut Deserializer<R>) -> Self {
+        de.root = false;
         SeqAccess {
             de: de,
+            index: 0,
         }
     }
 }
@@ -1931,15 +2758,23 @@ impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
             _ => (),
         }
 
-        let ret = seed.deserialize(&mut *self.de).map(Some);
+        let index = self.index;
+        self.index += 1;
+        let len = self.de.enter_element(index);
+        let ret = seed.deserialize(&mut *self.de).map_err(|err| err.prepend_index(index));
+        self.de.leave(len);
+        let ret = try!(ret);
 
         let mut had_newline = false;
         match try!(self.de.parse_whitespace_get_newline(&mut had_newline)) {
             Some(ch) => {
                 if ch == b',' {
                     self.de.eat_char();
+                    try!(self.de.check_trailing_comma(Some(b']')));
                 } else if ch != b']' && !had_newline {
                     return Err(self.de.peek_error(ErrorCode::ExpectedListCommaOrEnd));
+                } else if ch != b']' && !self.de.profile.optional_commas {
+                    return Err(self.de.peek_error(ErrorCode::MissingComma));
                 }
             }
             None => {
@@ -1947,23 +2782,264 @@ impl<'de, 'a, R: Read<'de> + 'a> de::SeqAccess<'de> for SeqAccess<'a, R> {
             }
         }
 
-        ret
+        Ok(Some(ret))
     }
 }
 
-struct MapAccess<'a, R: 'a> {
+struct MapAccess<'de, 'a, R: 'a> {
     de: &'a mut Deserializer<R>,
+    /// Whether this is a root object without braces, which ends at the end of
+    /// the input instead of at a `}`.
+    braceless: bool,
+    /// A member name that has already been read, along with its position.
+    first_key: Option<(ParsedKey<'de>, Position)>,
+    /// Positions of the member names seen so far, for the `Error` and
+    /// `KeepFirst` duplicate key policies.
+    seen: HashMap<String, Position>,
+    /// The members of the object read ahead of time, for the `KeepLast` and
+    /// `Collect` duplicate key policies.
+    members: Option<vec::IntoIter<(ParsedKey<'de>, Value)>>,
+    /// The value of the member returned by the last call to `next_key_seed`
+    /// if it was read ahead of time.
+    value: Option<Value>,
+    /// The name of the member returned by the last call to `next_key_seed`,
+    /// for the path of errors.
+    key: Option<ParsedKey<'de>>,
+    /// The byte range of the member name read last, for errors about it.
+    key_range: Range<usize>,
+    /// The span of the member name read last, if ignored members are being
+    /// collected.
+    key_span: Option<Span>,
+    /// The member names seen so far by their lowercase form, if warnings are
+    /// being collected.
+    folded: HashMap<String, String>,
 }
 
-impl<'a, R: 'a> MapAccess<'a, R> {
+impl<'de, 'a, R: Read<'de> + 'a> MapAccess<'de, 'a, R> {
     fn new(de: &'a mut Deserializer<R>) -> Self {
+        de.root = false;
         MapAccess {
             de: de,
+            braceless: false,
+            first_key: None,
+            seen: HashMap::new(),
+            members: None,
+            value: None,
+            key: None,
+            key_range: 0..0,
+            key_span: None,
+            folded: HashMap::new(),
+        }
+    }
+
+    /// Map access for a root object whose braces were omitted. The first
+    /// member name has already been read while looking for the `:` following
+    /// it.
+    fn braceless(
+        de: &'a mut Deserializer<R>,
+        first_key: ParsedKey<'de>,
+        position: Position,
+        range: Range<usize>,
+    ) -> Self {
+        let mut map = MapAccess::new(de);
+        map.braceless = true;
+        map.first_key = Some((first_key, position));
+        map.key_range = range;
+        map
+    }
+
+    /// Returns whether another member follows, leaving the deserializer in
+    /// front of its name.
+    fn has_next_key(&mut self) -> Result<bool> {
+        match try!(self.de.parse_whitespace()) {
+            Some(b'}') if !self.braceless => Ok(false),
+            Some(b'}') | Some(b']') if self.braceless => {
+                Err(self.de.peek_error(ErrorCode::TrailingCharacters))
+            }
+            Some(b',') => Err(self.de.peek_error(ErrorCode::ExtraComma)),
+            Some(_) => Ok(true),
+            None if self.braceless => Ok(false),
+            None => Err(self.de.peek_error(ErrorCode::EofWhileParsingObject)),
+        }
+    }
+
+    /// Reads the next member name and its position.
+    fn next_key(&mut self) -> Result<Option<(ParsedKey<'de>, Position)>> {
+        let (key, position) = match self.first_key.take() {
+            Some(key) => key,
+            None => {
+                if !try!(self.has_next_key()) {
+                    return Ok(None);
+                }
+                let position = self.de.read.peek_position();
+                let (key, range) = try!(self.de.parse_key_range());
+                self.key_range = range;
+                (key, position)
+            }
+        };
+
+        if self.de.warnings.is_some() {
+            self.check_case(key.as_str(), position);
+        }
+        if self.de.ignored.is_some() {
+            self.key_span = Some(Span {
+                start: self.key_range.start,
+                end: self.key_range.end,
+                line: position.line,
+                column: position.column,
+            });
+        }
+        Ok(Some((key, position)))
+    }
+
+    /// Warns if a member name differs only in case from an earlier one.
+    fn check_case(&mut self, key: &str, position: Position) {
+        let earlier = match self.folded.entry(key.to_lowercase()) {
+            Entry::Vacant(entry) => {
+                entry.insert(String::from(key));
+                return;
+            }
+            Entry::Occupied(entry) => entry.get().clone(),
+        };
+        if earlier != key {
+            let kind = WarningKind::KeyCaseMismatch(earlier);
+            self.de.warn(kind, position.line, position.column);
+        }
+    }
+
+    /// Consumes the separator after the value of a member.
+    fn end_value(&mut self) -> Result<()> {
+        let mut had_newline = false;
+        match try!(self.de.parse_whitespace_get_newline(&mut had_newline)) {
+            Some(b',') => {
+                self.de.eat_char();
+                let end = if self.braceless { None } else { Some(b'}') };
+                self.de.check_trailing_comma(end)
+            }
+            Some(b'}') if !self.braceless => Ok(()),
+            Some(_) if !had_newline => {
+                if self.braceless {
+                    Err(self.de.peek_error(ErrorCode::UnexpectedCharacter))
+                } else {
+                    Err(self.de.peek_error(ErrorCode::ExpectedListCommaOrEnd))
+                }
+            }
+            Some(_) if !self.de.profile.optional_commas => {
+                Err(self.de.peek_error(ErrorCode::MissingComma))
+            }
+            Some(_) => Ok(()),
+            // The object ends at the end of the input rather than at a `}`.
+            None if self.braceless => Ok(()),
+            None => Err(self.de.peek_error(ErrorCode::EofWhileParsingList)),
+        }
+    }
+
+    /// Reads the next member name whose value should be passed on to the
+    /// visitor under the `Error` and `KeepFirst` duplicate key policies.
+    fn next_unique_key(&mut self, keep_first: bool) -> Result<Option<ParsedKey<'de>>> {
+        loop {
+            let (key, position) = match try!(self.next_key()) {
+                Some(key) => key,
+                None => return Ok(None),
+            };
+
+            match self.seen.entry(String::from(key.as_str())) {
+                Entry::Vacant(entry) => {
+                    entry.insert(position);
+                    return Ok(Some(key));
+                }
+                Entry::Occupied(ref entry) if !keep_first => {
+                    let first = entry.get();
+                    let key = entry.key().clone();
+                    let code = ErrorCode::DuplicateKey(key, first.line, first.column);
+                    let range = position.start..self.de.read.byte_offset();
+                    return Err(Error::syntax_at(code, position, range));
+                }
+                Entry::Occupied(_) => {
+                    try!(self.de.parse_object_colon());
+                    try!(self.de.ignore_value());
+                    try!(self.end_value());
+                }
+            }
+        }
+    }
+
+    /// Reads all members ahead of time, merging the values of duplicate keys
+    /// according to the `KeepLast` and `Collect` policies.
+    fn read_members(&mut self, collect: bool) -> Result<vec::IntoIter<(ParsedKey<'de>, Value)>> {
+        if let Some(ref mut ignored) = self.de.ignored {
+            ignored.buffering += 1;
+        }
+        let members = self.read_member_values(collect);
+        if let Some(ref mut ignored) = self.de.ignored {
+            ignored.buffering -= 1;
+        }
+        members
+    }
+
+    fn read_member_values(
+        &mut self,
+        collect: bool,
+    ) -> Result<vec::IntoIter<(ParsedKey<'de>, Value)>> {
+        let mut members: Vec<(ParsedKey<'de>, Vec<Value>)> = Vec::new();
+        let mut indices = HashMap::new();
+
+        while let Some((key, _)) = try!(self.next_key()) {
+            try!(self.de.parse_object_colon());
+            // The names of the members are recorded on the way in case the
+            // visitor skips them.
+            let len = self.de.enter_member(key.as_str(), self.key_span.take());
+            let value = Value::deserialize(&mut *self.de);
+            let value = value.map_err(|err| err.prepend_key(key.as_str()));
+            self.de.leave(len);
+            let value = try!(value);
+            try!(self.end_value());
+
+            match indices.entry(String::from(key.as_str())) {
+                Entry::Vacant(entry) => {
+                    entry.insert(members.len());
+                    members.push((key, vec![value]));
+                }
+                Entry::Occupied(entry) => {
+                    members[*entry.get()].1.push(value);
+                }
+            }
         }
+
+        let members: Vec<_> = members
+            .into_iter()
+            .map(|(key, mut values)| if collect && values.len() > 1 {
+                (key, Value::Array(values))
+            } else {
+                (key, values.pop().unwrap())
+            })
+            .collect();
+        Ok(members.into_iter())
+    }
+
+    /// Keeps the name of a member for the path of errors in its value and
+    /// passes it on to the visitor.
+    fn visit_key<K>(&mut self, key: ParsedKey<'de>, seed: K) -> Result<K::Value>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        self.key = Some(key);
+        let ret = seed.deserialize(self.key.as_ref().unwrap());
+        if self.members.is_some() {
+            // The input has already been read up to the end of the object.
+            return ret;
+        }
+        let range = self.key_range.clone();
+        ret.map_err(|err| self.de.fix_key_position(err, range))
+    }
+
+    /// The name of the member returned by the last call to `next_key_seed`.
+    fn key(&self) -> &str {
+        self.key.as_ref().map_or("", ParsedKey::as_str)
     }
 }
 
-impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
+impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'de, 'a, R> {
     type Error = Error;
 
     fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
@@ -1971,56 +3047,1000 @@ impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for MapAccess<'a, R> {
         K: de::DeserializeSeed<'de>,
     {
         debug!(next_key_seed);
-        match try!(self.de.parse_whitespace()) {
-            Some(b'}') => {
-                return Ok(None);
+        let collect = match self.de.duplicate_keys {
+            None if self.de.warnings.is_some() || self.de.ignored.is_some() => {
+                return match try!(self.next_key()) {
+                    Some((key, _)) => self.visit_key(key, seed).map(Some),
+                    None => Ok(None),
+                };
             }
-            Some(b',') => {
-                return Err(self.de.peek_error(ErrorCode::ExtraComma));
-            },
-            Some(_) => {
-                seed.deserialize(MapKey { de: &mut *self.de }).map(Some)
+            None => {
+                let key = match self.first_key.take() {
+                    Some((key, _)) => key,
+                    None if try!(self.has_next_key()) => {
+                        let (key, range) = try!(self.de.parse_key_range());
+                        self.key_range = range;
+                        key
+                    }
+                    None => return Ok(None),
+                };
+                return self.visit_key(key, seed).map(Some);
             }
+            Some(DuplicateKeys::Error) | Some(DuplicateKeys::KeepFirst) => {
+                let keep_first = self.de.duplicate_keys == Some(DuplicateKeys::KeepFirst);
+                return match try!(self.next_unique_key(keep_first)) {
+                    Some(key) => self.visit_key(key, seed).map(Some),
+                    None => Ok(None),
+                };
+            }
+            Some(DuplicateKeys::KeepLast) => false,
+            Some(DuplicateKeys::Collect) => true,
+        };
+
+        if self.members.is_none() {
+            self.members = Some(try!(self.read_members(collect)));
+        }
+
+        match self.members.as_mut().and_then(Iterator::next) {
+            Some((key, value)) => {
+                self.value = Some(value);
+                self.visit_key(key, seed).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        debug!(next_value_seed);
+        if let Some(value) = self.value.take() {
+            let len = {
+                let key = self.key.as_ref().map_or("", ParsedKey::as_str);
+                self.de.enter_member(key, None)
+            };
+            let ret = match self.de.ignored {
+                Some(ref mut ignored) => {
+                    seed.deserialize(BufferedValue {
+                        value: value,
+                        ignored: ignored,
+                    })
+                }
+                None => seed.deserialize(value),
+            };
+            self.de.leave(len);
+            return ret.map_err(|err| err.prepend_key(self.key()));
+        }
+
+        try!(self.de.parse_object_colon());
+        let len = {
+            let key = self.key.as_ref().map_or("", ParsedKey::as_str);
+            self.de.enter_member(key, self.key_span.take())
+        };
+        let ret = seed.deserialize(&mut *self.de).map_err(|err| err.prepend_key(self.key()));
+        self.de.leave(len);
+        let ret = try!(ret);
+        try!(self.end_value());
+        Ok(ret)
+    }
+}
+
+/// Hands out the fields of a `Spanned` in the order of `spanned::FIELDS`.
+struct SpannedAccess<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    field: usize,
+    start: usize,
+    position: Position,
+    end: usize,
+}
+
+impl<'de, 'a, R: Read<'de> + 'a> de::MapAccess<'de> for SpannedAccess<'a, R> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match spanned::FIELDS.get(self.field) {
+            Some(field) => {
+                let field = de::value::BorrowedStrDeserializer::new(field);
+                seed.deserialize(field).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let field = spanned::FIELDS[self.field];
+        self.field += 1;
+
+        let position = if field == spanned::START {
+            self.start
+        } else if field == spanned::LINE {
+            self.position.line
+        } else if field == spanned::COLUMN {
+            self.position.column
+        } else if field == spanned::END {
+            self.end
+        } else {
+            self.de.value_end = None;
+            let container = match try!(self.de.peek()) {
+                Some(b'[') | Some(b'{') => true,
+                _ => false,
+            };
+            let ret = try!(seed.deserialize(&mut *self.de));
+            // Leave out whitespace and comments that were consumed after a
+            // number, literal or quoteless string. A root object without
+            // braces ends with the end of its last value.
+            self.end = match self.de.value_end {
+                Some(end) if !container => end,
+                _ => self.de.read.byte_offset(),
+            };
+            return Ok(ret);
+        };
+        seed.deserialize(de::IntoDeserializer::into_deserializer(position))
+    }
+}
+
+/// The leading word of a document that does not start with a bracket.
+enum RootWord {
+    /// The name of the first member of a root object without braces.
+    Name(String),
+    /// The start of a root value, along with the byte offset and position of
+    /// the start and the length of the word.
+    Value {
+        start: usize,
+        position: Position,
+        captured: String,
+        word_len: usize,
+    },
+}
+
+/// Hands a root value starting with a word that is not a member name to a
+/// visitor.
+struct RootQuoteless<'a, R: 'a> {
+    de: &'a mut Deserializer<R>,
+    start: usize,
+    position: Position,
+    captured: String,
+    word_len: usize,
+}
+
+impl<'de, 'a, R: Read<'de> + 'a> de::Deserializer<'de> for RootQuoteless<'a, R> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        let de = self.de;
+        de.visit_root_quoteless(self.start, self.position, self.captured, self.word_len, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// State of `Deserializer::collect_ignored`.
+struct Ignored {
+    /// The path of the value being deserialized.
+    path: String,
+    /// The name of the member whose value is being deserialized, which is
+    /// recorded if the value is ignored.
+    member: Option<Span>,
+    keys: Vec<(String, Span)>,
+    /// How many objects are being read ahead of time under the `KeepLast` and
+    /// `Collect` duplicate key policies.
+    buffering: usize,
+    /// The names of the members in objects read ahead of time by their path,
+    /// for when their values are skipped later on.
+    buffered: HashMap<String, Span>,
+}
+
+impl<R> Deserializer<R> {
+    /// Appends a member name, or the variant name of an enum, to the path of
+    /// the current value while ignored members are being collected. Returns
+    /// the length of the path to go back to with `leave` afterwards.
+    fn enter_member(&mut self, key: &str, span: Option<Span>) -> usize {
+        match self.ignored {
+            Some(ref mut ignored) => {
+                let len = ignored.path.len();
+                push_path_key(&mut ignored.path, key);
+                if let (Some(span), true) = (span, ignored.buffering > 0) {
+                    ignored.buffered.insert(ignored.path.clone(), span);
+                }
+                ignored.member = span;
+                len
+            }
+            None => 0,
+        }
+    }
+
+    /// Like `enter_member` for an array element.
+    fn enter_element(&mut self, index: usize) -> usize {
+        match self.ignored {
+            Some(ref mut ignored) => {
+                let len = ignored.path.len();
+                ignored.path.push_str(&format!("[{}]", index));
+                ignored.member = None;
+                len
+            }
+            None => 0,
+        }
+    }
+
+    fn leave(&mut self, len: usize) {
+        if let Some(ref mut ignored) = self.ignored {
+            ignored.path.truncate(len);
+            ignored.member = None;
+        }
+    }
+
+    /// Records the current member as ignored if its value is being skipped.
+    fn record_ignored(&mut self) {
+        if let Some(ref mut ignored) = self.ignored {
+            if let Some(span) = ignored.member.take() {
+                ignored.keys.push((ignored.path.clone(), span));
+            }
+        }
+    }
+}
+
+/// A member value that was read ahead of time under the `KeepLast` and
+/// `Collect` duplicate key policies, which reports the members that the
+/// visitor skips like the deserializer does.
+struct BufferedValue<'a> {
+    value: Value,
+    ignored: &'a mut Ignored,
+}
+
+impl<'de, 'a> de::Deserializer<'de> for BufferedValue<'a> {
+    type Error = Error;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Array(array) => {
+                let len = array.len();
+                let mut seq = BufferedSeq {
+                    iter: array.into_iter(),
+                    index: 0,
+                    ignored: self.ignored,
+                };
+                let ret = try!(visitor.visit_seq(&mut seq));
+                if seq.iter.len() == 0 {
+                    Ok(ret)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in array"))
+                }
+            }
+            Value::Object(object) => {
+                let len = object.len();
+                let mut map = BufferedMap {
+                    iter: object.into_iter(),
+                    key: None,
+                    value: None,
+                    ignored: self.ignored,
+                };
+                let ret = try!(visitor.visit_map(&mut map));
+                if map.iter.len() == 0 {
+                    Ok(ret)
+                } else {
+                    Err(de::Error::invalid_length(len, &"fewer elements in map"))
+                }
+            }
+            value => value.deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match self.value {
+            Value::Null => visitor.visit_none(),
+            _ => visitor.visit_some(self),
+        }
+    }
+
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        self.value.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        if let Some(span) = self.ignored.buffered.remove(&self.ignored.path) {
+            self.ignored.keys.push((self.ignored.path.clone(), span));
+        }
+        visitor.visit_unit()
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
+        byte_buf unit unit_struct seq tuple tuple_struct map struct identifier
+    }
+}
+
+struct BufferedSeq<'a> {
+    iter: vec::IntoIter<Value>,
+    index: usize,
+    ignored: &'a mut Ignored,
+}
+
+impl<'de, 'a> de::SeqAccess<'de> for BufferedSeq<'a> {
+    type Error = Error;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>>
+    where
+        T: de::DeserializeSeed<'de>,
+    {
+        let value = match self.iter.next() {
+            Some(value) => value,
+            None => return Ok(None),
+        };
+        let index = self.index;
+        self.index += 1;
+
+        let len = self.ignored.path.len();
+        self.ignored.path.push_str(&format!("[{}]", index));
+        let ret = seed.deserialize(BufferedValue {
+            value: value,
+            ignored: &mut *self.ignored,
+        });
+        self.ignored.path.truncate(len);
+        ret.map(Some).map_err(|err| err.prepend_index(index))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct BufferedMap<'de, 'a> {
+    iter: <Map<String, Value> as IntoIterator>::IntoIter,
+    key: Option<ParsedKey<'de>>,
+    value: Option<Value>,
+    ignored: &'a mut Ignored,
+}
+
+impl<'de, 'a> de::MapAccess<'de> for BufferedMap<'de, 'a> {
+    type Error = Error;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>>
+    where
+        K: de::DeserializeSeed<'de>,
+    {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.key = Some(ParsedKey::Owned(key));
+                self.value = Some(value);
+                seed.deserialize(self.key.as_ref().unwrap()).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    where
+        V: de::DeserializeSeed<'de>,
+    {
+        let value = match self.value.take() {
+            Some(value) => value,
+            None => return Err(de::Error::custom("value is missing")),
+        };
+        let key = self.key.as_ref().map_or("", ParsedKey::as_str);
+
+        let len = self.ignored.path.len();
+        push_path_key(&mut self.ignored.path, key);
+        let ret = seed.deserialize(BufferedValue {
+            value: value,
+            ignored: &mut *self.ignored,
+        });
+        self.ignored.path.truncate(len);
+        ret.map_err(|err| err.prepend_key(key))
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// What `Deserializer::parse_value_recovering` makes of input with syntax
+/// errors.
+#[derive(Debug)]
+pub struct Recovered {
+    /// The value as far as it could be parsed.
+    pub value: Value,
+    /// The errors in the order they were found, so the input is valid if there
+    /// are none.
+    pub errors: Vec<Error>,
+    /// JSON Pointers to the nulls in `value` that stand in for values that
+    /// could not be parsed, like `/ports/1`. The pointer to the root value is
+    /// the empty string.
+    pub placeholders: Vec<String>,
+}
+
+/// State of `Deserializer::parse_value_recovering`.
+struct Recovery {
+    errors: Vec<Error>,
+    /// The closing brackets of the arrays and objects being parsed, innermost
+    /// last.
+    closers: Vec<u8>,
+    /// Set by an IO error, after which nothing more can be read.
+    failed: bool,
+    /// JSON Pointer to the value being parsed.
+    pointer: String,
+    placeholders: Vec<String>,
+}
+
+impl Recovery {
+    fn record(&mut self, err: Error) {
+        if err.is_io() {
+            self.failed = true;
+        }
+        self.errors.push(err);
+    }
+
+    /// A null in place of the value being parsed, which could not be.
+    fn placeholder(&mut self) -> Value {
+        self.placeholders.push(self.pointer.clone());
+        Value::Null
+    }
+}
+
+impl<'de, R: Read<'de>> Deserializer<R> {
+    fn recover_root(&mut self, recovery: &mut Recovery) -> Value {
+        let peek = match self.parse_whitespace() {
+            Ok(Some(peek)) => peek,
+            Ok(None) => {
+                recovery.record(self.peek_error(ErrorCode::EofWhileParsingValue));
+                return recovery.placeholder();
+            }
+            Err(err) => {
+                recovery.record(err);
+                return recovery.placeholder();
+            }
+        };
+
+        let braceless = self.at_braceless_root(peek);
+        self.root = false;
+        let value = if braceless {
+            let start = self.read.byte_offset();
+            match self.recover_root_start(peek) {
+                Ok(Err(name)) => Value::Object(self.recover_members(Some(name), true, recovery)),
+                Ok(Ok(value)) => value,
+                Err(err) => {
+                    recovery.record(err);
+                    self.resync(start, recovery);
+                    recovery.placeholder()
+                }
+            }
+        } else {
+            self.recover_value_at(peek, recovery)
+        };
+
+        if !recovery.failed {
+            if let Err(err) = self.end() {
+                recovery.record(err);
+            }
+        }
+        value
+    }
+
+    /// Reads the start of a document that may be an object without braces.
+    /// Returns the name of the first member if it is, or else the root value.
+    fn recover_root_start(&mut self, peek: u8) -> Result<result::Result<Value, String>> {
+        let start = self.read.byte_offset();
+        if peek == b'"' || peek == b'\'' {
+            let key = String::from(try!(self.parse_key()).as_str());
+            let range = start..self.read.byte_offset();
+            return match try!(self.parse_whitespace()) {
+                Some(b':') => {
+                    self.record(TokenKind::MemberName, range);
+                    Ok(Err(key))
+                }
+                _ => {
+                    self.record(TokenKind::QuotelessString, range);
+                    Ok(Ok(Value::String(key)))
+                }
+            };
+        }
+
+        match try!(self.parse_root_start()) {
+            RootWord::Name(name) => {
+                self.record(TokenKind::MemberName, start..start + name.len());
+                Ok(Err(name))
+            }
+            RootWord::Value { start, position, captured, word_len } => {
+                let value = try!(Value::deserialize(RootQuoteless {
+                    de: self,
+                    start: start,
+                    position: position,
+                    captured: captured,
+                    word_len: word_len,
+                }));
+                self.record_scalar(start, &value);
+                Ok(Ok(value))
+            }
+        }
+    }
+
+    fn recover_value(&mut self, recovery: &mut Recovery) -> Value {
+        match self.parse_whitespace() {
+            Ok(Some(peek)) => self.recover_value_at(peek, recovery),
+            Ok(None) => {
+                recovery.record(self.peek_error(ErrorCode::EofWhileParsingValue));
+                recovery.placeholder()
+            }
+            Err(err) => {
+                recovery.record(err);
+                recovery.placeholder()
+            }
+        }
+    }
+
+    /// Parses a value starting at `peek`, which `parse_whitespace` returned.
+    fn recover_value_at(&mut self, peek: u8, recovery: &mut Recovery) -> Value {
+        let start = self.read.byte_offset();
+        self.value_end = None;
+        let result = match peek {
+            b'{' => {
+                self.eat_char();
+                return self.recover_nested(b'}', recovery);
+            }
+            b'[' => {
+                self.eat_char();
+                return self.recover_nested(b']', recovery);
+            }
+            // After a `/` that does not start a comment, `deserialize_any`
+            // reads a quoteless string from the byte after it, even if that
+            // would be skipped as whitespace or a comment on its own.
+            b' ' | b'\t' | b'\r' | b'\n' | b'#' | b'/' => {
+                self.parse_none_str().map(|s| Value::String(String::from(&*s)))
+            }
+            _ => Value::deserialize(&mut *self),
+        };
+        match result {
+            Ok(value) => {
+                self.record_scalar(start, &value);
+                value
+            }
+            Err(err) => {
+                recovery.record(err);
+                self.resync(start, recovery);
+                recovery.placeholder()
+            }
+        }
+    }
+
+    /// Parses the rest of an array or object whose opening bracket has been
+    /// read, minding the recursion limit.
+    fn recover_nested(&mut self, closer: u8, recovery: &mut Recovery) -> Value {
+        let depth = match self.remaining_depth {
+            Some(0) => {
+                recovery.record(self.error(ErrorCode::RecursionLimitExceeded));
+                self.skip_nested(recovery);
+                return recovery.placeholder();
+            }
+            Some(depth) => depth,
             None => {
-                Err(self.de.peek_error(ErrorCode::EofWhileParsingObject))
+                return self
+                    .grow_stack(move |de| Ok(de.recover_entries(closer, recovery)))
+                    .unwrap_or(Value::Null);
+            }
+        };
+
+        self.remaining_depth = Some(depth - 1);
+        let value = self.recover_entries(closer, recovery);
+        self.remaining_depth = Some(depth);
+        value
+    }
+
+    fn recover_entries(&mut self, closer: u8, recovery: &mut Recovery) -> Value {
+        recovery.closers.push(closer);
+        let value = if closer == b'}' {
+            Value::Object(self.recover_members(None, false, recovery))
+        } else {
+            Value::Array(self.recover_elements(recovery))
+        };
+        recovery.closers.pop();
+        value
+    }
+
+    fn recover_members(
+        &mut self,
+        mut first_key: Option<String>,
+        braceless: bool,
+        recovery: &mut Recovery,
+    ) -> Map<String, Value> {
+        let mut map = Map::new();
+        let mut separated = true;
+        while !recovery.failed {
+            let key = match first_key.take() {
+                Some(key) => key,
+                None => {
+                    match self.parse_whitespace() {
+                        Ok(Some(b'}')) if !braceless => {
+                            self.eat_char();
+                            break;
+                        }
+                        // Like `deserialize_any`, read a member name that
+                        // starts with a bracket where a member may start.
+                        Ok(Some(b']')) if !braceless && separated && self.profile.quoteless_strings => {}
+                        Ok(Some(ch)) if ch == b'}' || ch == b']' => {
+                            let code = if braceless {
+                                ErrorCode::TrailingCharacters
+                            } else {
+                                ErrorCode::ExpectedObjectCommaOrEnd
+                            };
+                            if self.recover_stray_closer(ch, code, recovery) {
+                                break;
+                            }
+                            continue;
+                        }
+                        Ok(Some(b',')) => {
+                            recovery.record(self.peek_error(ErrorCode::ExtraComma));
+                            self.eat_char();
+                            continue;
+                        }
+                        Ok(Some(_)) => {}
+                        Ok(None) => {
+                            if !braceless {
+                                recovery.record(self.peek_error(ErrorCode::EofWhileParsingObject));
+                            }
+                            break;
+                        }
+                        Err(err) => {
+                            recovery.record(err);
+                            break;
+                        }
+                    }
+                    let start = self.read.byte_offset();
+                    match self.parse_key() {
+                        Ok(key) => {
+                            let key = String::from(key.as_str());
+                            self.record(TokenKind::MemberName, start..self.read.byte_offset());
+                            key
+                        }
+                        Err(err) => {
+                            recovery.record(err);
+                            self.resync(start, recovery);
+                            continue;
+                        }
+                    }
+                }
+            };
+
+            if let Err(err) = self.parse_object_colon() {
+                recovery.record(err);
+                let start = self.read.byte_offset();
+                self.resync(start, recovery);
+                continue;
+            }
+            let len = recovery.pointer.len();
+            recovery.pointer.push('/');
+            recovery.pointer.push_str(&key.replace('~', "~0").replace('/', "~1"));
+            let earlier = recovery.placeholders.len();
+            let value = self.recover_value(recovery);
+            if map.contains_key(&key) {
+                // The value of an earlier member with the same name is
+                // replaced, along with the placeholders in it.
+                let later = recovery.placeholders.split_off(earlier);
+                let pointer = &recovery.pointer;
+                let prefix = format!("{}/", pointer);
+                recovery.placeholders.retain(|p| p != pointer && !p.starts_with(&prefix));
+                recovery.placeholders.extend(later);
+            }
+            recovery.pointer.truncate(len);
+            map.insert(key, value);
+
+            separated = if braceless {
+                self.recover_separator(None, ErrorCode::UnexpectedCharacter, recovery)
+            } else {
+                self.recover_separator(Some(b'}'), ErrorCode::ExpectedListCommaOrEnd, recovery)
+            };
+        }
+        map
+    }
+
+    fn recover_elements(&mut self, recovery: &mut Recovery) -> Vec<Value> {
+        let mut vec = Vec::new();
+        let mut separated = true;
+        while !recovery.failed {
+            match self.parse_whitespace() {
+                Ok(Some(b']')) => {
+                    self.eat_char();
+                    break;
+                }
+                // Like `deserialize_any`, read a quoteless string that starts
+                // with a bracket where an element may start.
+                Ok(Some(b'}')) if separated && self.profile.quoteless_strings => {}
+                Ok(Some(b'}')) => {
+                    let code = ErrorCode::ExpectedListCommaOrEnd;
+                    if self.recover_stray_closer(b'}', code, recovery) {
+                        break;
+                    }
+                    continue;
+                }
+                Ok(Some(b',')) => {
+                    recovery.record(self.peek_error(ErrorCode::ExtraComma));
+                    self.eat_char();
+                    continue;
+                }
+                Ok(Some(_)) => {}
+                Ok(None) => {
+                    recovery.record(self.peek_error(ErrorCode::EofWhileParsingList));
+                    break;
+                }
+                Err(err) => {
+                    recovery.record(err);
+                    break;
+                }
+            }
+
+            let len = recovery.pointer.len();
+            recovery.pointer.push_str(&format!("/{}", vec.len()));
+            vec.push(self.recover_value(recovery));
+            recovery.pointer.truncate(len);
+            separated = self.recover_separator(Some(b']'), ErrorCode::ExpectedListCommaOrEnd, recovery);
+        }
+        vec
+    }
+
+    /// Consumes the separator after an array element or object member. A
+    /// closing bracket is left for the caller. Returns whether there was a
+    /// comma or a newline, after which a value may start with a bracket.
+    fn recover_separator(&mut self, closer: Option<u8>, code: ErrorCode, recovery: &mut Recovery) -> bool {
+        let mut had_newline = false;
+        match self.parse_whitespace_get_newline(&mut had_newline) {
+            Ok(Some(b',')) => {
+                self.eat_char();
+                if let Err(err) = self.check_trailing_comma(closer) {
+                    recovery.record(err);
+                }
+                true
+            }
+            Ok(Some(b'}')) | Ok(Some(b']')) | Ok(None) => had_newline,
+            Ok(Some(_)) if !had_newline => {
+                recovery.record(self.peek_error(code));
+                let start = self.read.byte_offset();
+                self.resync(start, recovery);
+                false
+            }
+            Ok(Some(_)) if !self.profile.optional_commas => {
+                recovery.record(self.peek_error(ErrorCode::MissingComma));
+                true
+            }
+            Ok(Some(_)) => true,
+            Err(err) => {
+                recovery.record(err);
+                false
             }
         }
     }
 
-    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value>
+    /// Handles a closing bracket that does not match the array or object
+    /// being parsed. Returns whether it closes an enclosing one instead, in
+    /// which case the current one ends here as if it had been closed.
+    /// Otherwise the bracket is skipped.
+    fn recover_stray_closer(&mut self, closer: u8, code: ErrorCode, recovery: &mut Recovery) -> bool {
+        recovery.record(self.peek_error(code));
+        let enclosing = recovery.closers.len().saturating_sub(1);
+        if recovery.closers[..enclosing].contains(&closer) {
+            true
+        } else {
+            self.eat_char();
+            false
+        }
+    }
+
+    /// Skips ahead to the next newline, `,` or closing bracket after an error.
+    /// What could not be parsed from `start` on is recorded as an error token.
+    fn resync(&mut self, start: usize, recovery: &mut Recovery) {
+        let mut end = self.read.byte_offset();
+        loop {
+            match self.peek() {
+                Ok(Some(b'\n')) | Ok(Some(b',')) | Ok(Some(b'}')) | Ok(Some(b']')) | Ok(None) => {
+                    break;
+                }
+                Ok(Some(ch)) => {
+                    self.eat_char();
+                    if ch != b' ' && ch != b'\t' && ch != b'\r' {
+                        end = self.read.byte_offset();
+                    }
+                }
+                Err(err) => {
+                    recovery.record(err);
+                    break;
+                }
+            }
+        }
+        if end > start {
+            self.record(TokenKind::Error, start..end);
+        }
+    }
+
+    /// Skips the rest of an array or object that is nested too deeply.
+    fn skip_nested(&mut self, recovery: &mut Recovery) {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.peek() {
+                Ok(Some(b'{')) | Ok(Some(b'[')) => depth += 1,
+                Ok(Some(b'}')) | Ok(Some(b']')) => depth -= 1,
+                Ok(Some(_)) => {}
+                Ok(None) => break,
+                Err(err) => {
+                    recovery.record(err);
+                    break;
+                }
+            }
+            self.eat_char();
+        }
+    }
+}
+
+/// A member name that has already been read from the input.
+enum ParsedKey<'de> {
+    Borrowed(&'de str),
+    Owned(String),
+}
+
+impl<'de> ParsedKey<'de> {
+    fn as_str(&self) -> &str {
+        match *self {
+            ParsedKey::Borrowed(s) => s,
+            ParsedKey::Owned(ref s) => s,
+        }
+    }
+}
+
+impl<'de, 's> From<Reference<'de, 's, str>> for ParsedKey<'de> {
+    fn from(reference: Reference<'de, 's, str>) -> Self {
+        match reference {
+            Reference::Borrowed(s) => ParsedKey::Borrowed(s),
+            Reference::Copied(s) => ParsedKey::Owned(String::from(s)),
+        }
+    }
+}
+
+macro_rules! deserialize_integer_parsed_key {
+    ($deserialize:ident => $visit:ident) => {
+        fn $deserialize<V>(self, visitor: V) -> Result<V::Value>
+        where
+            V: de::Visitor<'de>,
+        {
+            debug!($deserialize);
+            match (self.as_str().parse(), self) {
+                (Ok(integer), _) => visitor.$visit(integer),
+                (Err(_), &ParsedKey::Borrowed(s)) => visitor.visit_borrowed_str(s),
+                (Err(_), &ParsedKey::Owned(ref s)) => visitor.visit_str(s),
+            }
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &ParsedKey<'de> {
+    type Error = Error;
+
+    #[inline]
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            ParsedKey::Borrowed(s) => visitor.visit_borrowed_str(s),
+            ParsedKey::Owned(ref s) => visitor.visit_str(s),
+        }
+    }
+
+    deserialize_integer_parsed_key!(deserialize_i8 => visit_i8);
+    deserialize_integer_parsed_key!(deserialize_i16 => visit_i16);
+    deserialize_integer_parsed_key!(deserialize_i32 => visit_i32);
+    deserialize_integer_parsed_key!(deserialize_i64 => visit_i64);
+    deserialize_integer_parsed_key!(deserialize_u8 => visit_u8);
+    deserialize_integer_parsed_key!(deserialize_u16 => visit_u16);
+    deserialize_integer_parsed_key!(deserialize_u32 => visit_u32);
+    deserialize_integer_parsed_key!(deserialize_u64 => visit_u64);
+
+    #[inline]
+    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        // Map keys cannot be null.
+        visitor.visit_some(self)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        visitor.visit_newtype_struct(self)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            ParsedKey::Borrowed(s) => visitor.visit_enum(de::value::BorrowedStrDeserializer::new(s)),
+            ParsedKey::Owned(ref s) => {
+                visitor.visit_enum(de::IntoDeserializer::into_deserializer(s.as_str()))
+            }
+        }
+    }
+
+    #[inline]
+    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
+    where
+        V: de::Visitor<'de>,
+    {
+        match *self {
+            ParsedKey::Borrowed(s) => visitor.visit_borrowed_bytes(s.as_bytes()),
+            ParsedKey::Owned(ref s) => visitor.visit_bytes(s.as_bytes()),
+        }
+    }
+
+    #[inline]
+    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
     where
-        V: de::DeserializeSeed<'de>,
+        V: de::Visitor<'de>,
     {
-        debug!(next_value_seed);
-        try!(self.de.parse_object_colon());
-
-        let ret = seed.deserialize(&mut *self.de);
-
-        let mut had_newline = false;
-        match try!(self.de.parse_whitespace_get_newline(&mut had_newline)) {
-            Some(ch) => {
-                if ch == b',' {
-                    self.de.eat_char();
-                } else if ch != b'}' && !had_newline {
-                    return Err(self.de.peek_error(ErrorCode::ExpectedListCommaOrEnd));
-                }
-            }
-            None => {
-                return Err(self.de.peek_error(ErrorCode::EofWhileParsingList));
-            }
-        }
+        self.deserialize_bytes(visitor)
+    }
 
-        ret
+    forward_to_deserialize_any! {
+        bool f32 f64 char str string unit unit_struct seq tuple tuple_struct map
+        struct identifier ignored_any
     }
 }
 
 struct VariantAccess<'a, R: 'a> {
     de: &'a mut Deserializer<R>,
+    /// The name of the variant, for the path of errors.
+    variant: String,
 }
 
 impl<'a, R: 'a> VariantAccess<'a, R> {
     fn new(de: &'a mut Deserializer<R>) -> Self {
-        VariantAccess { de: de }
+        de.root = false;
+        VariantAccess {
+            de: de,
+            variant: String::new(),
+        }
     }
 }
 
@@ -2028,12 +4048,17 @@ impl<'de, 'a, R: Read<'de> + 'a> de::EnumAccess<'de> for VariantAccess<'a, R> {
     type Error = Error;
     type Variant = Self;
 
-    fn variant_seed<V>(self, seed: V) -> Result<(V::Value, Self)>
+    fn variant_seed<V>(mut self, seed: V) -> Result<(V::Value, Self)>
     where
         V: de::DeserializeSeed<'de>,
     {
         debug!(variant_seed);
-        let val = try!(seed.deserialize(&mut *self.de));
+        if try!(self.de.parse_whitespace()).is_none() {
+            return Err(self.de.peek_error(ErrorCode::EofWhileParsingValue));
+        }
+        let (variant, range) = try!(self.de.parse_key_range());
+        self.variant.push_str(variant.as_str());
+        let val = try!(seed.deserialize(&variant).map_err(|err| self.de.fix_key_position(err, range)));
         try!(self.de.parse_object_colon());
         Ok((val, self))
     }
@@ -2051,7 +4076,11 @@ impl<'de, 'a, R: Read<'de> + 'a> de::VariantAccess<'de> for VariantAccess<'a, R>
         T: de::DeserializeSeed<'de>,
     {
         debug!(newtype_variant_seed);
-        seed.deserialize(self.de)
+        let variant = self.variant;
+        let len = self.de.enter_member(&variant, None);
+        let ret = seed.deserialize(&mut *self.de).map_err(|err| err.prepend_key(&variant));
+        self.de.leave(len);
+        ret
     }
 
     fn tuple_variant<V>(self, _len: usize, visitor: V) -> Result<V::Value>
@@ -2059,7 +4088,12 @@ impl<'de, 'a, R: Read<'de> + 'a> de::VariantAccess<'de> for VariantAccess<'a, R>
         V: de::Visitor<'de>,
     {
         debug!(tuple_variant);
-        de::Deserializer::deserialize_seq(self.de, visitor)
+        let variant = self.variant;
+        let len = self.de.enter_member(&variant, None);
+        let ret = de::Deserializer::deserialize_seq(&mut *self.de, visitor)
+            .map_err(|err| err.prepend_key(&variant));
+        self.de.leave(len);
+        ret
     }
 
     fn struct_variant<V>(self, fields: &'static [&'static str], visitor: V) -> Result<V::Value>
@@ -2067,7 +4101,12 @@ impl<'de, 'a, R: Read<'de> + 'a> de::VariantAccess<'de> for VariantAccess<'a, R>
         V: de::Visitor<'de>,
     {
         debug!(struct_variant);
-        de::Deserializer::deserialize_struct(self.de, "", fields, visitor)
+        let variant = self.variant;
+        let len = self.de.enter_member(&variant, None);
+        let ret = de::Deserializer::deserialize_struct(&mut *self.de, "", fields, visitor)
+            .map_err(|err| err.prepend_key(&variant));
+        self.de.leave(len);
+        ret
     }
 }
 
@@ -2124,157 +4163,6 @@ impl<'de, 'a, R: Read<'de> + 'a> de::VariantAccess<'de> for UnitVariantAccess<'a
     }
 }
 
-/// Only deserialize from this after peeking a '"' byte! Otherwise it may
-/// deserialize invalid JSON successfully.
-struct MapKey<'a, R: 'a> {
-    de: &'a mut Deserializer<R>,
-}
-
-macro_rules! deserialize_integer_key {
-    ($deserialize:ident => $visit:ident) => {
-        fn $deserialize<V>(self, visitor: V) -> Result<V::Value>
-        where
-            V: de::Visitor<'de>,
-        {
-            debug!($deserialize);
-            let string;
-            match try!(self.de.peek()) {
-                Some(b'"') => {
-                    self.de.eat_char();
-                    self.de.str_buf.clear();
-                    string = try!(self.de.read.parse_double_str(&mut self.de.str_buf))
-                }
-                Some(b'\'') => {
-                    self.de.eat_char();
-                    self.de.str_buf.clear();
-                    let col;
-                    {
-                        col = self.de.read.peek_position().column - 1;
-                    }
-                    string = try!(self.de.read.parse_single_str(&mut self.de.str_buf, col))
-                }
-                Some(_) => {
-                    self.de.str_buf.clear();
-                    string = try!(self.de.read.parse_none_str(&mut self.de.str_buf));
-                }
-                None => {
-                    return Err(self.de.peek_error(ErrorCode::EofWhileParsingObject));
-                }
-            }
-
-            match (string.parse(), string) {
-                (Ok(integer), _) => visitor.$visit(integer),
-                (Err(_), Reference::Borrowed(s)) => visitor.visit_borrowed_str(s),
-                (Err(_), Reference::Copied(s)) => visitor.visit_str(s),
-            }
-        }
-    }
-}
-
-impl<'de, 'a, R> de::Deserializer<'de> for MapKey<'a, R>
-where
-    R: Read<'de>,
-{
-    type Error = Error;
-
-    #[inline]
-    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        debug!(deserialize_any);
-        match try!(self.de.peek_or_null()) {
-            b'"' => {
-                self.de.eat_char();
-                self.de.str_buf.clear();
-                match try!(self.de.read.parse_double_str(&mut self.de.str_buf)) {
-                    Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
-                    Reference::Copied(s) => visitor.visit_str(s),
-                }
-            },
-            b'\'' => {
-                self.de.eat_char();
-                self.de.str_buf.clear();
-                let col;
-                {
-                    col = self.de.read.peek_position().column - 1;
-                }
-                match try!(self.de.read.parse_single_str(&mut self.de.str_buf, col)) {
-                    Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
-                    Reference::Copied(s) => visitor.visit_str(s),
-                }
-            },
-            _ => {
-                self.de.str_buf.clear();
-                match try!(self.de.read.parse_member_name(&mut self.de.str_buf)) {
-                    Reference::Borrowed(s) => visitor.visit_borrowed_str(s),
-                    Reference::Copied(s) => visitor.visit_str(s),
-                }
-            },
-        }
-    }
-
-    deserialize_integer_key!(deserialize_i8 => visit_i8);
-    deserialize_integer_key!(deserialize_i16 => visit_i16);
-    deserialize_integer_key!(deserialize_i32 => visit_i32);
-    deserialize_integer_key!(deserialize_i64 => visit_i64);
-    deserialize_integer_key!(deserialize_u8 => visit_u8);
-    deserialize_integer_key!(deserialize_u16 => visit_u16);
-    deserialize_integer_key!(deserialize_u32 => visit_u32);
-    deserialize_integer_key!(deserialize_u64 => visit_u64);
-
-    #[inline]
-    fn deserialize_option<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        // Map keys cannot be null.
-        visitor.visit_some(self)
-    }
-
-    #[inline]
-    fn deserialize_newtype_struct<V>(self, _name: &'static str, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        visitor.visit_newtype_struct(self)
-    }
-
-    #[inline]
-    fn deserialize_enum<V>(
-        self,
-        name: &'static str,
-        variants: &'static [&'static str],
-        visitor: V,
-    ) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.de.deserialize_enum(name, variants, visitor)
-    }
-
-    #[inline]
-    fn deserialize_bytes<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.de.deserialize_bytes(visitor)
-    }
-
-    #[inline]
-    fn deserialize_byte_buf<V>(self, visitor: V) -> Result<V::Value>
-    where
-        V: de::Visitor<'de>,
-    {
-        self.de.deserialize_bytes(visitor)
-    }
-
-    forward_to_deserialize_any! {
-        bool f32 f64 char str string unit unit_struct seq tuple tuple_struct map
-        struct identifier ignored_any
-    }
-}
-
 //////////////////////////////////////////////////////////////////////////////
 
 /// Iterator that deserializes a stream into multiple JSON values.
@@ -2322,8 +4210,11 @@ where
     ///   - Deserializer::from_reader(...).into_iter()
     pub fn new(read: R) -> Self {
         let offset = read.byte_offset();
+        let mut de = Deserializer::new(read);
+        // A braceless root object would swallow every following value.
+        de.root = false;
         StreamDeserializer {
-            de: Deserializer::new(read),
+            de: de,
             offset: offset,
             output: PhantomData,
             lifetime: PhantomData,
@@ -2334,6 +4225,8 @@ where
     ///
     /// If a stream deserializer returns an EOF error, new data can be joined to
     /// `old_data[stream.byte_offset()..]` to try again.
+    /// [`events::PushParser`] does this by itself, for input that arrives in
+    /// chunks.
     ///
     /// ```rust
     /// let data = b"[0] [1] [";
@@ -2361,6 +4254,7 @@ where
     /// example illustrating this.
     ///
     /// [serde-rs/json#70]: https://github.com/serde-rs/json/issues/70
+    /// [`events::PushParser`]: ../events/struct.PushParser.html
     pub fn byte_offset(&self) -> usize {
         self.offset
     }
@@ -2372,7 +4266,7 @@ where
             Some(b'}') | Some(b',') | Some(b':') | None => Ok(()),
             Some(_) => {
                 let pos = self.de.read.peek_position();
-                Err(Error::syntax(ErrorCode::TrailingCharacters, pos.line, pos.column))
+                Err(Error::syntax_at(ErrorCode::TrailingCharacters, pos, pos.start..pos.end))
             },
         }
     }
@@ -2428,12 +4322,53 @@ fn from_trait<'de, R, T>(read: R) -> Result<T>
 where
     R: Read<'de>,
     T: de::Deserialize<'de>,
+{
+    from_trait_with(read, Profile::hjson())
+}
+
+fn from_trait_with<'de, R, T>(read: R, profile: Profile) -> Result<T>
+where
+    R: Read<'de>,
+    T: de::Deserialize<'de>,
+{
+    let mut de = Deserializer::new(read);
+    de.set_profile(profile);
+    let value = try!(de::Deserialize::deserialize(&mut de));
+
+    // Make sure the whole stream has been consumed.
+    try!(de.end());
+    Ok(value)
+}
+
+fn from_trait_with_warnings<'de, R, T>(read: R) -> Result<(T, Vec<Warning>)>
+where
+    R: Read<'de>,
+    T: de::Deserialize<'de>,
+{
+    let mut de = Deserializer::new(read);
+    de.collect_warnings();
+    let value = try!(de::Deserialize::deserialize(&mut de));
+
+    // Make sure the whole stream has been consumed.
+    try!(de.end());
+    Ok((value, de.take_warnings()))
+}
+
+fn from_trait_with_ignored<'de, R, T, F>(read: R, mut f: F) -> Result<T>
+where
+    R: Read<'de>,
+    T: de::Deserialize<'de>,
+    F: FnMut(&str, Span),
 {
     let mut de = Deserializer::new(read);
+    de.collect_ignored();
     let value = try!(de::Deserialize::deserialize(&mut de));
 
     // Make sure the whole stream has been consumed.
     try!(de.end());
+    for (path, span) in de.take_ignored() {
+        f(&path, span);
+    }
     Ok(value)
 }
 
@@ -2577,3 +4512,183 @@ where
 {
     from_trait(read::StrRead::new(s))
 }
+
+/// Like `from_reader`, but only accepts the Hjson extensions allowed by
+/// `profile`.
+///
+/// ```rust
+/// use serde_hjson::{Profile, Value};
+///
+/// let err = serde_hjson::from_reader_with::<_, Value>(&b"[1, 2] // two"[..], Profile::json())
+///     .unwrap_err();
+/// assert_eq!(err.to_string(), "comments are not allowed at line 1 column 9");
+/// ```
+pub fn from_reader_with<R, T>(rdr: R, profile: Profile) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    from_trait_with(read::IoRead::new(rdr), profile)
+}
+
+/// Like `from_slice`, but only accepts the Hjson extensions allowed by
+/// `profile`.
+pub fn from_slice_with<'a, T>(v: &'a [u8], profile: Profile) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with(read::SliceRead::new(v), profile)
+}
+
+/// Like `from_str`, but only accepts the Hjson extensions allowed by
+/// `profile`.
+pub fn from_str_with<'a, T>(s: &'a str, profile: Profile) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with(read::StrRead::new(s), profile)
+}
+
+/// Like `from_reader`, but also returns the input that is valid but probably
+/// not what was meant. See `WarningKind` for what is reported.
+pub fn from_reader_with_warnings<R, T>(rdr: R) -> Result<(T, Vec<Warning>)>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    from_trait_with_warnings(read::IoRead::new(rdr))
+}
+
+/// Like `from_slice`, but also returns the input that is valid but probably
+/// not what was meant. See `WarningKind` for what is reported.
+pub fn from_slice_with_warnings<'a, T>(v: &'a [u8]) -> Result<(T, Vec<Warning>)>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with_warnings(read::SliceRead::new(v))
+}
+
+/// Like `from_str`, but also returns the input that is valid but probably not
+/// what was meant. See `WarningKind` for what is reported.
+pub fn from_str_with_warnings<'a, T>(s: &'a str) -> Result<(T, Vec<Warning>)>
+where
+    T: de::Deserialize<'a>,
+{
+    from_trait_with_warnings(read::StrRead::new(s))
+}
+
+/// Like `from_reader`, but calls `f` with the path and the span of the name of
+/// every object member that was skipped because `T` has no field for it. See
+/// `from_str_with_ignored`.
+pub fn from_reader_with_ignored<R, T, F>(rdr: R, f: F) -> Result<T>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+    F: FnMut(&str, Span),
+{
+    from_trait_with_ignored(read::IoRead::new(rdr), f)
+}
+
+/// Like `from_slice`, but calls `f` with the path and the span of the name of
+/// every object member that was skipped because `T` has no field for it. See
+/// `from_str_with_ignored`.
+pub fn from_slice_with_ignored<'a, T, F>(v: &'a [u8], f: F) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+    F: FnMut(&str, Span),
+{
+    from_trait_with_ignored(read::SliceRead::new(v), f)
+}
+
+/// Like `from_str`, but calls `f` with the path and the span of the name of
+/// every object member that was skipped because `T` has no field for it, in
+/// the order they appear in the input. The paths are in the format of
+/// `Error::path`. Nothing is reported if deserialization fails.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// #
+/// # extern crate serde_hjson;
+/// #
+/// #[derive(Deserialize)]
+/// struct Config {
+///     logging: Logging,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Logging {
+///     level: String,
+/// }
+///
+/// # fn main() {
+/// let s = "
+/// logging: {
+///   level: info
+///   colour: true
+/// }
+/// ";
+/// let mut unknown = Vec::new();
+/// let config: Config = serde_hjson::from_str_with_ignored(s, |path, span| {
+///     unknown.push(format!("unknown setting `{}` in app.hjson:{}", path, span.line));
+/// }).unwrap();
+///
+/// assert_eq!(config.logging.level, "info");
+/// assert_eq!(unknown, ["unknown setting `logging.colour` in app.hjson:4"]);
+/// # }
+/// ```
+pub fn from_str_with_ignored<'a, T, F>(s: &'a str, f: F) -> Result<T>
+where
+    T: de::Deserialize<'a>,
+    F: FnMut(&str, Span),
+{
+    from_trait_with_ignored(read::StrRead::new(s), f)
+}
+
+/// Parses a `Value` from an IO stream of Hjson, carrying on after syntax
+/// errors to find all of them at once. See
+/// `Deserializer::parse_value_recovering`.
+pub fn from_reader_recovering<R>(rdr: R) -> Recovered
+where
+    R: io::Read,
+{
+    Deserializer::from_reader(rdr).parse_value_recovering()
+}
+
+/// Parses a `Value` from bytes of Hjson text, carrying on after syntax errors
+/// to find all of them at once. See `Deserializer::parse_value_recovering`.
+pub fn from_slice_recovering(v: &[u8]) -> Recovered {
+    Deserializer::from_slice(v).parse_value_recovering()
+}
+
+/// Parses a `Value` from a string of Hjson text, carrying on after syntax
+/// errors to find all of them at once. See
+/// `Deserializer::parse_value_recovering`.
+///
+/// ```rust
+/// let recovered = serde_hjson::from_str_recovering("[1, 2,, 3");
+/// assert_eq!(recovered.value, serde_hjson::from_str::<serde_hjson::Value>("[1, 2, 3]").unwrap());
+/// assert_eq!(recovered.errors.len(), 2);
+/// assert!(recovered.placeholders.is_empty());
+///
+/// let recovered = serde_hjson::from_str_recovering("[null, \"a\\q\"]");
+/// assert_eq!(recovered.value, serde_hjson::from_str::<serde_hjson::Value>("[null, null]").unwrap());
+/// assert_eq!(recovered.placeholders, ["/1"]);
+/// ```
+pub fn from_str_recovering(s: &str) -> Recovered {
+    Deserializer::from_str(s).parse_value_recovering()
+}
+
+// Not public API. Should be pub(crate).
+//
+// The comments, member names and values of a document with their byte ranges,
+// as the deserializer finds them while it carries on after syntax errors, for
+// the `syntax` module. Tokens may come out of order and inside of others, like
+// a comment after a word that turned out to start a quoteless string.
+#[doc(hidden)]
+pub fn lex(s: &str) -> Vec<(TokenKind, Range<usize>)> {
+    let mut de = Deserializer::from_str(s);
+    de.tokens = Some(Vec::new());
+    de.parse_value_recovering();
+    de.tokens.unwrap_or_default()
+}
@@ -0,0 +1,328 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Reports of errors along with the input they were found in.
+//!
+//! A [`Diagnostic`] renders an `Error` the way rustc renders its errors: the
+//! error code and message, the location, the offending line with the bad
+//! token underlined, and a hint for common mistakes.
+//!
+//! ```rust
+//! use serde_hjson::diagnostic::Diagnostic;
+//! use serde_hjson::Value;
+//!
+//! let source = "servers: [\n  {host: \"a\", port: 80,,}\n]\n";
+//! let err = serde_hjson::from_str::<Value>(source).unwrap_err();
+//!
+//! let report = Diagnostic::new(&err, source).file_name("config.hjson").to_string();
+//! assert_eq!(report, "\
+//! error[E0213]: extra comma
+//!  --> config.hjson:2:24
+//!   |
+//! 2 |   {host: \"a\", port: 80,,}
+//!   |                        ^
+//! ");
+//! ```
+//!
+//! [`Diagnostic`]: struct.Diagnostic.html
+
+use std::cmp;
+use std::fmt::{self, Display};
+
+use error::{Category, Error, ErrorKind};
+
+/// An `Error` along with the input it was found in, rendered by its `Display`
+/// implementation.
+#[derive(Clone, Copy, Debug)]
+pub struct Diagnostic<'a> {
+    error: &'a Error,
+    source: &'a str,
+    file_name: Option<&'a str>,
+    color: bool,
+}
+
+impl<'a> Diagnostic<'a> {
+    /// Renders `error`, which was returned when parsing `source`.
+    pub fn new(error: &'a Error, source: &'a str) -> Self {
+        Diagnostic {
+            error: error,
+            source: source,
+            file_name: None,
+            color: false,
+        }
+    }
+
+    /// Sets the file name shown along with the line and column.
+    pub fn file_name(mut self, file_name: &'a str) -> Self {
+        self.file_name = Some(file_name);
+        self
+    }
+
+    /// Whether to color the report with ANSI escape codes for terminals. Off
+    /// by default.
+    pub fn color(mut self, color: bool) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// A hint on how to fix the error, if it looks like a common mistake.
+    pub fn hint(&self) -> Option<&'static str> {
+        let line = self.error.line();
+        if line == 0 {
+            return None;
+        }
+
+        // The lines up to the error, closest first.
+        let mut lines = self.source.split('\n').take(line).collect::<Vec<_>>();
+        lines.reverse();
+        let current = lines.first().and_then(|line| quoteless_value(line));
+
+        match self.error.classify() {
+            Category::Data => {
+                match current {
+                    Some(value) if value.contains('#') || value.contains("//") => {
+                        Some(QUOTELESS_COMMENT)
+                    }
+                    _ => None,
+                }
+            }
+            Category::Syntax if self.error.kind() == ErrorKind::UnexpectedNewline => {
+                Some(MULTILINE)
+            }
+            Category::Syntax | Category::Eof => {
+                // A syntax error usually shows up on the line after a
+                // quoteless string swallowed a closing bracket, but only at the
+                // end of the input when the bracket was the last one.
+                let candidates = if self.error.is_eof() {
+                    lines.len()
+                } else {
+                    2
+                };
+                let swallowed = lines
+                    .iter()
+                    .filter(|line| !line.trim().is_empty())
+                    .take(candidates)
+                    .filter_map(|line| quoteless_value(line))
+                    .any(|value| value.contains(|ch| ch == ',' || ch == ']' || ch == '}'));
+                if swallowed {
+                    Some(QUOTELESS_PUNCTUATION)
+                } else {
+                    None
+                }
+            }
+            Category::Io => None,
+        }
+    }
+}
+
+const QUOTELESS_COMMENT: &str = "quoteless strings end at the end of the line, so `#` and \
+                                 `//` are part of the string; wrap the string in quotes to \
+                                 follow it with a comment";
+const QUOTELESS_PUNCTUATION: &str = "quoteless strings end at the end of the line, including \
+                                     any `,`, `]` or `}`; wrap the string in quotes to end it \
+                                     earlier";
+const MULTILINE: &str = "use a `'''` multiline string for text spanning several lines";
+
+struct Style {
+    error: &'static str,
+    bold: &'static str,
+    gutter: &'static str,
+    reset: &'static str,
+}
+
+const PLAIN: Style = Style {
+    error: "",
+    bold: "",
+    gutter: "",
+    reset: "",
+};
+
+const ANSI: Style = Style {
+    error: "\x1b[1;31m",
+    bold: "\x1b[1m",
+    gutter: "\x1b[1;34m",
+    reset: "\x1b[0m",
+};
+
+impl<'a> Display for Diagnostic<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let style = if self.color { &ANSI } else { &PLAIN };
+        try!(writeln!(
+            f,
+            "{}error[E{:04}]{}{}: {}{}",
+            style.error,
+            self.error.kind().code(),
+            style.reset,
+            style.bold,
+            self.error.message(),
+            style.reset
+        ));
+
+        let line_number = self.error.line();
+        let mut lines = self.source.split('\n');
+        let line_start: usize = lines
+            .by_ref()
+            .take(line_number.saturating_sub(1))
+            .map(|line| line.len() + 1)
+            .sum();
+        let line = if line_number == 0 { None } else { lines.next() };
+        let line = match line {
+            Some(line) => line.trim_end_matches('\r'),
+            None => {
+                if let Some(file_name) = self.file_name {
+                    try!(writeln!(f, " {}-->{} {}", style.gutter, style.reset, file_name));
+                }
+                return self.fmt_hint(f, style, "");
+            }
+        };
+
+        let (start, end) = self.underline(line, line_start);
+        let column = line[..start].chars().count() + 1;
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        try!(write!(f, "{}{}-->{} ", gutter, style.gutter, style.reset));
+        if let Some(file_name) = self.file_name {
+            try!(write!(f, "{}:", file_name));
+        }
+        try!(writeln!(f, "{}:{}", line_number, column));
+        try!(writeln!(f, "{} {}|{}", gutter, style.gutter, style.reset));
+        try!(write!(f, "{}{} |{}", style.gutter, line_number, style.reset));
+        if line.is_empty() {
+            try!(writeln!(f));
+        } else {
+            try!(writeln!(f, " {}", line.replace('\t', "    ")));
+        }
+        try!(writeln!(
+            f,
+            "{} {}|{} {}{}{}{}",
+            gutter,
+            style.gutter,
+            style.reset,
+            " ".repeat(width(&line[..start])),
+            style.error,
+            "^".repeat(cmp::max(width(&line[start..end]), 1)),
+            style.reset
+        ));
+        self.fmt_hint(f, style, &gutter)
+    }
+}
+
+impl<'a> Diagnostic<'a> {
+    fn fmt_hint(&self, f: &mut fmt::Formatter, style: &Style, gutter: &str) -> fmt::Result {
+        match self.hint() {
+            Some(hint) => {
+                writeln!(
+                    f,
+                    "{} {}={} {}help{}: {}",
+                    gutter,
+                    style.gutter,
+                    style.reset,
+                    style.bold,
+                    style.reset,
+                    hint
+                )
+            }
+            None => Ok(()),
+        }
+    }
+
+    /// The byte range of the line to underline, which starts at `line_start`
+    /// in the source. It is empty past the end of the line.
+    ///
+    /// That is the part of the error's byte range on the line if it has one,
+    /// and otherwise a guess from the column.
+    fn underline(&self, line: &str, line_start: usize) -> (usize, usize) {
+        if let Some(range) = self.error.byte_range() {
+            let line_end = line_start + line.len();
+            // A range ending at a newline or at the end of the input ends up
+            // empty past the end of the line.
+            let start = cmp::min(cmp::max(range.start, line_start), line_end) - line_start;
+            let end = cmp::min(cmp::max(range.end, line_start), line_end) - line_start;
+            let on_line = range.start <= line_end && range.end >= line_start;
+            if on_line && line.is_char_boundary(start) && line.is_char_boundary(end) {
+                return (start, end);
+            }
+        }
+
+        let mut start = self.error.column().saturating_sub(1);
+        if start >= line.len() {
+            // Past the end of the line, like an unexpected newline or EOF.
+            return (line.len(), line.len());
+        }
+        while !line.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let ch = line[start..].chars().next().unwrap();
+        let mut end = start + ch.len_utf8();
+        if is_delimiter(ch) {
+            return (start, end);
+        }
+
+        if self.error.is_data() {
+            // Data errors are found at the end of the value or member name, so
+            // underline all of it up to there.
+            let value_start = line[..start]
+                .rfind(|ch| ch == ':' || ch == ',' || ch == '[' || ch == '{')
+                .map_or(0, |i| i + 1);
+            let value = &line[value_start..start];
+            start = value_start + (value.len() - value.trim_start().len());
+        } else {
+            end += line[end..].find(|ch: char| ch.is_whitespace() || is_delimiter(ch))
+                .unwrap_or(line.len() - end);
+        }
+        (start, end)
+    }
+}
+
+fn is_delimiter(ch: char) -> bool {
+    match ch {
+        ',' | ':' | '[' | ']' | '{' | '}' | '"' | '\'' => true,
+        _ => ch.is_whitespace(),
+    }
+}
+
+/// The number of columns the text takes up in a terminal, with tabs expanded
+/// like in the printed source line.
+fn width(s: &str) -> usize {
+    s.chars().map(|ch| if ch == '\t' { 4 } else { 1 }).sum()
+}
+
+/// The value of the member on a line if it is a quoteless string, which runs
+/// to the end of the line.
+fn quoteless_value(line: &str) -> Option<&str> {
+    let line = line.trim_start_matches(|ch: char| ch == '{' || ch == '[' || ch.is_whitespace());
+    let rest = match line.chars().next() {
+        Some(quote) if quote == '"' || quote == '\'' => {
+            match line[1..].find(quote) {
+                Some(i) => &line[i + 2..],
+                None => return None,
+            }
+        }
+        _ => line,
+    };
+
+    let value = match rest.find(':') {
+        Some(i) => rest[i + 1..].trim(),
+        None => return None,
+    };
+    match value.chars().next() {
+        None | Some('"') | Some('\'') | Some('[') | Some('{') | Some('-') | Some('0'..='9') => {
+            return None;
+        }
+        _ => {}
+    }
+    for literal in &["true", "false", "null"] {
+        if value.starts_with(literal) &&
+            !value[literal.len()..].starts_with(|ch: char| ch.is_alphanumeric())
+        {
+            return None;
+        }
+    }
+    Some(value)
+}
@@ -0,0 +1,697 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Edit Hjson documents without losing their formatting.
+//!
+//! Deserializing into a `Value` and serializing it again drops comments,
+//! blank lines and the quoting style of strings. A [`Document`] keeps the
+//! original text instead and only rewrites the parts that are edited, so
+//! everything else comes out byte for byte as it went in.
+//!
+//! Values are addressed by JSON Pointers, as in [`Value::pointer`].
+//!
+//! ```rust
+//! use serde_hjson::edit::Document;
+//!
+//! let mut doc: Document = "
+//! // Deployed by the release script
+//! name: server
+//! version: \"1.2.3\" // bumped automatically
+//! ".parse().unwrap();
+//!
+//! doc.set("/version", &"1.2.4").unwrap();
+//! assert_eq!(doc.to_string(), "
+//! // Deployed by the release script
+//! name: server
+//! version: \"1.2.4\" // bumped automatically
+//! ");
+//! ```
+//!
+//! [`Document`]: struct.Document.html
+//! [`Value::pointer`]: ../enum.Value.html#method.pointer
+
+use std::fmt::{self, Display};
+use std::str::FromStr;
+
+use serde::de::Error as DeError;
+use serde::ser::Serialize;
+
+use de::from_str;
+use error::{Error, Result};
+use ser::{to_string, to_string_pretty};
+use syntax::{self, Child, NodeKind, Token, TokenKind};
+use value::Value;
+
+/// An Hjson document that remembers its formatting.
+pub struct Document {
+    text: String,
+    root: Node,
+    value: Value,
+}
+
+impl Document {
+    /// Parses an Hjson document.
+    pub fn parse(text: &str) -> Result<Document> {
+        let value = try!(from_str(text));
+        let root = layout(text);
+        Ok(Document {
+            text: String::from(text),
+            root: root,
+            value: value,
+        })
+    }
+
+    /// The text of the document, including all edits so far.
+    pub fn as_str(&self) -> &str {
+        &self.text
+    }
+
+    /// The value of the whole document.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+
+    /// Looks up a value by a JSON Pointer.
+    pub fn get(&self, pointer: &str) -> Option<&Value> {
+        self.value.pointer(pointer)
+    }
+
+    /// Replaces the existing value at `pointer`.
+    pub fn set<T: ?Sized + Serialize>(&mut self, pointer: &str, value: &T) -> Result<()> {
+        let tokens = try!(parse_pointer(pointer));
+        let (start, end) = match self.root.find(&tokens) {
+            Some(node) => (node.start, node.end),
+            None => return Err(not_found(pointer)),
+        };
+
+        if let Some(text) = try!(render_requoted(value, &self.text[start..end])) {
+            return self.apply(vec![(start, end, text)]);
+        }
+        let rest = &self.text[end..line_end(&self.text, end)];
+        let member = self.text[..start].trim_end().ends_with(':');
+        let newline = newline(&self.text);
+        let text = try!(render(value, line_indent(&self.text, start), rest, member, newline));
+        if text.starts_with(newline) {
+            // Start the value on the line after the member name.
+            let colon = self.text[..start].trim_end().len();
+            return self.apply(vec![(colon, end, text)]);
+        }
+        self.apply(vec![(start, end, text)])
+    }
+
+    /// Adds a value at `pointer`.
+    ///
+    /// If the parent of `pointer` is an object, the member is added after the
+    /// existing ones, or replaced if it already exists. If the parent is an
+    /// array, the value is inserted at the given index, where `-` appends it.
+    pub fn insert<T: ?Sized + Serialize>(&mut self, pointer: &str, value: &T) -> Result<()> {
+        let mut tokens = try!(parse_pointer(pointer));
+        let token = match tokens.pop() {
+            Some(token) => token,
+            None => return self.set(pointer, value),
+        };
+
+        let edit = {
+            let parent = match self.root.find(&tokens) {
+                Some(node) => node,
+                None => return Err(not_found(pointer)),
+            };
+            match parent.kind {
+                Kind::Object(ref container) => {
+                    if container.entries.iter().any(|entry| entry.key_is(&token)) {
+                        return self.set(pointer, value);
+                    }
+                    try!(container.insert(&self.text, parent, None, Some(&token), value))
+                }
+                Kind::Array(ref container) => {
+                    let index = if token == "-" {
+                        container.entries.len()
+                    } else {
+                        match parse_index(&token) {
+                            Some(index) if index <= container.entries.len() => index,
+                            _ => return Err(not_found(pointer)),
+                        }
+                    };
+                    try!(container.insert(&self.text, parent, Some(index), None, value))
+                }
+                Kind::Scalar => return Err(not_found(pointer)),
+            }
+        };
+
+        self.apply(edit)
+    }
+
+    /// Removes the value at `pointer` and returns it.
+    ///
+    /// The member or array element is removed along with its separator, and
+    /// with its whole line if it is on a line of its own. Comments on the
+    /// lines above it are kept.
+    pub fn remove(&mut self, pointer: &str) -> Result<Value> {
+        let mut tokens = try!(parse_pointer(pointer));
+        let removed = match self.value.pointer(pointer) {
+            Some(value) => value.clone(),
+            None => return Err(not_found(pointer)),
+        };
+        let token = match tokens.pop() {
+            Some(token) => token,
+            None => return Err(DeError::custom("cannot remove the root of a document")),
+        };
+
+        let edit = {
+            let container = match self.root.find(&tokens) {
+                Some(&Node { kind: Kind::Object(ref container), .. }) => container,
+                Some(&Node { kind: Kind::Array(ref container), .. }) => container,
+                _ => return Err(not_found(pointer)),
+            };
+            let index = match container.position(&token) {
+                Some(index) => index,
+                None => return Err(not_found(pointer)),
+            };
+            container.remove(&self.text, index)
+        };
+
+        try!(self.apply(edit));
+        Ok(removed)
+    }
+
+    /// Applies a list of replacements, given in increasing order of position.
+    fn apply(&mut self, edit: Vec<(usize, usize, String)>) -> Result<()> {
+        let mut text = self.text.clone();
+        for &(start, end, ref replacement) in edit.iter().rev() {
+            if start > end || text.get(start..end).is_none() {
+                return Err(DeError::custom("cannot edit the document at this position"));
+            }
+            text = format!("{}{}{}", &text[..start], replacement, &text[end..]);
+        }
+        let doc = try!(Document::parse(&text));
+        *self = doc;
+        Ok(())
+    }
+}
+
+impl FromStr for Document {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Document> {
+        Document::parse(s)
+    }
+}
+
+impl Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.text)
+    }
+}
+
+fn not_found(pointer: &str) -> Error {
+    DeError::custom(format_args!("no value at `{}`", pointer))
+}
+
+fn parse_pointer(pointer: &str) -> Result<Vec<String>> {
+    if pointer.is_empty() {
+        return Ok(Vec::new());
+    }
+    if !pointer.starts_with('/') {
+        return Err(DeError::custom(format_args!("invalid JSON Pointer `{}`", pointer)));
+    }
+    Ok(
+        pointer
+            .split('/')
+            .skip(1)
+            .map(|x| x.replace("~1", "/").replace("~0", "~"))
+            .collect(),
+    )
+}
+
+/// Serializes a value to be placed in a document at the given indentation,
+/// followed by `rest` on the same line.
+///
+/// Values with more content after them on the same line are written as JSON,
+/// to keep a quoteless string from swallowing the rest of the line. Only a
+/// comment may follow a value that spans multiple lines. A multiline string
+/// that is the value of a member starts on the next line, like the serializer
+/// writes it, and is returned with a leading newline.
+///
+/// Lines are broken with `newline`. The deserializer does not read multiline
+/// strings with `\r\n` line breaks, so those are written as JSON instead.
+fn render<T: ?Sized + Serialize>(
+    value: &T,
+    indent: &str,
+    rest: &str,
+    member: bool,
+    newline: &str,
+) -> Result<String> {
+    let rest = rest.trim();
+    if !rest.is_empty() && !is_comment(rest) {
+        return to_string(value);
+    }
+
+    let pretty = try!(to_string_pretty(value));
+    if !rest.is_empty() && !pretty.contains('\n') {
+        return to_string(value);
+    }
+    if newline != "\n" && pretty.contains("'''") {
+        return to_string(value);
+    }
+    let mut text = String::new();
+    let mut indent = String::from(indent);
+    if member && pretty.starts_with("'''") {
+        indent.push_str("  ");
+        text.push('\n');
+        text.push_str(&indent);
+    }
+    for (i, line) in pretty.split('\n').enumerate() {
+        if i > 0 {
+            text.push_str(newline);
+            if !line.is_empty() {
+                text.push_str(&indent);
+            }
+        }
+        text.push_str(line);
+    }
+    Ok(text)
+}
+
+/// Serializes a string that replaces the quoted string `old` in the same
+/// quotes. Returns `None` if either of them is not a quoted string, or if the
+/// new one spans multiple lines and is better written as a multiline string.
+fn render_requoted<T: ?Sized + Serialize>(value: &T, old: &str) -> Result<Option<String>> {
+    let single = match old.chars().next() {
+        Some('"') => false,
+        Some('\'') if !old.starts_with("'''") => true,
+        _ => return Ok(None),
+    };
+    let json = try!(to_string(value));
+    if !json.starts_with('"') || try!(to_string_pretty(value)).contains('\n') {
+        return Ok(None);
+    }
+    if !single {
+        return Ok(Some(json));
+    }
+    // A `"` in JSON is always escaped, and a `'` never is.
+    let body = json[1..json.len() - 1].replace('\'', "\\'").replace("\\\"", "\"");
+    Ok(Some(format!("'{}'", body)))
+}
+
+/// Joins the `key: ` prefix of a member, if any, with its rendered value.
+fn join_entry(prefix: &str, value: &str) -> String {
+    if value.starts_with(|ch| ch == '\r' || ch == '\n') {
+        format!("{}{}", prefix.trim_end(), value)
+    } else {
+        format!("{}{}", prefix, value)
+    }
+}
+
+/// Writes a member name, without quotes if that is unambiguous.
+fn render_key(key: &str, quoted: bool) -> Result<String> {
+    let bare = !key.is_empty() &&
+        key.chars().all(|ch| match ch {
+            '_' | '-' | '.' | '$' => true,
+            _ => ch.is_alphanumeric(),
+        });
+    if bare && !quoted {
+        Ok(String::from(key))
+    } else {
+        to_string(key)
+    }
+}
+
+/// The line break the document uses, `\r\n` if it has any and `\n`
+/// otherwise.
+fn newline(text: &str) -> &'static str {
+    if text.contains("\r\n") { "\r\n" } else { "\n" }
+}
+
+fn line_start(text: &str, pos: usize) -> usize {
+    text[..pos].rfind('\n').map_or(0, |i| i + 1)
+}
+
+/// End of the line containing `pos`, not including the line break.
+fn line_end(text: &str, pos: usize) -> usize {
+    match text[pos..].find('\n') {
+        Some(i) if i > 0 && text[..pos + i].ends_with('\r') => pos + i - 1,
+        Some(i) => pos + i,
+        None => text.len(),
+    }
+}
+
+/// Start of the line after the one containing `pos`, or the end of the text.
+fn next_line_start(text: &str, pos: usize) -> usize {
+    text[pos..].find('\n').map_or(text.len(), |i| pos + i + 1)
+}
+
+fn is_comment(text: &str) -> bool {
+    text.starts_with('#') || text.starts_with("//") || text.starts_with("/*")
+}
+
+/// Parses an array index in a JSON Pointer.
+fn parse_index(s: &str) -> Option<usize> {
+    if s.starts_with('+') || (s.starts_with('0') && s.len() != 1) {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// The whitespace at the beginning of the line containing `pos`.
+fn line_indent(text: &str, pos: usize) -> &str {
+    let start = line_start(text, pos);
+    let line = &text[start..];
+    let len = line.len() - line.trim_start_matches(|ch| ch == ' ' || ch == '\t').len();
+    &line[..len]
+}
+
+/// Whether only whitespace and comments follow `pos` on its line.
+fn rest_of_line_is_blank(text: &str, pos: usize) -> bool {
+    let rest = text[pos..line_end(text, pos)].trim();
+    rest.is_empty() || is_comment(rest)
+}
+
+fn is_blank(text: &str) -> bool {
+    text.chars().all(char::is_whitespace)
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// A value in the document, spanning `start..end`.
+struct Node {
+    start: usize,
+    end: usize,
+    kind: Kind,
+}
+
+enum Kind {
+    Scalar,
+    Object(Container),
+    Array(Container),
+}
+
+/// The members of an object or the elements of an array.
+struct Container {
+    /// Whether the container has brackets, which a root object may omit.
+    brackets: bool,
+    entries: Vec<Entry>,
+}
+
+struct Entry {
+    /// The member name, for objects.
+    key: Option<Key>,
+    value: Node,
+    /// The position of the `,` following the value, if any.
+    comma: Option<usize>,
+}
+
+/// A member name as written in the document, spanning `start..end`.
+struct Key {
+    name: String,
+    start: usize,
+    quoted: bool,
+}
+
+/// Finds the values in a document from its syntax tree.
+fn layout(text: &str) -> Node {
+    match syntax::parse(text).value() {
+        Some(value) => layout_value(value),
+        None => {
+            Node {
+                start: 0,
+                end: 0,
+                kind: Kind::Scalar,
+            }
+        }
+    }
+}
+
+fn layout_value(value: &Child) -> Node {
+    let node = match *value {
+        Child::Node(ref node) => node,
+        Child::Token(ref token) => {
+            return Node {
+                start: token.start,
+                end: token.end(),
+                kind: Kind::Scalar,
+            };
+        }
+    };
+
+    // Trivia does not matter to where the entries and their commas are.
+    let children: Vec<_> = node.children()
+        .iter()
+        .filter(|child| match child.as_token() {
+            Some(token) => !token.kind.is_trivia(),
+            None => true,
+        })
+        .collect();
+    let mut entries = Vec::new();
+    for (i, child) in children.iter().enumerate() {
+        let (key, value) = match child.as_node() {
+            Some(member) if member.kind() == NodeKind::Member => {
+                match (member.name(), member.value()) {
+                    (Some(name), Some(value)) => (Some(layout_key(name)), value),
+                    _ => continue,
+                }
+            }
+            Some(_) => (None, *child),
+            None if child.as_token().map(|token| token.kind.is_scalar()) == Some(true) => {
+                (None, *child)
+            }
+            None => continue,
+        };
+        let comma = match children.get(i + 1).and_then(|child| child.as_token()) {
+            Some(token) if token.kind == TokenKind::Comma => Some(token.start),
+            _ => None,
+        };
+        entries.push(Entry {
+            key: key,
+            value: layout_value(value),
+            comma: comma,
+        });
+    }
+
+    let brackets = match children.first().and_then(|child| child.as_token()) {
+        Some(token) => token.kind == TokenKind::LeftBrace || token.kind == TokenKind::LeftBracket,
+        None => false,
+    };
+    // A root object without braces ends with its last value, not with the
+    // comments after it.
+    let end = if brackets {
+        node.end()
+    } else {
+        entries.last().map_or(node.start(), |entry| entry.value.end)
+    };
+    let container = Container {
+        brackets: brackets,
+        entries: entries,
+    };
+    Node {
+        start: node.start(),
+        end: end,
+        kind: if node.kind() == NodeKind::Array {
+            Kind::Array(container)
+        } else {
+            Kind::Object(container)
+        },
+    }
+}
+
+fn layout_key(name: &Token) -> Key {
+    Key {
+        name: syntax::member_name(name),
+        start: name.start,
+        quoted: name.text.starts_with('"'),
+    }
+}
+
+impl Node {
+    /// Looks up a value by the unescaped tokens of a JSON Pointer.
+    fn find(&self, tokens: &[String]) -> Option<&Node> {
+        let mut node = self;
+        for token in tokens {
+            node = match node.kind {
+                Kind::Object(ref container) |
+                Kind::Array(ref container) => {
+                    match container.position(token) {
+                        Some(index) => &container.entries[index].value,
+                        None => return None,
+                    }
+                }
+                Kind::Scalar => return None,
+            };
+        }
+        Some(node)
+    }
+}
+
+impl Entry {
+    fn start(&self) -> usize {
+        match self.key {
+            Some(ref key) => key.start,
+            None => self.value.start,
+        }
+    }
+
+    /// The end of the entry, including the `,` after it.
+    fn end(&self) -> usize {
+        match self.comma {
+            Some(comma) => comma + 1,
+            None => self.value.end,
+        }
+    }
+
+    fn key_is(&self, token: &str) -> bool {
+        match self.key {
+            Some(ref key) => key.name == token,
+            None => false,
+        }
+    }
+}
+
+impl Container {
+    /// The index of the entry a pointer token refers to. Like a map, a later
+    /// member wins over an earlier one with the same name.
+    fn position(&self, token: &str) -> Option<usize> {
+        if self.entries.iter().any(|entry| entry.key.is_some()) {
+            self.entries.iter().rposition(|entry| entry.key_is(token))
+        } else {
+            parse_index(token).and_then(|index| if index < self.entries.len() {
+                Some(index)
+            } else {
+                None
+            })
+        }
+    }
+
+    fn uses_commas(&self) -> bool {
+        let len = self.entries.len();
+        self.entries.iter().take(len.saturating_sub(1)).any(|entry| entry.comma.is_some())
+    }
+
+    fn has_trailing_comma(&self) -> bool {
+        self.entries.last().and_then(|entry| entry.comma).is_some()
+    }
+
+    /// Whether the container spreads over multiple lines.
+    fn is_multiline(&self, text: &str, node: &Node) -> bool {
+        !self.brackets || text[node.start..node.end].contains('\n')
+    }
+
+    /// Computes the replacements that insert an entry before the one at
+    /// `index`, or after the last one if `index` is `None` or out of range.
+    fn insert<T: ?Sized + Serialize>(
+        &self,
+        text: &str,
+        node: &Node,
+        index: Option<usize>,
+        key: Option<&str>,
+        value: &T,
+    ) -> Result<Vec<(usize, usize, String)>> {
+        let quoted_keys = self.entries.iter().any(|entry| match entry.key {
+            Some(ref key) => key.quoted,
+            None => false,
+        });
+        let prefix = match key {
+            Some(key) => format!("{}: ", try!(render_key(key, quoted_keys))),
+            None => String::new(),
+        };
+        let member = key.is_some();
+        let newline = newline(text);
+        let multiline = self.is_multiline(text, node);
+        let uses_commas = self.uses_commas() || !multiline;
+
+        let mut edit = Vec::new();
+
+        // Insert before an existing entry.
+        if let Some(next) = index.and_then(|index| self.entries.get(index)) {
+            let start = next.start();
+            let value = try!(render(value, line_indent(text, start), ",", member, newline));
+            if multiline && is_blank(&text[line_start(text, start)..start]) {
+                let indent = line_indent(text, start);
+                let comma = if uses_commas { "," } else { "" };
+                let line = format!("{}{}{}{}{}", indent, prefix, value, comma, newline);
+                let at = line_start(text, start);
+                edit.push((at, at, line));
+            } else {
+                edit.push((start, start, format!("{}{}, ", prefix, value)));
+            }
+            return Ok(edit);
+        }
+
+        // Append after the last entry.
+        match self.entries.last() {
+            Some(last) => {
+                let indent = String::from(line_indent(text, last.start()));
+                if multiline {
+                    let mut at = line_end(text, last.end());
+                    if self.brackets && node.end - 1 < at {
+                        at = node.end - 1;
+                    }
+                    let rest = &text[at..line_end(text, at)];
+                    let value = try!(render(value, &indent, rest, member, newline));
+                    if uses_commas && last.comma.is_none() {
+                        edit.push((last.value.end, last.value.end, String::from(",")));
+                    }
+                    let comma = if self.has_trailing_comma() { "," } else { "" };
+                    let entry = join_entry(&prefix, &value);
+                    edit.push((at, at, format!("{}{}{}{}", newline, indent, entry, comma)));
+                } else {
+                    let value = try!(render(value, &indent, ",", member, newline));
+                    match last.comma {
+                        Some(comma) => {
+                            edit.push((comma + 1, comma + 1, format!(" {}{},", prefix, value)));
+                        }
+                        None => {
+                            let at = last.value.end;
+                            edit.push((at, at, format!(", {}{}", prefix, value)));
+                        }
+                    }
+                }
+            }
+            None => {
+                let open = node.start + 1;
+                if multiline {
+                    let indent = format!("{}  ", line_indent(text, node.start));
+                    let value = try!(render(value, &indent, "", member, newline));
+                    let entry = join_entry(&prefix, &value);
+                    edit.push((open, open, format!("{}{}{}", newline, indent, entry)));
+                } else {
+                    let value = try!(render(value, "", ",", member, newline));
+                    edit.push((open, node.end - 1, format!("{}{}", prefix, value)));
+                }
+            }
+        }
+
+        Ok(edit)
+    }
+
+    /// Computes the replacements that remove the entry at `index`.
+    fn remove(&self, text: &str, index: usize) -> Vec<(usize, usize, String)> {
+        let entry = &self.entries[index];
+        let start = entry.start();
+        let end = entry.end();
+        let mut edit = Vec::new();
+
+        let line = line_start(text, start);
+        if is_blank(&text[line..start]) && rest_of_line_is_blank(text, end) {
+            // A JSON style container must not be left with a trailing comma.
+            if index + 1 == self.entries.len() && entry.comma.is_none() && index > 0 {
+                if let Some(comma) = self.entries[index - 1].comma {
+                    edit.push((comma, comma + 1, String::new()));
+                }
+            }
+            // Remove the whole lines the entry is on.
+            edit.push((line, next_line_start(text, end), String::new()));
+        } else if let Some(next) = self.entries.get(index + 1) {
+            edit.push((start, next.start(), String::new()));
+        } else if index > 0 {
+            edit.push((self.entries[index - 1].value.end, end, String::new()));
+        } else {
+            edit.push((start, end, String::new()));
+        }
+
+        edit
+    }
+}
@@ -8,14 +8,18 @@
 
 //! When serializing or deserializing JSON goes wrong.
 
+use std::cmp;
 use std::error;
 use std::fmt::{self, Debug, Display};
 use std::io;
+use std::ops::Range;
 use std::result;
 
 use serde::de;
 use serde::ser;
 
+use read::Position;
+
 /// This type represents all possible errors that can occur when serializing or
 /// deserializing JSON data.
 pub struct Error {
@@ -44,10 +48,127 @@ impl Error {
     ///
     /// Note that errors may occur in column 0, for example if a read from an IO
     /// stream fails immediately following a previously read newline character.
+    ///
+    /// The column is counted in bytes, so it is further right than it seems on
+    /// lines with non-ASCII text before the error. See `char_column` and
+    /// `utf16_column`.
     pub fn column(&self) -> usize {
         self.err.column
     }
 
+    /// Like `column`, but counted in characters (Unicode scalar values), the
+    /// way most people would count them.
+    ///
+    /// ```rust
+    /// let err = serde_hjson::from_str::<serde_hjson::Value>("{\"größe\" 1}").unwrap_err();
+    /// assert_eq!(err.column(), 12);
+    /// assert_eq!(err.char_column(), 10);
+    /// ```
+    pub fn char_column(&self) -> usize {
+        self.err.char_column
+    }
+
+    /// Like `column`, but counted in UTF-16 code units, as the Language Server
+    /// Protocol expects (after subtracting one, since its columns start at
+    /// zero). Characters outside the Basic Multilingual Plane, like most
+    /// emoji, take two code units.
+    pub fn utf16_column(&self) -> usize {
+        self.err.utf16_column
+    }
+
+    /// The byte range of the input that caused the error.
+    ///
+    /// For syntax errors this is the offending character, or an empty range
+    /// where the input ended unexpectedly. For data errors this is the value
+    /// that could not be deserialized. Returns `None` for IO errors, for
+    /// serialization errors and for errors deserializing from a `Value`.
+    ///
+    /// ```rust
+    /// # #[macro_use]
+    /// # extern crate serde_derive;
+    /// #
+    /// # extern crate serde_hjson;
+    /// #
+    /// #[derive(Deserialize, Debug)]
+    /// struct Server {
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn main() {
+    /// let s = "port: 99999\n";
+    /// let err = serde_hjson::from_str::<Server>(s).unwrap_err();
+    /// assert_eq!(&s[err.byte_range().unwrap()], "99999");
+    /// # }
+    /// ```
+    pub fn byte_range(&self) -> Option<Range<usize>> {
+        self.err.range.clone()
+    }
+
+    /// The member names and array indices leading to the value that could not
+    /// be deserialized, like `servers[2].tls.cert_path`.
+    ///
+    /// Member names that are not made up of letters, digits, `_`, `-` and `$`
+    /// are written in brackets and quotes, like `headers["Content-Type"]`.
+    ///
+    /// Syntax errors inside an object or array have a path too, though only
+    /// data errors show it in their message since a syntax error already
+    /// points at the offending character. Returns `None` for errors outside
+    /// of any object or array, and for IO and serialization errors.
+    pub fn path(&self) -> Option<&str> {
+        if self.err.path.is_empty() {
+            None
+        } else {
+            Some(&self.err.path)
+        }
+    }
+
+    /// For an unknown field or variant, the expected name closest to the one
+    /// in the input, if any is close enough to be a likely typo.
+    ///
+    /// ```rust
+    /// # #[macro_use]
+    /// # extern crate serde_derive;
+    /// #
+    /// # extern crate serde_hjson;
+    /// #
+    /// #[derive(Deserialize)]
+    /// #[serde(deny_unknown_fields)]
+    /// struct Server {
+    ///     host: String,
+    ///     port: u16,
+    /// }
+    ///
+    /// # fn main() {
+    /// let s = "host: example.com\nprot: 8080\n";
+    /// let err = serde_hjson::from_str::<Server>(s).err().unwrap();
+    /// assert_eq!(err.suggestion(), Some("port"));
+    /// assert_eq!(&s[err.byte_range().unwrap()], "prot");
+    /// # }
+    /// ```
+    pub fn suggestion(&self) -> Option<&'static str> {
+        match self.err.code {
+            ErrorCode::UnknownField(ref name, expected) |
+            ErrorCode::UnknownVariant(ref name, expected) => closest(name, expected),
+            _ => None,
+        }
+    }
+
+    /// The specific cause of this error.
+    ///
+    /// Unlike the `Display` output, which may be reworded, the kind of an
+    /// error and its numeric code are stable.
+    ///
+    /// ```rust
+    /// use serde_hjson::error::ErrorKind;
+    ///
+    /// let err = serde_hjson::from_str::<serde_hjson::Value>("[1, 2,,]").unwrap_err();
+    /// assert_eq!(err.kind(), ErrorKind::ExtraComma);
+    /// assert_eq!(err.kind().code(), 213);
+    /// ```
+    pub fn kind(&self) -> ErrorKind {
+        self.err.code.kind()
+    }
+
     /// Categorizes the cause of this error.
     ///
     /// - `Category::Io` - failure to read or write bytes on an IO stream
@@ -55,33 +176,7 @@ impl Error {
     /// - `Category::Data` - input data that is semantically incorrect
     /// - `Category::Eof` - unexpected end of the input data
     pub fn classify(&self) -> Category {
-        match self.err.code {
-            ErrorCode::Message(_) => Category::Data,
-            ErrorCode::Io(_) => Category::Io,
-            ErrorCode::EofWhileParsingList |
-            ErrorCode::EofWhileParsingObject |
-            ErrorCode::EofWhileParsingString |
-            ErrorCode::EofWhileParsingValue => Category::Eof,
-            ErrorCode::ExpectedColon |
-            ErrorCode::ExpectedListCommaOrEnd |
-            ErrorCode::ExpectedObjectCommaOrEnd |
-            ErrorCode::ExpectedObjectOrArray |
-            ErrorCode::ExpectedSomeIdent |
-            ErrorCode::ExpectedSomeValue |
-            ErrorCode::ExpectedSomeString |
-            ErrorCode::InvalidEscape |
-            ErrorCode::InvalidNumber |
-            ErrorCode::NumberOutOfRange |
-            ErrorCode::InvalidUnicodeCodePoint |
-            ErrorCode::KeyMustBeAString |
-            ErrorCode::LoneLeadingSurrogateInHexEscape |
-            ErrorCode::ExtraComma |
-            ErrorCode::TrailingCharacters |
-            ErrorCode::UnexpectedEndOfHexEscape |
-            ErrorCode::RecursionLimitExceeded |
-            ErrorCode::UnexpectedCharacter |
-            ErrorCode::UnexpectedNewline => Category::Syntax,
-        }
+        self.err.code.category()
     }
 
     /// Returns true if this error was caused by a failure to read or write
@@ -138,6 +233,363 @@ pub enum Category {
     Eof,
 }
 
+/// The specific cause of a `serde_hjson::Error`.
+///
+/// Every kind has a numeric code that stays the same across releases, see
+/// `ErrorKind::code`, and a longer explanation for showing to users, see
+/// `ErrorKind::explanation`.
+///
+/// More kinds may be added in the future, so matching on this enum needs a
+/// wildcard arm.
+#[derive(Copy, Clone, PartialEq, Eq, Hash, Debug)]
+pub enum ErrorKind {
+    /// A message from a `Serialize` or `Deserialize` implementation, usually
+    /// about a value of the wrong type. Code 1.
+    Message,
+
+    /// Reading or writing failed. Code 2.
+    Io,
+
+    /// A member name that is not a field of the struct being deserialized,
+    /// which does not allow unknown fields. Code 3.
+    UnknownField,
+
+    /// A name that is not a variant of the enum being deserialized. Code 4.
+    UnknownVariant,
+
+    /// The input ended inside of an array. Code 100.
+    EofWhileParsingList,
+
+    /// The input ended inside of an object. Code 101.
+    EofWhileParsingObject,
+
+    /// The input ended inside of a string. Code 102.
+    EofWhileParsingString,
+
+    /// The input ended where a value was expected. Code 103.
+    EofWhileParsingValue,
+
+    /// Expected a `:` after a member name. Code 200.
+    ExpectedColon,
+
+    /// Expected a `,` or `]` after an array element. Code 201.
+    ExpectedListCommaOrEnd,
+
+    /// Expected a `,` or `}` after an object member. Code 202.
+    ExpectedObjectCommaOrEnd,
+
+    /// Expected a `{` or `[`. Code 203.
+    ExpectedObjectOrArray,
+
+    /// Expected `true`, `false` or `null`. Code 204.
+    ExpectedSomeIdent,
+
+    /// Expected the start of a value. Code 205.
+    ExpectedSomeValue,
+
+    /// Expected the start of a string. Code 206.
+    ExpectedSomeString,
+
+    /// A `\` escape in a string is not valid. Code 207.
+    InvalidEscape,
+
+    /// A number is not valid. Code 208.
+    InvalidNumber,
+
+    /// A number does not fit the type it is deserialized into. Code 209.
+    NumberOutOfRange,
+
+    /// A `\u` escape or the input is not valid unicode. Code 210.
+    InvalidUnicodeCodePoint,
+
+    /// A map key did not serialize to a string. Code 211.
+    KeyMustBeAString,
+
+    /// A `\u` escape of a leading surrogate is not followed by a trailing
+    /// surrogate. Code 212.
+    LoneLeadingSurrogateInHexEscape,
+
+    /// A `,` follows another `,` or an opening bracket. Code 213.
+    ExtraComma,
+
+    /// Something other than whitespace and comments follows the root value.
+    /// Code 214.
+    TrailingCharacters,
+
+    /// The input ended inside of a `\u` escape. Code 215.
+    UnexpectedEndOfHexEscape,
+
+    /// Arrays and objects are nested deeper than the recursion limit. Code
+    /// 216.
+    RecursionLimitExceeded,
+
+    /// A value is directly followed by another character. Code 217.
+    UnexpectedCharacter,
+
+    /// A `"` or `'` quoted string continues past the end of the line. Code
+    /// 218.
+    UnexpectedNewline,
+
+    /// An object has the same member name twice. Code 219.
+    DuplicateKey,
+
+    /// A comment, which the parse profile does not allow. Code 300.
+    CommentNotAllowed,
+
+    /// A quoteless string or member name, which the parse profile does not
+    /// allow. Code 301.
+    QuotelessStringNotAllowed,
+
+    /// A `'` quoted string, which the parse profile does not allow. Code 302.
+    SingleQuotedStringNotAllowed,
+
+    /// A `'''` multiline string, which the parse profile does not allow. Code
+    /// 303.
+    MultilineStringNotAllowed,
+
+    /// Values separated only by a newline, where the parse profile requires a
+    /// `,`. Code 304.
+    MissingComma,
+
+    /// A `,` after the last value in an array or object, which the parse
+    /// profile does not allow. Code 305.
+    TrailingCommaNotAllowed,
+
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+impl ErrorKind {
+    /// The stable numeric code of this kind of error.
+    ///
+    /// Codes are grouped by category: 1, 3 and 4 for data errors, 2 for IO
+    /// errors, 1xx for unexpected EOF, 2xx for syntax errors and 3xx for
+    /// syntax that the parse profile does not allow.
+    pub fn code(&self) -> u32 {
+        match *self {
+            ErrorKind::Message => 1,
+            ErrorKind::Io => 2,
+            ErrorKind::UnknownField => 3,
+            ErrorKind::UnknownVariant => 4,
+            ErrorKind::EofWhileParsingList => 100,
+            ErrorKind::EofWhileParsingObject => 101,
+            ErrorKind::EofWhileParsingString => 102,
+            ErrorKind::EofWhileParsingValue => 103,
+            ErrorKind::ExpectedColon => 200,
+            ErrorKind::ExpectedListCommaOrEnd => 201,
+            ErrorKind::ExpectedObjectCommaOrEnd => 202,
+            ErrorKind::ExpectedObjectOrArray => 203,
+            ErrorKind::ExpectedSomeIdent => 204,
+            ErrorKind::ExpectedSomeValue => 205,
+            ErrorKind::ExpectedSomeString => 206,
+            ErrorKind::InvalidEscape => 207,
+            ErrorKind::InvalidNumber => 208,
+            ErrorKind::NumberOutOfRange => 209,
+            ErrorKind::InvalidUnicodeCodePoint => 210,
+            ErrorKind::KeyMustBeAString => 211,
+            ErrorKind::LoneLeadingSurrogateInHexEscape => 212,
+            ErrorKind::ExtraComma => 213,
+            ErrorKind::TrailingCharacters => 214,
+            ErrorKind::UnexpectedEndOfHexEscape => 215,
+            ErrorKind::RecursionLimitExceeded => 216,
+            ErrorKind::UnexpectedCharacter => 217,
+            ErrorKind::UnexpectedNewline => 218,
+            ErrorKind::DuplicateKey => 219,
+            ErrorKind::CommentNotAllowed => 300,
+            ErrorKind::QuotelessStringNotAllowed => 301,
+            ErrorKind::SingleQuotedStringNotAllowed => 302,
+            ErrorKind::MultilineStringNotAllowed => 303,
+            ErrorKind::MissingComma => 304,
+            ErrorKind::TrailingCommaNotAllowed => 305,
+            ErrorKind::__Nonexhaustive => 0,
+        }
+    }
+
+    /// Looks up the kind of error with the given numeric code.
+    pub fn from_code(code: u32) -> Option<ErrorKind> {
+        ALL_KINDS.iter().cloned().find(|kind| kind.code() == code)
+    }
+
+    /// A longer explanation of this kind of error and how to fix it, for
+    /// showing to users.
+    pub fn explanation(&self) -> &'static str {
+        match *self {
+            ErrorKind::Message => {
+                "The input is valid Hjson, but a value does not fit the type it is \
+                 deserialized into. For example a member holds a string where a \
+                 number is expected, or a required member is missing."
+            }
+            ErrorKind::Io => {
+                "Reading the input or writing the output failed. The underlying IO \
+                 error has the details."
+            }
+            ErrorKind::UnknownField => {
+                "An object has a member that the type it is deserialized into does not \
+                 know, and the type does not allow unknown members. Check the member \
+                 name for typos; the error suggests a similar name if there is one."
+            }
+            ErrorKind::UnknownVariant => {
+                "A value names a variant that the enum it is deserialized into does not \
+                 have. Check the name for typos; the error suggests a similar name if \
+                 there is one."
+            }
+            ErrorKind::EofWhileParsingList => {
+                "The input ended before the `]` closing an array. Check that every `[` \
+                 has a matching `]`."
+            }
+            ErrorKind::EofWhileParsingObject => {
+                "The input ended before the `}` closing an object. Check that every `{` \
+                 has a matching `}`."
+            }
+            ErrorKind::EofWhileParsingString => {
+                "The input ended inside of a string. Check that every string is closed \
+                 with the same quotes it was opened with: `\"`, `'` or `'''`."
+            }
+            ErrorKind::EofWhileParsingValue => {
+                "The input ended where a value was expected, for example after a `:` or \
+                 in an empty document."
+            }
+            ErrorKind::ExpectedColon => {
+                "A member name in an object must be followed by a `:` and the value. \
+                 Member names that contain `,`, `:`, `[`, `]`, `{`, `}` or whitespace \
+                 must be quoted."
+            }
+            ErrorKind::ExpectedListCommaOrEnd => {
+                "An array element must be followed by a `,`, a newline or the `]` \
+                 closing the array."
+            }
+            ErrorKind::ExpectedObjectCommaOrEnd => {
+                "An object member must be followed by a `,`, a newline or the `}` \
+                 closing the object."
+            }
+            ErrorKind::ExpectedObjectOrArray => "The input must start with an object or an array.",
+            ErrorKind::ExpectedSomeIdent => {
+                "The value looks like `true`, `false` or `null` but is misspelled. Quote \
+                 it if it is meant to be a string."
+            }
+            ErrorKind::ExpectedSomeValue => {
+                "A value was expected here. Quoteless strings can not start with any of \
+                 `,`, `:`, `[`, `]`, `{` or `}`, quote the string if it does."
+            }
+            ErrorKind::ExpectedSomeString => "A string was expected here.",
+            ErrorKind::InvalidEscape => {
+                "A `\\` in a quoted string must be followed by one of `\"`, `'`, `\\`, `/`, \
+                 `b`, `f`, `n`, `r`, `t` or `u` and four hex digits. Write `\\\\` for a \
+                 backslash."
+            }
+            ErrorKind::InvalidNumber => {
+                "The value starts like a number but is not a valid one. Quote it if it \
+                 is meant to be a string."
+            }
+            ErrorKind::NumberOutOfRange => {
+                "The number is too large or too small for the type it is deserialized \
+                 into."
+            }
+            ErrorKind::InvalidUnicodeCodePoint => {
+                "The input is not valid UTF-8, or a `\\u` escape does not encode a valid \
+                 unicode code point."
+            }
+            ErrorKind::KeyMustBeAString => {
+                "Only maps whose keys serialize to strings or numbers can be written as \
+                 Hjson objects."
+            }
+            ErrorKind::LoneLeadingSurrogateInHexEscape => {
+                "A `\\u` escape of a UTF-16 leading surrogate, `\\uD800` to `\\uDBFF`, must \
+                 be directly followed by a `\\u` escape of a trailing surrogate."
+            }
+            ErrorKind::ExtraComma => {
+                "There is a `,` with no value before it. Remove the extra comma."
+            }
+            ErrorKind::TrailingCharacters => {
+                "Only whitespace and comments may follow the root value. Check for a \
+                 stray closing bracket or a second value."
+            }
+            ErrorKind::UnexpectedEndOfHexEscape => {
+                "A `\\u` escape must be followed by exactly four hex digits."
+            }
+            ErrorKind::RecursionLimitExceeded => {
+                "Arrays and objects are nested too deeply. The limit can be raised or \
+                 disabled on the `Deserializer`."
+            }
+            ErrorKind::UnexpectedCharacter => {
+                "A value must be followed by a `,`, a newline or a closing bracket, not \
+                 by another value on the same line."
+            }
+            ErrorKind::UnexpectedNewline => {
+                "Strings quoted with `\"` or `'` must end on the line they start on. Use \
+                 `\\n` for a line break, or a `'''` multiline string."
+            }
+            ErrorKind::DuplicateKey => {
+                "An object has the same member name twice, which the duplicate key \
+                 policy of the `Deserializer` does not allow. Remove or rename one of \
+                 the members."
+            }
+            ErrorKind::CommentNotAllowed => {
+                "The parse profile does not allow comments. Remove the comment or parse \
+                 with a profile that allows them."
+            }
+            ErrorKind::QuotelessStringNotAllowed => {
+                "The parse profile requires strings and member names to be quoted with \
+                 `\"`."
+            }
+            ErrorKind::SingleQuotedStringNotAllowed => {
+                "The parse profile requires strings and member names to be quoted with \
+                 `\"` rather than `'`."
+            }
+            ErrorKind::MultilineStringNotAllowed => {
+                "The parse profile does not allow `'''` multiline strings. Use a `\"` \
+                 quoted string with `\\n` for line breaks."
+            }
+            ErrorKind::MissingComma => {
+                "The parse profile requires values to be separated by `,`, not only by \
+                 a newline."
+            }
+            ErrorKind::TrailingCommaNotAllowed => {
+                "The parse profile does not allow a `,` after the last value in an \
+                 array or object. Remove the comma."
+            }
+            ErrorKind::__Nonexhaustive => "",
+        }
+    }
+}
+
+const ALL_KINDS: &'static [ErrorKind] = &[
+    ErrorKind::Message,
+    ErrorKind::Io,
+    ErrorKind::UnknownField,
+    ErrorKind::UnknownVariant,
+    ErrorKind::EofWhileParsingList,
+    ErrorKind::EofWhileParsingObject,
+    ErrorKind::EofWhileParsingString,
+    ErrorKind::EofWhileParsingValue,
+    ErrorKind::ExpectedColon,
+    ErrorKind::ExpectedListCommaOrEnd,
+    ErrorKind::ExpectedObjectCommaOrEnd,
+    ErrorKind::ExpectedObjectOrArray,
+    ErrorKind::ExpectedSomeIdent,
+    ErrorKind::ExpectedSomeValue,
+    ErrorKind::ExpectedSomeString,
+    ErrorKind::InvalidEscape,
+    ErrorKind::InvalidNumber,
+    ErrorKind::NumberOutOfRange,
+    ErrorKind::InvalidUnicodeCodePoint,
+    ErrorKind::KeyMustBeAString,
+    ErrorKind::LoneLeadingSurrogateInHexEscape,
+    ErrorKind::ExtraComma,
+    ErrorKind::TrailingCharacters,
+    ErrorKind::UnexpectedEndOfHexEscape,
+    ErrorKind::RecursionLimitExceeded,
+    ErrorKind::UnexpectedCharacter,
+    ErrorKind::UnexpectedNewline,
+    ErrorKind::DuplicateKey,
+    ErrorKind::CommentNotAllowed,
+    ErrorKind::QuotelessStringNotAllowed,
+    ErrorKind::SingleQuotedStringNotAllowed,
+    ErrorKind::MultilineStringNotAllowed,
+    ErrorKind::MissingComma,
+    ErrorKind::TrailingCommaNotAllowed,
+];
+
 #[cfg_attr(feature = "cargo-clippy", allow(fallible_impl_from))]
 impl From<Error> for io::Error {
     /// Convert a `serde_json::Error` into an `io::Error`.
@@ -185,6 +637,12 @@ struct ErrorImpl {
     code: ErrorCode,
     line: usize,
     column: usize,
+    char_column: usize,
+    utf16_column: usize,
+    /// The bytes of the input that caused the error, see `Error::byte_range`.
+    range: Option<Range<usize>>,
+    /// Where in the document the error happened, see `Error::path`.
+    path: String,
 }
 
 // Not public API. Should be pub(crate).
@@ -197,6 +655,12 @@ pub enum ErrorCode {
     /// Some IO error occurred while serializing or deserializing.
     Io(io::Error),
 
+    /// A member name that is not one of the expected fields.
+    UnknownField(Box<str>, &'static [&'static str]),
+
+    /// A name that is not one of the expected variants.
+    UnknownVariant(Box<str>, &'static [&'static str]),
+
     /// EOF while parsing a list.
     EofWhileParsingList,
 
@@ -257,7 +721,8 @@ pub enum ErrorCode {
     /// Unexpected end of hex excape.
     UnexpectedEndOfHexEscape,
 
-    /// Encountered nesting of JSON maps and arrays more than 128 layers deep.
+    /// Encountered nesting of JSON maps and arrays deeper than the recursion
+    /// limit, 128 layers by default.
     RecursionLimitExceeded,
 
     /// Encountered another character directly after a value
@@ -265,6 +730,33 @@ pub enum ErrorCode {
 
     /// Encountered a newline in a single-line string
     UnexpectedNewline,
+
+    /// Encountered a comment, which the parse profile does not allow.
+    CommentNotAllowed,
+
+    /// Encountered a quoteless string or member name, which the parse profile
+    /// does not allow.
+    QuotelessStringNotAllowed,
+
+    /// Encountered a `'` quoted string, which the parse profile does not
+    /// allow.
+    SingleQuotedStringNotAllowed,
+
+    /// Encountered a `'''` multiline string, which the parse profile does not
+    /// allow.
+    MultilineStringNotAllowed,
+
+    /// Values are only separated by a newline, but the parse profile requires
+    /// a `','`.
+    MissingComma,
+
+    /// Encountered a comma after the last value in an array or object, which
+    /// the parse profile does not allow.
+    TrailingCommaNotAllowed,
+
+    /// An object has the same key twice. Holds the key and the line and
+    /// column of its first occurrence.
+    DuplicateKey(String, usize, usize),
 }
 
 impl Error {
@@ -277,6 +769,45 @@ impl Error {
                     code: code,
                     line: line,
                     column: column,
+                    char_column: column,
+                    utf16_column: column,
+                    range: None,
+                    path: String::new(),
+                },
+            ),
+        }
+    }
+
+    // Not public API. Should be pub(crate).
+    #[doc(hidden)]
+    pub fn syntax_at(code: ErrorCode, position: Position, range: Range<usize>) -> Self {
+        Error {
+            err: Box::new(
+                ErrorImpl {
+                    code: code,
+                    line: position.line,
+                    column: position.column,
+                    char_column: position.char_column,
+                    utf16_column: position.utf16_column,
+                    range: Some(range),
+                    path: String::new(),
+                },
+            ),
+        }
+    }
+
+    /// A data error, whose position is filled in by `fix_position`.
+    fn data(code: ErrorCode) -> Self {
+        Error {
+            err: Box::new(
+                ErrorImpl {
+                    code: code,
+                    line: 0,
+                    column: 0,
+                    char_column: 0,
+                    utf16_column: 0,
+                    range: None,
+                    path: String::new(),
                 },
             ),
         }
@@ -291,6 +822,10 @@ impl Error {
                     code: ErrorCode::Io(error),
                     line: 0,
                     column: 0,
+                    char_column: 0,
+                    utf16_column: 0,
+                    range: None,
+                    path: String::new(),
                 },
             ),
         }
@@ -303,11 +838,133 @@ impl Error {
         F: FnOnce(ErrorCode) -> Error,
     {
         if self.err.line == 0 {
-            f(self.err.code)
+            let ErrorImpl { code, path, .. } = *self.err;
+            let mut err = f(code);
+            err.err.path = path;
+            err
         } else {
             self
         }
     }
+
+    // Not public API. Should be pub(crate).
+    #[doc(hidden)]
+    pub fn message(&self) -> String {
+        match self.err.shown_path() {
+            Some(path) => format!("{}: {}", path, self.err.code),
+            None => self.err.code.to_string(),
+        }
+    }
+
+    // Not public API. Should be pub(crate).
+    #[doc(hidden)]
+    pub fn prepend_key(self, key: &str) -> Self {
+        if is_simple_key(key) {
+            self.prepend_path(key)
+        } else {
+            self.prepend_path(&format!("[{:?}]", key))
+        }
+    }
+
+    // Not public API. Should be pub(crate).
+    #[doc(hidden)]
+    pub fn prepend_index(self, index: usize) -> Self {
+        self.prepend_path(&format!("[{}]", index))
+    }
+
+    fn prepend_path(mut self, segment: &str) -> Self {
+        if self.classify() == Category::Io {
+            return self;
+        }
+        self.err.path = if self.err.path.is_empty() || self.err.path.starts_with('[') {
+            format!("{}{}", segment, self.err.path)
+        } else {
+            format!("{}.{}", segment, self.err.path)
+        };
+        self
+    }
+}
+
+impl ErrorCode {
+    fn category(&self) -> Category {
+        match *self {
+            ErrorCode::Message(_) |
+            ErrorCode::UnknownField(..) |
+            ErrorCode::UnknownVariant(..) => Category::Data,
+            ErrorCode::Io(_) => Category::Io,
+            ErrorCode::EofWhileParsingList |
+            ErrorCode::EofWhileParsingObject |
+            ErrorCode::EofWhileParsingString |
+            ErrorCode::EofWhileParsingValue => Category::Eof,
+            ErrorCode::ExpectedColon |
+            ErrorCode::ExpectedListCommaOrEnd |
+            ErrorCode::ExpectedObjectCommaOrEnd |
+            ErrorCode::ExpectedObjectOrArray |
+            ErrorCode::ExpectedSomeIdent |
+            ErrorCode::ExpectedSomeValue |
+            ErrorCode::ExpectedSomeString |
+            ErrorCode::InvalidEscape |
+            ErrorCode::InvalidNumber |
+            ErrorCode::NumberOutOfRange |
+            ErrorCode::InvalidUnicodeCodePoint |
+            ErrorCode::KeyMustBeAString |
+            ErrorCode::LoneLeadingSurrogateInHexEscape |
+            ErrorCode::ExtraComma |
+            ErrorCode::TrailingCharacters |
+            ErrorCode::UnexpectedEndOfHexEscape |
+            ErrorCode::RecursionLimitExceeded |
+            ErrorCode::UnexpectedCharacter |
+            ErrorCode::UnexpectedNewline |
+            ErrorCode::CommentNotAllowed |
+            ErrorCode::QuotelessStringNotAllowed |
+            ErrorCode::SingleQuotedStringNotAllowed |
+            ErrorCode::MultilineStringNotAllowed |
+            ErrorCode::MissingComma |
+            ErrorCode::TrailingCommaNotAllowed |
+            ErrorCode::DuplicateKey(..) => Category::Syntax,
+        }
+    }
+
+    fn kind(&self) -> ErrorKind {
+        match *self {
+            ErrorCode::Message(_) => ErrorKind::Message,
+            ErrorCode::Io(_) => ErrorKind::Io,
+            ErrorCode::UnknownField(..) => ErrorKind::UnknownField,
+            ErrorCode::UnknownVariant(..) => ErrorKind::UnknownVariant,
+            ErrorCode::EofWhileParsingList => ErrorKind::EofWhileParsingList,
+            ErrorCode::EofWhileParsingObject => ErrorKind::EofWhileParsingObject,
+            ErrorCode::EofWhileParsingString => ErrorKind::EofWhileParsingString,
+            ErrorCode::EofWhileParsingValue => ErrorKind::EofWhileParsingValue,
+            ErrorCode::ExpectedColon => ErrorKind::ExpectedColon,
+            ErrorCode::ExpectedListCommaOrEnd => ErrorKind::ExpectedListCommaOrEnd,
+            ErrorCode::ExpectedObjectCommaOrEnd => ErrorKind::ExpectedObjectCommaOrEnd,
+            ErrorCode::ExpectedObjectOrArray => ErrorKind::ExpectedObjectOrArray,
+            ErrorCode::ExpectedSomeIdent => ErrorKind::ExpectedSomeIdent,
+            ErrorCode::ExpectedSomeValue => ErrorKind::ExpectedSomeValue,
+            ErrorCode::ExpectedSomeString => ErrorKind::ExpectedSomeString,
+            ErrorCode::InvalidEscape => ErrorKind::InvalidEscape,
+            ErrorCode::InvalidNumber => ErrorKind::InvalidNumber,
+            ErrorCode::NumberOutOfRange => ErrorKind::NumberOutOfRange,
+            ErrorCode::InvalidUnicodeCodePoint => ErrorKind::InvalidUnicodeCodePoint,
+            ErrorCode::KeyMustBeAString => ErrorKind::KeyMustBeAString,
+            ErrorCode::LoneLeadingSurrogateInHexEscape => {
+                ErrorKind::LoneLeadingSurrogateInHexEscape
+            }
+            ErrorCode::ExtraComma => ErrorKind::ExtraComma,
+            ErrorCode::TrailingCharacters => ErrorKind::TrailingCharacters,
+            ErrorCode::UnexpectedEndOfHexEscape => ErrorKind::UnexpectedEndOfHexEscape,
+            ErrorCode::RecursionLimitExceeded => ErrorKind::RecursionLimitExceeded,
+            ErrorCode::UnexpectedCharacter => ErrorKind::UnexpectedCharacter,
+            ErrorCode::UnexpectedNewline => ErrorKind::UnexpectedNewline,
+            ErrorCode::CommentNotAllowed => ErrorKind::CommentNotAllowed,
+            ErrorCode::QuotelessStringNotAllowed => ErrorKind::QuotelessStringNotAllowed,
+            ErrorCode::SingleQuotedStringNotAllowed => ErrorKind::SingleQuotedStringNotAllowed,
+            ErrorCode::MultilineStringNotAllowed => ErrorKind::MultilineStringNotAllowed,
+            ErrorCode::MissingComma => ErrorKind::MissingComma,
+            ErrorCode::TrailingCommaNotAllowed => ErrorKind::TrailingCommaNotAllowed,
+            ErrorCode::DuplicateKey(..) => ErrorKind::DuplicateKey,
+        }
+    }
 }
 
 impl Display for ErrorCode {
@@ -315,6 +972,12 @@ impl Display for ErrorCode {
         match *self {
             ErrorCode::Message(ref msg) => f.write_str(msg),
             ErrorCode::Io(ref err) => Display::fmt(err, f),
+            ErrorCode::UnknownField(ref name, expected) => {
+                fmt_unknown(f, "field", name, expected)
+            }
+            ErrorCode::UnknownVariant(ref name, expected) => {
+                fmt_unknown(f, "variant", name, expected)
+            }
             ErrorCode::EofWhileParsingList => f.write_str("EOF while parsing a list"),
             ErrorCode::EofWhileParsingObject => f.write_str("EOF while parsing an object"),
             ErrorCode::EofWhileParsingString => f.write_str("EOF while parsing a string"),
@@ -340,8 +1003,118 @@ impl Display for ErrorCode {
             ErrorCode::RecursionLimitExceeded => f.write_str("recursion limit exceeded"),
             ErrorCode::UnexpectedCharacter => f.write_str("unexpected character"),
             ErrorCode::UnexpectedNewline => f.write_str("unexpected newline character"),
+            ErrorCode::CommentNotAllowed => f.write_str("comments are not allowed"),
+            ErrorCode::QuotelessStringNotAllowed => {
+                f.write_str("quoteless strings are not allowed")
+            }
+            ErrorCode::SingleQuotedStringNotAllowed => {
+                f.write_str("single quoted strings are not allowed")
+            }
+            ErrorCode::MultilineStringNotAllowed => {
+                f.write_str("multiline strings are not allowed")
+            }
+            ErrorCode::MissingComma => f.write_str("expected `,` between values"),
+            ErrorCode::TrailingCommaNotAllowed => f.write_str("trailing commas are not allowed"),
+            ErrorCode::DuplicateKey(ref key, line, column) => {
+                write!(
+                    f,
+                    "duplicate key `{}` (first defined at line {} column {})",
+                    key,
+                    line,
+                    column
+                )
+            }
+        }
+    }
+}
+
+/// Whether a member name can be written in a path without brackets and quotes.
+fn is_simple_key(key: &str) -> bool {
+    !key.is_empty() &&
+        key.chars().all(|ch| match ch {
+            '_' | '-' | '$' => true,
+            _ => ch.is_alphanumeric(),
+        })
+}
+
+// Not public API. Should be pub(crate).
+#[doc(hidden)]
+pub fn push_path_key(path: &mut String, key: &str) {
+    if is_simple_key(key) {
+        if !path.is_empty() {
+            path.push('.');
+        }
+        path.push_str(key);
+    } else {
+        path.push_str(&format!("[{:?}]", key));
+    }
+}
+
+/// Matches the messages of the default `de::Error::unknown_field` and
+/// `de::Error::unknown_variant`, followed by the suggestion if there is one.
+fn fmt_unknown(
+    f: &mut fmt::Formatter,
+    what: &str,
+    name: &str,
+    expected: &'static [&'static str],
+) -> fmt::Result {
+    try!(write!(f, "unknown {} `{}`, ", what, name));
+    match expected.len() {
+        0 => try!(write!(f, "there are no {}s", what)),
+        1 => try!(write!(f, "expected `{}`", expected[0])),
+        2 => try!(write!(f, "expected `{}` or `{}`", expected[0], expected[1])),
+        _ => {
+            try!(write!(f, "expected one of `{}`", expected[0]));
+            for alt in &expected[1..] {
+                try!(write!(f, ", `{}`", alt));
+            }
+        }
+    }
+    match closest(name, expected) {
+        Some(suggestion) => write!(f, " (did you mean `{}`?)", suggestion),
+        None => Ok(()),
+    }
+}
+
+/// The expected name with the smallest edit distance to `name`, ignoring
+/// case, if it is small enough for `name` to be a typo of it: at most one edit
+/// for every three characters, and at least one.
+fn closest(name: &str, expected: &'static [&'static str]) -> Option<&'static str> {
+    let name: Vec<char> = name.to_lowercase().chars().collect();
+    let max = cmp::max(name.len() / 3, 1);
+    let mut best = None;
+    for candidate in expected {
+        let chars: Vec<char> = candidate.to_lowercase().chars().collect();
+        let distance = edit_distance(&name, &chars);
+        match best {
+            Some((_, best_distance)) if best_distance <= distance => {}
+            _ if distance <= max => best = Some((*candidate, distance)),
+            _ => {}
+        }
+    }
+    best.map(|(candidate, _)| candidate)
+}
+
+/// The number of insertions, deletions, substitutions and transpositions of
+/// adjacent characters that turn `a` into `b`.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    // Rows of the distance matrix for the prefixes of `a` of length i - 2,
+    // i - 1 and i.
+    let mut before: Vec<usize> = Vec::new();
+    let mut prev: Vec<usize> = (0..b.len() + 1).collect();
+    for i in 1..a.len() + 1 {
+        let mut row = vec![i; b.len() + 1];
+        for j in 1..b.len() + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            row[j] = cmp::min(cmp::min(prev[j] + 1, row[j - 1] + 1), prev[j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                row[j] = cmp::min(row[j], before[j - 2] + 1);
+            }
         }
+        before = prev;
+        prev = row;
     }
+    prev[b.len()]
 }
 
 impl error::Error for Error {
@@ -369,8 +1142,23 @@ impl Display for Error {
     }
 }
 
+impl ErrorImpl {
+    /// The path to put in front of the message. Syntax errors have a path too,
+    /// but their position already says where they are.
+    fn shown_path(&self) -> Option<&str> {
+        if self.path.is_empty() || self.code.category() != Category::Data {
+            None
+        } else {
+            Some(&self.path)
+        }
+    }
+}
+
 impl Display for ErrorImpl {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if let Some(path) = self.shown_path() {
+            try!(write!(f, "{}: ", path));
+        }
         if self.line == 0 {
             Display::fmt(&self.code, f)
         } else {
@@ -395,15 +1183,7 @@ impl Debug for Error {
 
 impl de::Error for Error {
     fn custom<T: Display>(msg: T) -> Error {
-        Error {
-            err: Box::new(
-                ErrorImpl {
-                    code: ErrorCode::Message(msg.to_string().into_boxed_str()),
-                    line: 0,
-                    column: 0,
-                },
-            ),
-        }
+        Error::data(ErrorCode::Message(msg.to_string().into_boxed_str()))
     }
 
     fn invalid_type(unexp: de::Unexpected, exp: &de::Expected) -> Self {
@@ -413,6 +1193,14 @@ impl de::Error for Error {
             Error::custom(format_args!("invalid type: {}, expected {}", unexp, exp))
         }
     }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        Error::data(ErrorCode::UnknownField(field.into(), expected))
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        Error::data(ErrorCode::UnknownVariant(variant.into(), expected))
+    }
 }
 
 impl ser::Error for Error {
@@ -423,6 +1211,10 @@ impl ser::Error for Error {
                     code: ErrorCode::Message(msg.to_string().into_boxed_str()),
                     line: 0,
                     column: 0,
+                    char_column: 0,
+                    utf16_column: 0,
+                    range: None,
+                    path: String::new(),
                 },
             ),
         }
@@ -0,0 +1,991 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Read Hjson as a sequence of events, without building a `Value` or going
+//! through Serde.
+//!
+//! A [`Parser`] reads its input one [`Event`] at a time, as the caller asks for
+//! them. Only the string, member name or comment of the current event is held
+//! in memory, along with one byte per level of nesting, so that it can go
+//! through inputs of any size.
+//!
+//! ```rust
+//! use serde_hjson::events::{Event, Parser};
+//!
+//! let mut parser = Parser::from_str("
+//! // The release channel
+//! channel: stable
+//! mirrors: [1, 2]
+//! ");
+//!
+//! let mut keys = Vec::new();
+//! while let Some((event, span)) = parser.next_event().unwrap() {
+//!     if let Event::Key(key) = event {
+//!         keys.push(format!("{} at line {}", key, span.line));
+//!     }
+//! }
+//!
+//! assert_eq!(keys, ["channel at line 3", "mirrors at line 4"]);
+//! ```
+//!
+//! A [`PushParser`] is fed the input instead, a chunk at a time as it arrives,
+//! and reports the events or values that are complete so far. This suits input
+//! from a socket in an event loop, where waiting for the rest of a document
+//! would block.
+//!
+//! [`Parser`]: struct.Parser.html
+//! [`Event`]: enum.Event.html
+//! [`PushParser`]: struct.PushParser.html
+
+use std::io;
+use std::str;
+
+use de::{parse_number, starts_braceless_root};
+use error::{Error, ErrorCode, Result};
+use read::{self, Read};
+use map::Map;
+use source_map::Span;
+use value::{Number, Value};
+
+/// Something the `Parser` found in the input.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event<'a> {
+    /// The `{` at the start of an object, or the start of a root object
+    /// without braces.
+    StartObject,
+    /// The `}` at the end of an object, or the end of a root object without
+    /// braces.
+    EndObject,
+    /// The `[` at the start of an array.
+    StartArray,
+    /// The `]` at the end of an array.
+    EndArray,
+    /// The name of an object member, without quotes or escapes.
+    Key(&'a str),
+    /// A string, without quotes or escapes.
+    String(&'a str),
+    /// A number.
+    Number(Number),
+    /// `true` or `false`.
+    Bool(bool),
+    /// `null`.
+    Null,
+    /// A comment, as written including the `#`, `//` or `/*` and `*/`.
+    Comment(&'a str),
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+/// A pull parser that reads the events of an Hjson or JSON document.
+pub struct Parser<R> {
+    read: R,
+    scratch: Vec<u8>,
+    /// The member name, string or comment of the current event.
+    text: String,
+    /// The objects and arrays being read, innermost last.
+    containers: Vec<Container>,
+    state: State,
+    /// Whether a newline has been read since the last value.
+    newline: bool,
+    /// The start of a `/` that has been consumed, because it takes the byte
+    /// after it to tell whether it starts a comment.
+    slash: Option<Span>,
+    /// The first member name of a root object without braces, which is read
+    /// before the start of the object is reported.
+    root_key: Option<Span>,
+    /// Whether the input is a stream of root values rather than one document.
+    multiple: bool,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Container {
+    Object,
+    Array,
+    /// A root object without braces, which ends at the end of the input.
+    Braceless,
+}
+
+/// What the parser expects next.
+#[derive(Clone, Copy, PartialEq)]
+enum State {
+    Root,
+    Name,
+    Colon,
+    Value,
+    Element,
+    /// A separator after a member or element.
+    AfterValue,
+    /// The end of the input after the root value.
+    Trailing,
+    /// Nothing, after the end of the input or an error.
+    End,
+}
+
+/// An event whose text, if any, is in `Parser::text`.
+enum Found {
+    StartObject,
+    EndObject,
+    StartArray,
+    EndArray,
+    Key,
+    String,
+    Number(Number),
+    Bool(bool),
+    Null,
+    Comment,
+}
+
+impl<'de, R> Parser<R>
+where
+    R: Read<'de>,
+{
+    /// Create a parser from one of the possible serde_hjson input sources.
+    ///
+    /// Typically it is more convenient to use one of these methods instead:
+    ///
+    ///   - Parser::from_str
+    ///   - Parser::from_slice
+    ///   - Parser::from_reader
+    pub fn new(read: R) -> Self {
+        Parser {
+            read: read,
+            scratch: Vec::new(),
+            text: String::new(),
+            containers: Vec::new(),
+            state: State::Root,
+            newline: false,
+            slash: None,
+            root_key: None,
+            multiple: false,
+        }
+    }
+
+    /// Reads the next event along with its span, or returns `None` at the end
+    /// of the document.
+    ///
+    /// Events are read only as far as the input is valid, an error ends the
+    /// document. The span of the start and end of a root object without braces
+    /// is empty.
+    pub fn next_event(&mut self) -> Result<Option<(Event, Span)>> {
+        let result = self.parse_event();
+        self.report(result)
+    }
+
+    /// Turns what `parse_event` found into an event, ending the document at
+    /// an error.
+    fn report(&mut self, result: Result<Option<(Found, Span)>>) -> Result<Option<(Event, Span)>> {
+        let (found, span) = match result {
+            Ok(Some(found)) => found,
+            Ok(None) => return Ok(None),
+            Err(err) => {
+                self.state = State::End;
+                return Err(err);
+            }
+        };
+        let event = match found {
+            Found::StartObject => Event::StartObject,
+            Found::EndObject => Event::EndObject,
+            Found::StartArray => Event::StartArray,
+            Found::EndArray => Event::EndArray,
+            Found::Key => Event::Key(&self.text),
+            Found::String => Event::String(&self.text),
+            Found::Number(n) => Event::Number(n),
+            Found::Bool(b) => Event::Bool(b),
+            Found::Null => Event::Null,
+            Found::Comment => Event::Comment(&self.text),
+        };
+        Ok(Some((event, span)))
+    }
+
+    fn parse_event(&mut self) -> Result<Option<(Found, Span)>> {
+        loop {
+            if self.state == State::End {
+                return Ok(None);
+            }
+            if let Some(span) = self.root_key.take() {
+                return Ok(Some((Found::Key, span)));
+            }
+
+            let peek = try!(self.parse_whitespace());
+            if try!(self.at_comment(peek)) {
+                return self.parse_comment().map(Some);
+            }
+
+            let found = match self.state {
+                State::Root if self.multiple => {
+                    match peek {
+                        Some(b',') | Some(b']') | Some(b'}') => {
+                            return Err(self.peek_error(ErrorCode::ExpectedSomeValue));
+                        }
+                        Some(_) => try!(self.parse_value()),
+                        None => {
+                            self.state = State::End;
+                            return Ok(None);
+                        }
+                    }
+                }
+                State::Root => {
+                    match peek {
+                        Some(b'"') | Some(b'\'') => try!(self.parse_root_quoted()),
+                        Some(b'{') | Some(b'[') => try!(self.parse_value()),
+                        Some(_) => try!(self.parse_scalar(true)),
+                        None => return Err(self.peek_error(ErrorCode::EofWhileParsingValue)),
+                    }
+                }
+                State::Name => {
+                    let braceless = self.containers.last() == Some(&Container::Braceless);
+                    match peek {
+                        Some(b'}') if !braceless => self.close(),
+                        Some(b'}') | Some(b']') if braceless => {
+                            return Err(self.peek_error(ErrorCode::TrailingCharacters));
+                        }
+                        Some(b',') => return Err(self.peek_error(ErrorCode::ExtraComma)),
+                        Some(_) => try!(self.parse_key()),
+                        None if braceless => self.close(),
+                        None => return Err(self.peek_error(ErrorCode::EofWhileParsingObject)),
+                    }
+                }
+                State::Colon => {
+                    match peek {
+                        Some(b':') => {
+                            self.eat_char();
+                            self.state = State::Value;
+                            continue;
+                        }
+                        Some(_) => return Err(self.peek_error(ErrorCode::ExpectedColon)),
+                        None => return Err(self.peek_error(ErrorCode::EofWhileParsingObject)),
+                    }
+                }
+                State::Value => {
+                    match peek {
+                        Some(_) => try!(self.parse_value()),
+                        None => return Err(self.peek_error(ErrorCode::EofWhileParsingValue)),
+                    }
+                }
+                State::Element => {
+                    match peek {
+                        Some(b']') => self.close(),
+                        Some(b',') => return Err(self.peek_error(ErrorCode::ExtraComma)),
+                        Some(_) => try!(self.parse_value()),
+                        None => return Err(self.peek_error(ErrorCode::EofWhileParsingList)),
+                    }
+                }
+                State::AfterValue => {
+                    let container = *self.containers.last().unwrap();
+                    match (peek, container) {
+                        (Some(b','), _) => {
+                            self.eat_char();
+                            self.next_member();
+                            continue;
+                        }
+                        (Some(b'}'), Container::Object) | (Some(b']'), Container::Array) |
+                        (None, Container::Braceless) => self.close(),
+                        (Some(_), _) if self.newline => {
+                            self.next_member();
+                            continue;
+                        }
+                        (Some(_), Container::Object) => {
+                            return Err(self.peek_error(ErrorCode::ExpectedObjectCommaOrEnd));
+                        }
+                        (Some(_), Container::Array) => {
+                            return Err(self.peek_error(ErrorCode::ExpectedListCommaOrEnd));
+                        }
+                        (Some(_), Container::Braceless) => {
+                            return Err(self.peek_error(ErrorCode::UnexpectedCharacter));
+                        }
+                        (None, Container::Object) => {
+                            return Err(self.peek_error(ErrorCode::EofWhileParsingObject));
+                        }
+                        (None, Container::Array) => {
+                            return Err(self.peek_error(ErrorCode::EofWhileParsingList));
+                        }
+                    }
+                }
+                State::Trailing => {
+                    match peek {
+                        Some(_) => return Err(self.peek_error(ErrorCode::TrailingCharacters)),
+                        None => {
+                            self.state = State::End;
+                            return Ok(None);
+                        }
+                    }
+                }
+                State::End => unreachable!(),
+            };
+            return Ok(Some(found));
+        }
+    }
+
+    fn peek(&mut self) -> Result<Option<u8>> {
+        self.read.peek().map_err(Error::io)
+    }
+
+    fn eat_char(&mut self) {
+        let _ = self.read.next();
+    }
+
+    /// Error caused by the byte that would be read next.
+    fn peek_error(&self, reason: ErrorCode) -> Error {
+        let pos = self.read.peek_position();
+        let offset = self.read.byte_offset();
+        if pos.end > offset {
+            Error::syntax_at(reason, pos, pos.start..pos.end)
+        } else {
+            Error::syntax_at(reason, pos, offset..offset)
+        }
+    }
+
+    /// An empty span at the byte that would be read next, which has been
+    /// peeked unless the input ended.
+    fn span_start(&self) -> Span {
+        let start = self.read.byte_offset();
+        let peeked = self.read.peek_position();
+        let position = if peeked.end > start {
+            peeked
+        } else {
+            // Just past the last byte.
+            let mut position = self.read.position();
+            position.column += 1;
+            position
+        };
+        Span {
+            start: start,
+            end: start,
+            line: position.line,
+            column: position.column,
+        }
+    }
+
+    /// Ends `span` at the byte that would be read next.
+    fn span_end(&self, span: Span) -> Span {
+        Span { end: self.read.byte_offset(), ..span }
+    }
+
+    /// Takes the scratch bytes as the text of the current event.
+    fn take_text(&mut self, span: Span) -> Result<()> {
+        self.text.clear();
+        match str::from_utf8(&self.scratch) {
+            Ok(s) => {
+                self.text.push_str(s);
+                Ok(())
+            }
+            Err(_) => {
+                let pos = self.read.position();
+                Err(Error::syntax_at(ErrorCode::InvalidUnicodeCodePoint, pos, span.start..span.end))
+            }
+        }
+    }
+
+    /// Skips whitespace and returns the next byte without consuming it, or
+    /// `None` at the end of the input. A `/` is consumed though, and kept in
+    /// `slash`.
+    fn parse_whitespace(&mut self) -> Result<Option<u8>> {
+        if self.slash.is_some() {
+            return Ok(Some(b'/'));
+        }
+        loop {
+            match try!(self.peek()) {
+                Some(b'\n') => {
+                    self.eat_char();
+                    self.newline = true;
+                }
+                Some(b' ') | Some(b'\t') | Some(b'\r') => self.eat_char(),
+                Some(b'/') => {
+                    self.slash = Some(self.span_start());
+                    self.eat_char();
+                    return Ok(Some(b'/'));
+                }
+                other => return Ok(other),
+            }
+        }
+    }
+
+    fn at_comment(&mut self, peek: Option<u8>) -> Result<bool> {
+        match peek {
+            Some(b'#') => Ok(true),
+            Some(b'/') => Ok(match try!(self.peek()) {
+                Some(b'/') | Some(b'*') => true,
+                _ => false,
+            }),
+            _ => Ok(false),
+        }
+    }
+
+    fn parse_comment(&mut self) -> Result<(Found, Span)> {
+        self.scratch.clear();
+        let span = match self.slash.take() {
+            Some(span) => {
+                self.scratch.push(b'/');
+                span
+            }
+            None => self.span_start(),
+        };
+
+        if self.scratch.is_empty() || try!(self.peek()) == Some(b'/') {
+            // A line comment ends before the newline.
+            loop {
+                match try!(self.peek()) {
+                    Some(b'\n') | Some(b'\r') | None => break,
+                    Some(ch) => {
+                        self.eat_char();
+                        self.scratch.push(ch);
+                    }
+                }
+            }
+        } else {
+            self.eat_char();
+            self.scratch.push(b'*');
+            // A block comment without an end runs to the end of the input.
+            while let Some(ch) = try!(self.read.next().map_err(Error::io)) {
+                self.scratch.push(ch);
+                if ch == b'*' && try!(self.peek()) == Some(b'/') {
+                    self.eat_char();
+                    self.scratch.push(b'/');
+                    break;
+                }
+            }
+        }
+
+        let span = self.span_end(span);
+        try!(self.take_text(span));
+        Ok((Found::Comment, span))
+    }
+
+    /// Moves on to the next member or element after a separator.
+    fn next_member(&mut self) {
+        self.state = match self.containers.last() {
+            Some(&Container::Array) => State::Element,
+            _ => State::Name,
+        };
+    }
+
+    /// Moves on after a complete value.
+    fn end_value(&mut self) {
+        self.newline = false;
+        self.state = if !self.containers.is_empty() {
+            State::AfterValue
+        } else if self.multiple {
+            State::Root
+        } else {
+            State::Trailing
+        };
+    }
+
+    fn open(&mut self, container: Container, found: Found) -> Result<(Found, Span)> {
+        let span = self.span_start();
+        self.eat_char();
+        self.containers.push(container);
+        self.next_member();
+        Ok((found, self.span_end(span)))
+    }
+
+    fn close(&mut self) -> (Found, Span) {
+        let span = self.span_start();
+        let found = match self.containers.pop() {
+            Some(Container::Array) => Found::EndArray,
+            Some(Container::Braceless) => {
+                // The end of the input.
+                self.end_value();
+                return (Found::EndObject, span);
+            }
+            _ => Found::EndObject,
+        };
+        self.eat_char();
+        let span = self.span_end(span);
+        self.end_value();
+        (found, span)
+    }
+
+    fn parse_value(&mut self) -> Result<(Found, Span)> {
+        match try!(self.peek()) {
+            _ if self.slash.is_some() => self.parse_scalar(false),
+            Some(b'{') => self.open(Container::Object, Found::StartObject),
+            Some(b'[') => self.open(Container::Array, Found::StartArray),
+            Some(b'"') | Some(b'\'') => {
+                let span = try!(self.parse_quoted());
+                self.end_value();
+                Ok((Found::String, span))
+            }
+            _ => self.parse_scalar(false),
+        }
+    }
+
+    /// Reads a string in `"`, `'` or `'''` quotes into `text`.
+    fn parse_quoted(&mut self) -> Result<Span> {
+        let span = self.span_start();
+        self.scratch.clear();
+        let quote = try!(self.peek());
+        self.eat_char();
+        self.text.clear();
+        if quote == Some(b'"') {
+            let s = try!(self.read.parse_double_str(&mut self.scratch));
+            self.text.push_str(&s);
+        } else {
+            let col = self.read.position().column - 1;
+            let s = try!(self.read.parse_single_str(&mut self.scratch, col));
+            self.text.push_str(&s);
+        }
+        Ok(self.span_end(span))
+    }
+
+    /// Reads a quoted root value, which is the first member name of a root
+    /// object without braces if a `:` follows it.
+    fn parse_root_quoted(&mut self) -> Result<(Found, Span)> {
+        let span = try!(self.parse_quoted());
+        while let Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') = try!(self.peek()) {
+            self.eat_char();
+        }
+        if try!(self.peek()) == Some(b':') {
+            Ok(self.start_braceless(span))
+        } else {
+            self.end_value();
+            Ok((Found::String, span))
+        }
+    }
+
+    /// Starts a root object without braces whose first member name is in
+    /// `text`.
+    fn start_braceless(&mut self, key: Span) -> (Found, Span) {
+        self.containers.push(Container::Braceless);
+        self.state = State::Colon;
+        self.root_key = Some(key);
+        (Found::StartObject, Span { end: key.start, ..key })
+    }
+
+    fn parse_key(&mut self) -> Result<(Found, Span)> {
+        let span = match try!(self.peek()) {
+            Some(b'"') | Some(b'\'') if self.slash.is_none() => try!(self.parse_quoted()),
+            _ => {
+                self.scratch.clear();
+                let span = match self.slash.take() {
+                    Some(span) => {
+                        self.scratch.push(b'/');
+                        span
+                    }
+                    None => self.span_start(),
+                };
+                loop {
+                    match try!(self.peek()) {
+                        Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') | Some(b':') | None => {
+                            break
+                        }
+                        Some(ch) => {
+                            self.eat_char();
+                            self.scratch.push(ch);
+                        }
+                    }
+                }
+                let span = self.span_end(span);
+                try!(self.take_text(span));
+                span
+            }
+        };
+        self.state = State::Colon;
+        Ok((Found::Key, span))
+    }
+
+    /// Reads a number, `true`, `false`, `null` or a quoteless string. At the
+    /// root, it may also be the first member name of an object without
+    /// braces.
+    fn parse_scalar(&mut self, root: bool) -> Result<(Found, Span)> {
+        // Like in the deserializer, a document that is not a root object
+        // without braces is a single value, even if a `:` follows its first
+        // word.
+        let braceless = root && (self.slash.is_some() || starts_braceless_root(&mut self.read));
+        self.scratch.clear();
+        let span = match self.slash.take() {
+            Some(span) => {
+                self.scratch.push(b'/');
+                span
+            }
+            None => self.span_start(),
+        };
+
+        // The word that may be a literal. At the root it runs until whitespace
+        // or a `:`, elsewhere it also ends at a `,`, `]`, `}` or a comment.
+        loop {
+            match try!(self.peek()) {
+                Some(b' ') | Some(b'\t') | Some(b'\r') | Some(b'\n') | None => break,
+                Some(b':') if root => break,
+                Some(b',') | Some(b']') | Some(b'}') | Some(b'#') if !root => break,
+                Some(b'/') if !root => {
+                    self.slash = Some(self.span_start());
+                    self.eat_char();
+                    if let Some(b'/') | Some(b'*') = try!(self.peek()) {
+                        break;
+                    }
+                    self.slash = None;
+                    self.scratch.push(b'/');
+                }
+                Some(ch) => {
+                    self.eat_char();
+                    self.scratch.push(ch);
+                }
+            }
+        }
+        let word_len = self.scratch.len();
+
+        // A literal may only be followed by a comment or, unless it is the
+        // root value, by a `,`, `]` or `}`. Anything else makes the whole
+        // line a quoteless string.
+        let mut literal = true;
+        if self.slash.is_none() {
+            while let Some(ch @ b' ') | Some(ch @ b'\t') = try!(self.peek()) {
+                self.eat_char();
+                self.scratch.push(ch);
+            }
+            literal = match try!(self.peek()) {
+                Some(b':') if braceless && word_len > 0 => {
+                    self.scratch.truncate(word_len);
+                    let key = Span { end: span.start + word_len, ..span };
+                    try!(self.take_text(key));
+                    return Ok(self.start_braceless(key));
+                }
+                Some(b'\r') | Some(b'\n') | Some(b'#') | None => true,
+                Some(b',') | Some(b']') | Some(b'}') => !root,
+                Some(b'/') => {
+                    self.slash = Some(self.span_start());
+                    self.eat_char();
+                    if let Some(b'/') | Some(b'*') = try!(self.peek()) {
+                        true
+                    } else {
+                        self.slash = None;
+                        self.scratch.push(b'/');
+                        false
+                    }
+                }
+                Some(_) => false,
+            };
+        }
+
+        if literal {
+            let found = match &self.scratch[..word_len] {
+                b"true" => Some(Found::Bool(true)),
+                b"false" => Some(Found::Bool(false)),
+                b"null" => Some(Found::Null),
+                word => {
+                    str::from_utf8(word).ok().and_then(parse_number).map(Found::Number)
+                }
+            };
+            if let Some(found) = found {
+                self.end_value();
+                return Ok((found, Span { end: span.start + word_len, ..span }));
+            }
+            // The comment is part of the string.
+            if self.slash.take().is_some() {
+                self.scratch.push(b'/');
+            }
+        }
+
+        loop {
+            match try!(self.peek()) {
+                Some(b'\n') | Some(b'\r') | None => break,
+                Some(ch) => {
+                    self.eat_char();
+                    self.scratch.push(ch);
+                }
+            }
+        }
+        // Trailing whitespace is not part of the string.
+        while let Some(&b' ') | Some(&b'\t') = self.scratch.last() {
+            self.scratch.pop();
+        }
+        let span = Span { end: span.start + self.scratch.len(), ..span };
+        try!(self.take_text(span));
+        self.end_value();
+        Ok((Found::String, span))
+    }
+}
+
+impl<R> Parser<read::IoRead<R>>
+where
+    R: io::Read,
+{
+    /// Creates a parser from an `io::Read`.
+    pub fn from_reader(reader: R) -> Self {
+        Parser::new(read::IoRead::new(reader))
+    }
+}
+
+impl<'a> Parser<read::SliceRead<'a>> {
+    /// Creates a parser from a `&[u8]`.
+    pub fn from_slice(bytes: &'a [u8]) -> Self {
+        Parser::new(read::SliceRead::new(bytes))
+    }
+}
+
+impl<'a> Parser<read::StrRead<'a>> {
+    /// Creates a parser from a `&str`.
+    pub fn from_str(s: &'a str) -> Self {
+        Parser::new(read::StrRead::new(s))
+    }
+}
+
+/// A parser that is fed its input a chunk at a time, and reports the events or
+/// values that are complete so far.
+///
+/// A chunk may end anywhere, also inside a string, a comment or a UTF-8
+/// character. An event that the input fed so far ends in is held back until
+/// the rest of it has been fed, so only the input from the start of that event
+/// is kept in memory.
+///
+/// ```rust
+/// use serde_hjson::events::PushParser;
+///
+/// let mut parser = PushParser::stream();
+/// let mut values = Vec::new();
+/// for chunk in &["{id: 1, motd: '''\n  Wel", "come\n  '''}\n[1,", " 2] 3"] {
+///     parser.feed(chunk.as_bytes());
+///     while let Some(value) = parser.next_value().unwrap() {
+///         values.push(value);
+///     }
+/// }
+/// assert_eq!(values.len(), 2);
+/// assert_eq!(values[0]["motd"], "Welcome");
+///
+/// // The number at the end might have gone on.
+/// parser.finish();
+/// values.push(parser.next_value().unwrap().unwrap());
+/// assert_eq!(values[2], 3);
+/// ```
+pub struct PushParser {
+    parser: Parser<read::ChunkRead>,
+    /// Whether the input fed so far ends in the middle of an event.
+    stalled: bool,
+    /// Bytes one of which has to be fed before the event that stalled can be
+    /// complete, or empty if any byte may do.
+    wanted: &'static [u8],
+    /// The objects and arrays that `next_value` is building along with their
+    /// member names, innermost last.
+    stack: Vec<(Value, Option<String>)>,
+    key: Option<String>,
+}
+
+impl PushParser {
+    /// Creates a push parser for a single document.
+    pub fn new() -> Self {
+        PushParser {
+            parser: Parser::new(read::ChunkRead::new()),
+            stalled: false,
+            wanted: b"",
+            stack: Vec::new(),
+            key: None,
+        }
+    }
+
+    /// Creates a push parser for a stream of root values separated by
+    /// whitespace, like `StreamDeserializer`. Root objects need braces here,
+    /// and the end of the input is not an error between values.
+    pub fn stream() -> Self {
+        let mut parser = PushParser::new();
+        parser.parser.multiple = true;
+        parser
+    }
+
+    /// Appends the next chunk of the input.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.stalled {
+            self.stalled = if self.wanted.is_empty() {
+                bytes.is_empty()
+            } else {
+                !bytes.iter().any(|ch| self.wanted.contains(ch))
+            };
+        }
+        self.parser.read.feed(bytes);
+    }
+
+    /// Marks the end of the input, after which the last events can be read.
+    pub fn finish(&mut self) {
+        self.stalled = false;
+        self.parser.read.finish();
+    }
+
+    /// Reads the next event along with its span, or returns `None` if the
+    /// input fed so far ends before the next event is complete, or at the end
+    /// of the document after `finish`.
+    ///
+    /// Events are read only as far as the input is valid, an error ends the
+    /// document. The span of the start and end of a root object without braces
+    /// is empty.
+    pub fn next_event(&mut self) -> Result<Option<(Event, Span)>> {
+        if self.stalled {
+            return Ok(None);
+        }
+
+        let parser = &mut self.parser;
+        let checkpoint = parser.read.checkpoint();
+        // An event opens or closes at most one container.
+        let depth = parser.containers.len();
+        let container = parser.containers.last().cloned();
+        let state = parser.state;
+        let newline = parser.newline;
+        let slash = parser.slash;
+        let root_key = parser.root_key;
+
+        let result = parser.parse_event();
+        if parser.read.starved() {
+            parser.read.rewind(checkpoint);
+            parser.containers.truncate(depth);
+            if parser.containers.len() < depth {
+                parser.containers.extend(container);
+            }
+            parser.state = state;
+            parser.newline = newline;
+            parser.slash = slash;
+            parser.root_key = root_key;
+
+            let mut unread = parser.read.unread();
+            if slash.is_some() {
+                unread.insert(0, b'/');
+            }
+            self.wanted = wanted(&unread);
+            self.stalled = true;
+            return Ok(None);
+        }
+        parser.report(result)
+    }
+
+    /// Reads the next complete root value, skipping comments, or returns
+    /// `None` if the input fed so far ends before it, or at the end of the
+    /// document after `finish`.
+    ///
+    /// The events of the value are used up, so this is not meant to be mixed
+    /// with `next_event` in the middle of a value.
+    pub fn next_value(&mut self) -> Result<Option<Value>> {
+        loop {
+            let value = match self.next_event() {
+                Ok(Some((event, _))) => {
+                    match event {
+                        Event::StartObject => Value::Object(Map::new()),
+                        Event::StartArray => Value::Array(Vec::new()),
+                        Event::Key(key) => {
+                            self.key = Some(key.to_owned());
+                            continue;
+                        }
+                        Event::String(s) => Value::String(s.to_owned()),
+                        Event::Number(n) => Value::Number(n),
+                        Event::Bool(b) => Value::Bool(b),
+                        Event::Null => Value::Null,
+                        Event::EndObject | Event::EndArray => {
+                            let (value, key) = self.stack.pop().unwrap();
+                            self.key = key;
+                            match self.add(value) {
+                                Some(value) => return Ok(Some(value)),
+                                None => continue,
+                            }
+                        }
+                        Event::Comment(_) | Event::__Nonexhaustive => continue,
+                    }
+                }
+                Ok(None) => return Ok(None),
+                Err(err) => {
+                    self.stack.clear();
+                    return Err(err);
+                }
+            };
+            match value {
+                Value::Object(_) | Value::Array(_) => {
+                    let key = self.key.take();
+                    self.stack.push((value, key));
+                }
+                value => {
+                    if let Some(value) = self.add(value) {
+                        return Ok(Some(value));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds a complete value to the object or array around it, or returns it
+    /// if it is a root value.
+    fn add(&mut self, value: Value) -> Option<Value> {
+        match self.stack.last_mut() {
+            Some(&mut (Value::Object(ref mut object), _)) => {
+                object.insert(self.key.take().unwrap_or_default(), value);
+                None
+            }
+            Some(&mut (Value::Array(ref mut array), _)) => {
+                array.push(value);
+                None
+            }
+            _ => Some(value),
+        }
+    }
+}
+
+impl Default for PushParser {
+    fn default() -> Self {
+        PushParser::new()
+    }
+}
+
+/// Bytes one of which has to follow `unread`, the input from the start of an
+/// event that ends somewhere after it, before the event can be complete. Until
+/// then, reading the event again would only run out of input again. Empty if
+/// any byte may complete it.
+fn wanted(unread: &[u8]) -> &'static [u8] {
+    let token = match unread.iter().position(|ch| !b" \t\r\n".contains(ch)) {
+        Some(start) => &unread[start..],
+        None => return b"",
+    };
+    let contains = |bytes: &[u8], pattern: &[u8]| bytes.windows(pattern.len()).any(|w| w == pattern);
+
+    match token[0] {
+        b'\'' if token.starts_with(b"'''") => {
+            if contains(&token[3..], b"'''") { b"" } else { b"'" }
+        }
+        quote @ b'"' | quote @ b'\'' => {
+            // A string ends at the closing quote, or with an error at a newline.
+            let mut escaped = false;
+            for &ch in &token[1..] {
+                if escaped {
+                    escaped = false;
+                } else if ch == b'\\' {
+                    escaped = true;
+                } else if ch == quote || ch == b'\n' {
+                    return b"";
+                }
+            }
+            if quote == b'"' { b"\"\n" } else { b"'\n" }
+        }
+        b'#' => line_end(token),
+        b'/' if token.starts_with(b"//") => line_end(token),
+        b'/' if token.starts_with(b"/*") => {
+            if contains(&token[2..], b"*/") { b"" } else { b"/" }
+        }
+        b'{' | b'}' | b'[' | b']' | b',' | b':' | b'/' => b"",
+        _ => {
+            if token.contains(&b'\n') || token.contains(&b'\r') {
+                return b"";
+            }
+            // A word is a literal, a number or a member name if a delimiter
+            // follows it. Followed by anything else on the line, it is the
+            // start of a quoteless string, which ends at the end of the line.
+            let delimiters: &'static [u8] = b" \t:,]}#/";
+            match token.iter().position(|ch| delimiters.contains(ch)) {
+                None => b" \t\r\n:,]}#/",
+                Some(end) => {
+                    match token[end..].iter().find(|&&ch| ch != b' ' && ch != b'\t') {
+                        Some(ch) if !delimiters.contains(ch) => b"\r\n",
+                        _ => b"",
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Like `wanted`, for a line comment.
+fn line_end(comment: &[u8]) -> &'static [u8] {
+    if comment.contains(&b'\n') || comment.contains(&b'\r') {
+        b""
+    } else {
+        b"\r\n"
+    }
+}
@@ -25,6 +25,38 @@ pub struct LineColIterator<I> {
     /// computation of the current line, column, and byte offset while only
     /// updating one of the counters in `next()` in the common case.
     start_of_line: usize,
+
+    /// Number of UTF-8 continuation bytes in the current line, to compute the
+    /// column in characters.
+    continuation_bytes: usize,
+
+    /// Number of characters in the current line that take two UTF-16 code
+    /// units, to compute the column in UTF-16 code units.
+    surrogate_pairs: usize,
+
+    /// Byte offset of the start of the character of the most recent byte.
+    char_start: usize,
+
+    /// Length in bytes of the character of the most recent byte, as told by
+    /// its first byte.
+    char_len: usize,
+
+    /// Column in bytes, characters and UTF-16 code units of the last character
+    /// of the previous line, which the newline character at its end follows.
+    line_end: (usize, usize, usize),
+}
+
+/// The counters of a `LineColIterator`, see `LineColIterator::checkpoint`.
+#[derive(Clone, Copy)]
+pub struct Checkpoint {
+    line: usize,
+    col: usize,
+    start_of_line: usize,
+    continuation_bytes: usize,
+    surrogate_pairs: usize,
+    char_start: usize,
+    char_len: usize,
+    line_end: (usize, usize, usize),
 }
 
 impl<I> LineColIterator<I>
@@ -37,6 +69,11 @@ where
             line: 1,
             col: 0,
             start_of_line: 0,
+            continuation_bytes: 0,
+            surrogate_pairs: 0,
+            char_start: 0,
+            char_len: 0,
+            line_end: (0, 0, 0),
         }
     }
 
@@ -48,9 +85,67 @@ where
         self.col
     }
 
+    /// Like `col`, but counts characters instead of bytes.
+    pub fn char_col(&self) -> usize {
+        self.col - self.continuation_bytes
+    }
+
+    /// Like `col`, but counts UTF-16 code units instead of bytes.
+    pub fn utf16_col(&self) -> usize {
+        self.char_col() + self.surrogate_pairs
+    }
+
     pub fn byte_offset(&self) -> usize {
         self.start_of_line + self.col
     }
+
+    /// The position so far, to go back to with `rewind`.
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint {
+            line: self.line,
+            col: self.col,
+            start_of_line: self.start_of_line,
+            continuation_bytes: self.continuation_bytes,
+            surrogate_pairs: self.surrogate_pairs,
+            char_start: self.char_start,
+            char_len: self.char_len,
+            line_end: self.line_end,
+        }
+    }
+
+    /// Goes back to an earlier position. The underlying iterator has to be
+    /// rewound along with it.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.line = checkpoint.line;
+        self.col = checkpoint.col;
+        self.start_of_line = checkpoint.start_of_line;
+        self.continuation_bytes = checkpoint.continuation_bytes;
+        self.surrogate_pairs = checkpoint.surrogate_pairs;
+        self.char_start = checkpoint.char_start;
+        self.char_len = checkpoint.char_len;
+        self.line_end = checkpoint.line_end;
+    }
+
+    /// The underlying iterator.
+    pub fn get_mut(&mut self) -> &mut I {
+        &mut self.iter
+    }
+
+    /// Like `col`, `char_col` and `utf16_col` just before the most recent
+    /// newline character.
+    pub fn line_end(&self) -> (usize, usize, usize) {
+        self.line_end
+    }
+
+    /// Byte range of the character of the most recent byte, which may extend
+    /// past the bytes read so far.
+    pub fn char_range(&self) -> (usize, usize) {
+        if self.col == 0 {
+            (self.start_of_line, self.start_of_line)
+        } else {
+            (self.char_start, self.char_start + self.char_len)
+        }
+    }
 }
 
 impl<I> Iterator for LineColIterator<I>
@@ -63,16 +158,108 @@ where
         match self.iter.next() {
             None => None,
             Some(Ok(b'\n')) => {
+                self.line_end = (self.col, self.char_col(), self.utf16_col());
                 self.start_of_line += self.col + 1;
                 self.line += 1;
                 self.col = 0;
+                self.continuation_bytes = 0;
+                self.surrogate_pairs = 0;
                 Some(Ok(b'\n'))
             }
             Some(Ok(c)) => {
                 self.col += 1;
+                if c < 0x80 {
+                    self.char_start = self.start_of_line + self.col - 1;
+                    self.char_len = 1;
+                } else if c < 0xC0 {
+                    self.continuation_bytes += 1;
+                } else {
+                    self.char_start = self.start_of_line + self.col - 1;
+                    self.char_len = utf8_len(c);
+                    if c >= 0xF0 {
+                        self.surrogate_pairs += 1;
+                    }
+                }
                 Some(Ok(c))
             }
             Some(Err(e)) => Some(Err(e)),
         }
     }
 }
+
+/// Length in bytes of a UTF-8 encoded character starting with `first`.
+pub fn utf8_len(first: u8) -> usize {
+    match first {
+        0xF0..=0xFF => 4,
+        0xE0..=0xEF => 3,
+        0xC0..=0xDF => 2,
+        _ => 1,
+    }
+}
+
+/// An iterator over bytes that keeps the ones read after `mark`, so that they
+/// can be read again after `rewind`.
+pub struct Replay<I> {
+    iter: I,
+    /// The bytes read since the mark, or since the last rewind if the mark is
+    /// gone but some of them have not been read again yet.
+    buffer: Vec<u8>,
+    /// Index of the next byte to read from the buffer.
+    index: usize,
+    marked: bool,
+}
+
+impl<I> Replay<I> {
+    pub fn new(iter: I) -> Replay<I> {
+        Replay {
+            iter: iter,
+            buffer: Vec::new(),
+            index: 0,
+            marked: false,
+        }
+    }
+
+    pub fn mark(&mut self) {
+        self.buffer.drain(..self.index);
+        self.index = 0;
+        self.marked = true;
+    }
+
+    /// Goes back to the mark.
+    pub fn rewind(&mut self) {
+        self.index = 0;
+    }
+
+    /// Stops keeping the bytes that are read. The ones read since the mark can
+    /// still be read again if `rewind` was called.
+    pub fn unmark(&mut self) {
+        self.marked = false;
+    }
+}
+
+impl<I> Iterator for Replay<I>
+where
+    I: Iterator<Item = io::Result<u8>>,
+{
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<io::Result<u8>> {
+        if self.index < self.buffer.len() {
+            let ch = self.buffer[self.index];
+            self.index += 1;
+            return Some(Ok(ch));
+        }
+        if !self.marked {
+            self.buffer.clear();
+            self.index = 0;
+            return self.iter.next();
+        }
+
+        let next = self.iter.next();
+        if let Some(Ok(ch)) = next {
+            self.buffer.push(ch);
+            self.index += 1;
+        }
+        next
+    }
+}
@@ -347,18 +347,38 @@ extern crate itoa;
 extern crate dtoa;
 #[cfg(feature = "preserve_order")]
 extern crate linked_hash_map;
+#[cfg(feature = "unbounded_depth")]
+extern crate stacker;
+#[cfg(feature = "async")]
+extern crate futures_core;
+#[cfg(feature = "async")]
+extern crate futures_io;
 extern crate regex;
 #[macro_use]
 extern crate lazy_static;
 
 #[doc(inline)]
-pub use self::de::{Deserializer, StreamDeserializer, from_reader, from_slice, from_str};
+pub use self::de::{Deserializer, DuplicateKeys, Profile, Recovered, StreamDeserializer,
+                   from_reader, from_reader_recovering, from_reader_with,
+                   from_reader_with_ignored, from_reader_with_warnings, from_slice,
+                   from_slice_recovering, from_slice_with, from_slice_with_ignored,
+                   from_slice_with_warnings, from_str, from_str_recovering, from_str_with,
+                   from_str_with_ignored, from_str_with_warnings};
+#[cfg(feature = "async")]
+#[doc(inline)]
+pub use self::async_io::{AsyncStreamDeserializer, from_async_reader, to_async_writer};
 #[doc(inline)]
 pub use self::error::{Error, Result};
 #[doc(inline)]
-pub use self::ser::{Serializer, to_string, to_string_pretty, to_vec, to_vec_pretty, to_writer,
+pub use self::ser::{Serializer, to_string, to_string_json_pretty, to_string_pretty, to_vec,
+                    to_vec_json_pretty, to_vec_pretty, to_writer, to_writer_json_pretty,
                     to_writer_pretty};
 #[doc(inline)]
+pub use self::source_map::{SourceMap, from_reader_with_source_map, from_slice_with_source_map,
+                           from_str_with_source_map};
+#[doc(inline)]
+pub use self::spanned::Spanned;
+#[doc(inline)]
 pub use self::value::{Map, Number, Value, from_value, to_value};
 
 // We only use our own error type; no need for From conversions provided by the
@@ -377,12 +397,21 @@ macro_rules! try {
 #[macro_use]
 mod macros;
 
+#[cfg(feature = "async")]
+pub mod async_io;
 pub mod de;
+pub mod diagnostic;
+pub mod edit;
 pub mod error;
+pub mod events;
 pub mod map;
 pub mod ser;
+pub mod source_map;
+pub mod syntax;
 pub mod value;
+pub mod warning;
 
 mod iter;
 mod number;
 mod read;
+mod spanned;
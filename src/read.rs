@@ -7,9 +7,11 @@
 // except according to those terms.
 
 use std::{char, cmp, io, str};
+use std::cell::{Cell, RefCell};
 use std::ops::Deref;
+use std::rc::Rc;
 
-use iter::LineColIterator;
+use iter::{self, LineColIterator, Replay};
 
 use super::error::{Error, ErrorCode, Result};
 
@@ -21,26 +23,33 @@ fn is_whitespace(c: u8) -> bool {
 /// "specialized" for iterating over &[u8]. Once feature(specialization) is
 /// stable we can use actual specialization.
 ///
-/// This trait is sealed and cannot be implemented for types outside of
-/// `serde_json`.
-pub trait Read<'de>: private::Sealed {
-    #[doc(hidden)]
+/// Implement it to deserialize input that is not in one contiguous slice, like
+/// a rope or a list of buffers, without copying it first. Only the methods
+/// that read single bytes and report positions are required. The others parse
+/// strings with them into the scratch space, and can be overridden by input
+/// sources that know a faster way, or that can borrow strings from the input.
+pub trait Read<'de> {
+    /// Consumes the next byte of the input and returns it, or `None` at the
+    /// end of the input.
     fn next(&mut self) -> io::Result<Option<u8>>;
-    #[doc(hidden)]
+
+    /// Returns the next byte of the input without consuming it, or `None` at
+    /// the end of the input.
     fn peek(&mut self) -> io::Result<Option<u8>>;
 
     /// Only valid after a call to peek(). Discards the peeked byte.
-    #[doc(hidden)]
     fn discard(&mut self);
 
-    /// Position of the most recent call to next().
+    /// Position of the most recent call to next(), or of the byte most
+    /// recently discarded.
     ///
     /// The most recent call was probably next() and not peek(), but this method
     /// should try to return a sensible result if the most recent call was
     /// actually peek() because we don't always know.
     ///
-    /// Only called in case of an error, so performance is not important.
-    #[doc(hidden)]
+    /// Called for errors, spans and the indentation of strings in apostrophes,
+    /// mostly at increasing offsets, so it is worth counting on from the last
+    /// position rather than from the start of the input.
     fn position(&self) -> Position;
 
     /// Position of the most recent call to peek().
@@ -49,34 +58,69 @@ pub trait Read<'de>: private::Sealed {
     /// should try to return a sensible result if the most recent call was
     /// actually next() because we don't always know.
     ///
-    /// Only called in case of an error, so performance is not important.
-    #[doc(hidden)]
+    /// Called for errors, spans and the indentation of strings in apostrophes,
+    /// mostly at increasing offsets, so it is worth counting on from the last
+    /// position rather than from the start of the input.
     fn peek_position(&self) -> Position;
 
     /// Offset from the beginning of the input to the next byte that would be
     /// returned by next() or peek().
-    #[doc(hidden)]
     fn byte_offset(&self) -> usize;
 
+    /// Remembers the current position to go back to with rewind(). Returns
+    /// false if the input cannot be read again, which is the default.
+    ///
+    /// The deserializer uses it to read a document like `http://example.com`,
+    /// which starts like a root object without braces but is not one, as a
+    /// quoteless string instead. It rewinds only a little way, and calls
+    /// unmark() afterwards.
+    fn mark(&mut self) -> bool {
+        false
+    }
+
+    /// Goes back to the position remembered by mark(), which stays
+    /// remembered.
+    fn rewind(&mut self) {}
+
+    /// Forgets the position remembered by mark().
+    fn unmark(&mut self) {}
+
     /// Assumes the previous byte was a quotation mark. Parses a JSON-escaped
     /// string until the next quotation mark using the given scratch space if
     /// necessary. The scratch space is initially empty.
-    #[doc(hidden)]
-    fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>>;
+    fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        parse_double_str_bytes(self, scratch, true, as_str).map(Reference::Copied)
+    }
 
     /// Assumes the previous byte was an apostrophe. Parses a JSON-escaped
     /// string until the next quotation mark using the given scratch space if
     /// necessary. The scratch space is initially empty.
-    #[doc(hidden)]
-    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>>;
+    ///
+    /// The apostrophe was in column `indent_level` counted from 0. Up to that
+    /// much indentation is skipped on each line of a multiline string.
+    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>> {
+        parse_single_str_bytes(self, scratch, true, as_str, indent_level, |_| {})
+            .map(Reference::Copied)
+    }
+
+    /// Returns the position of the first tab that was skipped as indentation
+    /// of the most recent multiline string, if any.
+    ///
+    /// The default implementation of `parse_single_str` does not keep track of
+    /// it, so this returns `None` unless both are overridden.
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        None
+    }
 
     /// Parses a string until the next newline
-    #[doc(hidden)]
-    fn parse_none_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>>;
+    fn parse_none_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        parse_none_str_bytes(self, scratch, as_str).map(Reference::Copied)
+    }
 
     /// Parses a no-whitespace member name
-    #[doc(hidden)]
-    fn parse_member_name<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>>;
+    fn parse_member_name<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        parse_member_name_bytes(self, scratch, as_str).map(Reference::Copied)
+    }
 
     /// Assumes the previous byte was a quotation mark. Parses a JSON-escaped
     /// string until the next quotation mark using the given scratch space if
@@ -84,11 +128,12 @@ pub trait Read<'de>: private::Sealed {
     ///
     /// This function returns the raw bytes in the string with escape sequences
     /// expanded but without performing unicode validation.
-    #[doc(hidden)]
     fn parse_double_str_raw<'s>(
         &'s mut self,
         scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>>;
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        parse_double_str_bytes(self, scratch, false, |_, bytes| Ok(bytes)).map(Reference::Copied)
+    }
 
     /// Assumes the previous byte was an apostrophe. Parses a JSON-escaped
     /// string until the next quotation mark using the given scratch space if
@@ -96,59 +141,138 @@ pub trait Read<'de>: private::Sealed {
     ///
     /// This function returns the raw bytes in the string with escape sequences
     /// expanded but without performing unicode validation.
-    #[doc(hidden)]
     fn parse_single_str_raw<'s>(
         &'s mut self,
         scratch: &'s mut Vec<u8>,
         indent_level: usize,
-    ) -> Result<Reference<'de, 's, [u8]>>;
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        parse_single_str_bytes(self, scratch, false, |_, bytes| Ok(bytes), indent_level, |_| {})
+            .map(Reference::Copied)
+    }
 
     /// Parses a string until the next newline
     ///
     /// This function returns the raw bytes in the string with escape sequences
     /// expanded but without performing unicode validation.
-    #[doc(hidden)]
     fn parse_none_str_raw<'s>(
         &'s mut self,
         scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>>;
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        parse_none_str_bytes(self, scratch, |_, bytes| Ok(bytes)).map(Reference::Copied)
+    }
 
     /// Parses a no-whitespace member name
     ///
     /// This function returns the raw bytes in the string with escape sequences
     /// expanded but without performing unicode validation.
-    #[doc(hidden)]
     fn parse_member_name_raw<'s>(
         &'s mut self,
         scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>>;
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        parse_member_name_bytes(self, scratch, |_, bytes| Ok(bytes)).map(Reference::Copied)
+    }
 
     /// Assumes the previous byte was a quotation mark. Parses a JSON-escaped
     /// string until the next quotation mark but discards the data.
-    #[doc(hidden)]
-    fn ignore_double_str(&mut self) -> Result<()>;
+    fn ignore_double_str(&mut self) -> Result<()> {
+        debug!(ignore_double_str);
+        loop {
+            let ch = try!(peek_or_eof(self));
+            if !ESCAPE_DOUBLE[ch as usize] {
+                self.discard();
+                continue;
+            }
+            match ch {
+                b'"' => {
+                    self.discard();
+                    return Ok(());
+                }
+                b'\\' => {
+                    self.discard();
+                    try!(ignore_escape(self));
+                }
+                _ => {
+                    return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
+                }
+            }
+        }
+    }
 
     /// Assumes the previous byte was an apostrophe. Parses a JSON-escaped
     /// string until the next quotation mark but discards the data.
-    #[doc(hidden)]
-    fn ignore_single_str(&mut self) -> Result<()>;
+    fn ignore_single_str(&mut self) -> Result<()> {
+        debug!(ignore_single_str);
+        loop {
+            let ch = try!(peek_or_eof(self));
+            if !ESCAPE_SINGLE[ch as usize] {
+                self.discard();
+                continue;
+            }
+            match ch {
+                b'\'' => {
+                    self.discard();
+                    return Ok(());
+                }
+                b'\\' => {
+                    self.discard();
+                    try!(ignore_escape(self));
+                }
+                _ => {
+                    return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
+                }
+            }
+        }
+    }
 
-    /// Parses a string until the next newline
-    #[doc(hidden)]
-    fn ignore_none_str(&mut self) -> Result<()>;
+    /// Parses a string until the next newline or the end of the input
+    fn ignore_none_str(&mut self) -> Result<()> {
+        debug!(ignore_none_str);
+        loop {
+            match try!(self.peek().map_err(Error::io)) {
+                Some(b'\n') | Some(b'\r') | None => return Ok(()),
+                Some(_) => self.discard(),
+            }
+        }
+    }
 
     /// Parses a no-whitespace member name
-    #[doc(hidden)]
-    fn ignore_member_name(&mut self) -> Result<()>;
+    fn ignore_member_name(&mut self) -> Result<()> {
+        debug!(ignore_member_name);
+        loop {
+            let ch = try!(peek_or_eof(self));
+            if is_whitespace(ch) || ch == b':' {
+                return Ok(());
+            }
+            self.discard();
+        }
+    }
 }
 
+/// Where in the input an error is, as returned by the position methods of
+/// `Read`.
+#[derive(Clone, Copy)]
 pub struct Position {
+    /// Counted from 1.
     pub line: usize,
+    /// Counted in bytes.
     pub column: usize,
+    /// Counted in characters.
+    pub char_column: usize,
+    /// Counted in UTF-16 code units.
+    pub utf16_column: usize,
+    /// Byte offset of the start of the character at the column, or of the
+    /// line in column 0.
+    pub start: usize,
+    /// Byte offset of the end of the character at the column.
+    pub end: usize,
 }
 
+/// A string parsed by `Read`, either borrowed from the input for all of `'de`
+/// or copied into the scratch space.
 pub enum Reference<'b, 'c, T: ?Sized + 'static> {
+    /// Borrowed from the input.
     Borrowed(&'b T),
+    /// Copied into the scratch space.
     Copied(&'c T),
 }
 
@@ -168,9 +292,14 @@ pub struct IoRead<R>
 where
     R: io::Read,
 {
-    iter: LineColIterator<io::Bytes<R>>,
+    iter: LineColIterator<Replay<io::Bytes<R>>>,
     /// Temporary storage of peeked byte.
     ch: Option<u8>,
+    /// The peeked byte and the line and column at the mark, if any.
+    mark: Option<(Option<u8>, iter::Checkpoint)>,
+    /// Position of the first tab skipped as indentation of the most recent
+    /// multiline string.
+    indent_tab: Option<Position>,
 }
 
 /// JSON input source that reads from a slice of bytes.
@@ -181,6 +310,14 @@ pub struct SliceRead<'a> {
     slice: &'a [u8],
     /// Index of the *next* byte that will be returned by next() or peek().
     index: usize,
+    /// Index of the byte remembered by mark().
+    mark: Option<usize>,
+    /// Index of the first tab skipped as indentation of the most recent
+    /// multiline string.
+    indent_tab: Option<usize>,
+    /// The most recently computed position, from which positions further on
+    /// are counted so that reading them in order does not take quadratic time.
+    last_position: Cell<(usize, Position)>,
 }
 
 /// JSON input source that reads from a UTF-8 string.
@@ -190,10 +327,14 @@ pub struct StrRead<'a> {
     delegate: SliceRead<'a>,
 }
 
-// Prevent users from implementing the Read trait.
-mod private {
-    pub trait Sealed {}
-}
+const START_POSITION: Position = Position {
+    line: 1,
+    column: 0,
+    char_column: 0,
+    utf16_column: 0,
+    start: 0,
+    end: 0,
+};
 
 //////////////////////////////////////////////////////////////////////////////
 
@@ -204,243 +345,22 @@ where
     /// Create a JSON input source to read from a std::io input stream.
     pub fn new(reader: R) -> Self {
         IoRead {
-            iter: LineColIterator::new(reader.bytes()),
+            iter: LineColIterator::new(Replay::new(reader.bytes())),
             ch: None,
+            mark: None,
+            indent_tab: None,
         }
     }
 }
 
-impl<R> private::Sealed for IoRead<R>
-where
-    R: io::Read,
-{
-}
-
 impl<R> IoRead<R>
 where
     R: io::Read,
 {
-    fn parse_double_str_bytes<'s, T, F>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-        validate: bool,
-        result: F,
-    ) -> Result<T>
-    where
-        T: 's,
-        F: FnOnce(&'s Self, &'s [u8]) -> Result<T>,
-    {
-        debug!(parse_double_str_bytes);
-        loop {
-            let ch = try!(next_or_eof(self));
-            if !ESCAPE_DOUBLE[ch as usize] {
-                scratch.push(ch);
-                continue;
-            }
-            match ch {
-                b'"' => {
-                    return result(self, scratch);
-                }
-                b'\\' => {
-                    try!(parse_escape(self, scratch));
-                }
-                _ => {
-                    if validate {
-                        return error(self, ErrorCode::InvalidUnicodeCodePoint);
-                    }
-                    scratch.push(ch);
-                }
-            }
-        }
-    }
-
-    fn parse_single_str_bytes<'s, T, F>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-        validate: bool,
-        result: F,
-        indent_level: usize,
-    ) -> Result<T>
-    where
-        T: 's,
-        F: FnOnce(&'s Self, &'s [u8]) -> Result<T>,
-    {
-        debug!(parse_single_str_bytes);
-
-        let mut beginning = true;
-        let mut multiline = false;
-        let mut in_indent = false;
-        let mut indent_count = 0;
-        let mut was_newline = false;
-
-        loop {
-            let ch = try!(next_or_eof(self));
-            // Skip the indentations
-            if multiline && in_indent && indent_count < indent_level && ch == b' ' || ch == b'\t' {
-                indent_count += 1;
-                continue;
-            }
-
-            in_indent = false;
-
-            if !ESCAPE_SINGLE[ch as usize] {
-                beginning = false;
-                was_newline = false;
-                scratch.push(ch);
-                continue;
-            }
-
-            match ch {
-                b'\'' => {
-                    if beginning {
-                        // The first apostrophe has already been consumed - we only need to match two more
-                        if let Ok(Some(b'\'')) = self.peek() {
-                            multiline = true;
-
-                            // Consume char
-                            try!(next_or_eof(self));
-
-                            let mut newline = false;
-                            // Skip the beginning newline and whitespace if there is any
-                            loop {
-                                if indent_count >= indent_level {
-                                    break;
-                                }
-
-                                match try!(peek_or_eof(self)) {
-                                    b'\t' | b'\r' | b' ' => {
-                                        // Consume char
-                                        try!(next_or_eof(self));
-
-                                        if newline {
-                                            indent_count += 1;
-                                        }
-                                    }
-                                    b'\n' => {
-                                        if !newline {
-                                            newline = true;
-
-                                            // Consume char
-                                            try!(next_or_eof(self));
-                                        } else {
-                                            break;
-                                        }
-                                    }
-                                    _ => break,
-                                }
-                            }
-                            indent_count = 0;
-                        } else {
-                            // Scratch is empty at this point
-                            return result(self, scratch);
-                        }
-                    } else if multiline {
-                        if let Ok(Some(b'\'')) = self.peek() {
-                            // Consume char
-                            try!(next_or_eof(self));
-
-                            if let Ok(Some(b'\'')) = self.peek() {
-                                // Consume char
-                                try!(next_or_eof(self));
-
-                                // Take off the trailing newline if necessary
-                                if was_newline {
-                                    scratch.pop();
-                                }
-
-                                return result(self, scratch);
-                            } else {
-                                scratch.push(b'\'');
-                                scratch.push(b'\'');
-                            }
-                        } else {
-                            scratch.push(b'\'');
-                        }
-                    } else {
-                        return result(self, scratch);
-                    }
-                }
-
-                b'\\' => if !multiline {
-                    try!(parse_escape(self, scratch));
-                    was_newline = false;
-                } else {
-                    scratch.push(ch);
-                    was_newline = false;
-                }
-
-                b'\n' => if !multiline {
-                    return error(self, ErrorCode::UnexpectedNewline);
-                } else {
-                    scratch.push(ch);
-
-                    in_indent = true;
-                    was_newline = true;
-                    indent_count = 0;
-                }
-                _ => {
-                    if validate {
-                        return error(self, ErrorCode::InvalidUnicodeCodePoint);
-                    }
-                    scratch.push(ch);
-                    was_newline = false;
-                }
-            }
-
-            beginning = false;
-        }
-    }
-
-    fn parse_none_str_bytes<'s, T, F>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-        result: F,
-    ) -> Result<T>
-    where
-        T: 's,
-        F: FnOnce(&'s Self, &'s [u8]) -> Result<T>,
-    {
-        debug!(parse_none_str_bytes);
-        loop {
-            let ch = try!(self.peek().map_err(Error::io));
-            if ch != Some(b'\n') && ch != Some(b'\r') && ch != None {
-                scratch.push(try!(next_or_eof(self)));
-            } else {
-                let mut trailing_whitespace = 0;
-
-                for i in (0..scratch.len()).rev() {
-                    if is_whitespace(scratch[i]) {
-                        trailing_whitespace += 1;
-                    } else {
-                        break;
-                    }
-                }
-
-                let len = scratch.len();
-                scratch.truncate(len - trailing_whitespace);
-
-                return result(self, scratch);
-            }
-        }
-    }
-
-    fn parse_member_name_bytes<'s, T, F>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-        result: F,
-    ) -> Result<T>
-    where
-        T: 's,
-        F: FnOnce(&'s Self, &'s [u8]) -> Result<T>,
-    {
-        debug!(parse_member_name_bytes);
-        loop {
-            let ch = try!(peek_or_eof(self));
-            if !is_whitespace(ch) && ch != b':' {
-                scratch.push(try!(next_or_eof(self)));
-            } else {
-                return result(self, scratch);
-            }
+    /// Remembers the tab that was just consumed unless an earlier one was.
+    fn note_indent_tab(&mut self) {
+        if self.indent_tab.is_none() {
+            self.indent_tab = Some(self.position());
         }
     }
 }
@@ -489,13 +409,30 @@ where
         Position {
             line: self.iter.line(),
             column: self.iter.col(),
+            char_column: self.iter.char_col(),
+            utf16_column: self.iter.utf16_col(),
+            start: self.iter.char_range().0,
+            end: self.iter.char_range().1,
         }
     }
 
     fn peek_position(&self) -> Position {
         // The LineColIterator updates its position during peek() so it has the
-        // right one here.
-        self.position()
+        // right one here, except that a newline is counted at the start of the
+        // next line rather than at the end of its own.
+        if self.ch != Some(b'\n') {
+            return self.position();
+        }
+        let (column, char_column, utf16_column) = self.iter.line_end();
+        let start = self.iter.byte_offset() - 1;
+        Position {
+            line: self.iter.line() - 1,
+            column: column + 1,
+            char_column: char_column + 1,
+            utf16_column: utf16_column + 1,
+            start: start,
+            end: start + 1,
+        }
     }
 
     fn byte_offset(&self) -> usize {
@@ -505,31 +442,32 @@ where
         }
     }
 
-    fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
-        self.parse_double_str_bytes(scratch, true, as_str)
-            .map(Reference::Copied)
+    fn mark(&mut self) -> bool {
+        self.iter.get_mut().mark();
+        self.mark = Some((self.ch, self.iter.checkpoint()));
+        true
     }
 
-    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>> {
-        self.parse_single_str_bytes(scratch, true, as_str, indent_level)
-            .map(Reference::Copied)
+    fn rewind(&mut self) {
+        if let Some((ch, checkpoint)) = self.mark {
+            self.iter.get_mut().rewind();
+            self.iter.rewind(checkpoint);
+            self.ch = ch;
+        }
     }
 
-    fn parse_none_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
-        self.parse_none_str_bytes(scratch, as_str)
-            .map(Reference::Copied)
+    fn unmark(&mut self) {
+        self.iter.get_mut().unmark();
+        self.mark = None;
     }
 
-    fn parse_member_name<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
-        self.parse_member_name_bytes(scratch, as_str)
-            .map(Reference::Copied)
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        self.indent_tab.take()
     }
 
-    fn parse_double_str_raw<'s>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>> {
-        self.parse_double_str_bytes(scratch, false, |_, bytes| Ok(bytes))
+    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>> {
+        self.indent_tab = None;
+        parse_single_str_bytes(self, scratch, true, as_str, indent_level, Self::note_indent_tab)
             .map(Reference::Copied)
     }
 
@@ -538,94 +476,13 @@ where
         scratch: &'s mut Vec<u8>,
         indent_level: usize,
     ) -> Result<Reference<'de, 's, [u8]>> {
-        self.parse_single_str_bytes(scratch, false, |_, bytes| Ok(bytes), indent_level)
+        self.indent_tab = None;
+        parse_single_str_bytes(self, scratch, false, |_, bytes| Ok(bytes), indent_level, Self::note_indent_tab)
             .map(Reference::Copied)
     }
+}
 
-    fn parse_none_str_raw<'s>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>> {
-        self.parse_none_str_bytes(scratch, |_, bytes| Ok(bytes))
-            .map(Reference::Copied)
-    }
-
-    fn parse_member_name_raw<'s>(
-        &'s mut self,
-        scratch: &'s mut Vec<u8>,
-    ) -> Result<Reference<'de, 's, [u8]>> {
-        self.parse_member_name_bytes(scratch, |_, bytes| Ok(bytes))
-            .map(Reference::Copied)
-    }
-
-    fn ignore_double_str(&mut self) -> Result<()> {
-        debug!(ignore_double_str);
-        loop {
-            let ch = try!(next_or_eof(self));
-            if !ESCAPE_DOUBLE[ch as usize] {
-                continue;
-            }
-            match ch {
-                b'"' => {
-                    return Ok(());
-                }
-                b'\\' => {
-                    try!(ignore_escape(self));
-                }
-                _ => {
-                    return error(self, ErrorCode::InvalidUnicodeCodePoint);
-                }
-            }
-        }
-    }
-
-    fn ignore_single_str(&mut self) -> Result<()> {
-        debug!(ignore_single_str);
-        loop {
-            let ch = try!(next_or_eof(self));
-            if !ESCAPE_SINGLE[ch as usize] {
-                continue;
-            }
-            match ch {
-                b'\'' => {
-                    return Ok(());
-                }
-                b'\\' => {
-                    try!(ignore_escape(self));
-                }
-                _ => {
-                    return error(self, ErrorCode::InvalidUnicodeCodePoint);
-                }
-            }
-        }
-    }
-
-    fn ignore_none_str(&mut self) -> Result<()> {
-        debug!(ignore_none_str);
-        loop {
-            let ch = try!(next_or_eof(self));
-            if is_whitespace(ch) {
-                break;
-            }
-        }
-
-        Ok(())
-    }
-
-    fn ignore_member_name(&mut self) -> Result<()> {
-        debug!(ignore_member_name);
-        loop {
-            let ch = try!(next_or_eof(self));
-            if ch == b'\n' || ch == b'\r' {
-                break;
-            }
-        }
-
-        Ok(())
-    }
-}
-
-//////////////////////////////////////////////////////////////////////////////
+//////////////////////////////////////////////////////////////////////////////
 
 impl<'a> SliceRead<'a> {
     /// Create a JSON input source to read from a slice of bytes.
@@ -633,22 +490,49 @@ impl<'a> SliceRead<'a> {
         SliceRead {
             slice: slice,
             index: 0,
+            mark: None,
+            indent_tab: None,
+            last_position: Cell::new((0, START_POSITION)),
         }
     }
 
     fn position_of_index(&self, i: usize) -> Position {
-        let mut pos = Position { line: 1, column: 0 };
-        for ch in &self.slice[..i] {
-            match *ch {
+        let (from, mut pos) = match self.last_position.get() {
+            (from, pos) if from <= i => (from, pos),
+            _ => (0, START_POSITION),
+        };
+        for &ch in &self.slice[from..i] {
+            match ch {
                 b'\n' => {
                     pos.line += 1;
                     pos.column = 0;
+                    pos.char_column = 0;
+                    pos.utf16_column = 0;
                 }
                 _ => {
                     pos.column += 1;
+                    // Skip UTF-8 continuation bytes, and count characters
+                    // outside the Basic Multilingual Plane twice for UTF-16.
+                    if ch & 0xC0 != 0x80 {
+                        pos.char_column += 1;
+                        pos.utf16_column += if ch >= 0xF0 { 2 } else { 1 };
+                    }
                 }
             }
         }
+        self.last_position.set((i, pos));
+
+        if pos.column == 0 {
+            pos.start = i;
+            pos.end = i;
+        } else {
+            pos.start = i - 1;
+            while pos.start > 0 && self.slice[pos.start] & 0xC0 == 0x80 {
+                pos.start -= 1;
+            }
+            let len = iter::utf8_len(self.slice[pos.start]);
+            pos.end = cmp::min(pos.start + len, self.slice.len());
+        }
         pos
     }
 
@@ -700,7 +584,7 @@ impl<'a> SliceRead<'a> {
                 }
                 _ => {
                     if validate {
-                        return error(self, ErrorCode::InvalidUnicodeCodePoint);
+                        return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
                     }
                     self.index += 1;
                 }
@@ -731,17 +615,22 @@ impl<'a> SliceRead<'a> {
         let mut in_indent = false;
         let mut indent_count = 0;
         let mut was_newline = 0;
+        self.indent_tab = None;
 
         loop {
             // Skip the indentations
             if multiline && in_indent {
                 while
-                    indent_count + 1 < indent_level &&
+                    indent_count < indent_level &&
+                    self.index < self.slice.len() &&
                     (
                         self.slice[self.index] == b' ' ||
                         self.slice[self.index] == b'\t'
                     )
                 {
+                    if self.slice[self.index] == b'\t' && self.indent_tab.is_none() {
+                        self.indent_tab = Some(self.index);
+                    }
                     self.index += 1;
                     start += 1;
                     indent_count += 1;
@@ -773,12 +662,17 @@ impl<'a> SliceRead<'a> {
                         let mut newline = false;
                         // Skip the beginning newline and whitespace if there is any
                         loop {
-                            if indent_count >= indent_level {
+                            if indent_count > indent_level {
                                 break;
                             }
 
-                            match self.slice[self.index] {
+                            match self.slice.get(self.index).cloned().unwrap_or(b'\x00') {
                                 b'\t' | b'\r' | b' ' => {
+                                    if newline && self.slice[self.index] == b'\t' &&
+                                        self.indent_tab.is_none()
+                                    {
+                                        self.indent_tab = Some(self.index);
+                                    }
                                     self.index += 1;
                                     start += 1;
 
@@ -846,7 +740,7 @@ impl<'a> SliceRead<'a> {
                 }
 
                 b'\n' => if !multiline {
-                    return error(self, ErrorCode::UnexpectedNewline);
+                    return peek_error(self, ErrorCode::UnexpectedNewline);
                 } else {
                     scratch.extend_from_slice(&self.slice[start..self.index + 1]);
                     self.index += 1;
@@ -857,7 +751,7 @@ impl<'a> SliceRead<'a> {
 
                 _ => {
                     if validate {
-                        return error(self, ErrorCode::InvalidUnicodeCodePoint);
+                        return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
                     }
                     self.index += 1;
                     was_newline = 0;
@@ -943,8 +837,6 @@ impl<'a> SliceRead<'a> {
     }
 }
 
-impl<'a> private::Sealed for SliceRead<'a> {}
-
 impl<'a> Read<'a> for SliceRead<'a> {
     #[inline]
     fn next(&mut self) -> io::Result<Option<u8>> {
@@ -984,6 +876,16 @@ impl<'a> Read<'a> for SliceRead<'a> {
     }
 
     fn peek_position(&self) -> Position {
+        if self.slice.get(self.index) == Some(&b'\n') {
+            // At the end of its own line rather than the start of the next.
+            let mut pos = self.position_of_index(self.index);
+            pos.column += 1;
+            pos.char_column += 1;
+            pos.utf16_column += 1;
+            pos.start = self.index;
+            pos.end = self.index + 1;
+            return pos;
+        }
         // Cap it at slice.len() just in case the most recent call was next()
         // and it returned the last byte.
         self.position_of_index(cmp::min(self.slice.len(), self.index + 1))
@@ -993,6 +895,25 @@ impl<'a> Read<'a> for SliceRead<'a> {
         self.index
     }
 
+    fn mark(&mut self) -> bool {
+        self.mark = Some(self.index);
+        true
+    }
+
+    fn rewind(&mut self) {
+        if let Some(index) = self.mark {
+            self.index = index;
+        }
+    }
+
+    fn unmark(&mut self) {
+        self.mark = None;
+    }
+
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        self.indent_tab.take().map(|i| self.position_of_index(i + 1))
+    }
+
     fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'a, 's, str>> {
         self.parse_double_str_bytes(scratch, true, as_str)
     }
@@ -1057,7 +978,7 @@ impl<'a> Read<'a> for SliceRead<'a> {
                     try!(ignore_escape(self));
                 }
                 _ => {
-                    return error(self, ErrorCode::InvalidUnicodeCodePoint);
+                    return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
                 }
             }
         }
@@ -1082,7 +1003,7 @@ impl<'a> Read<'a> for SliceRead<'a> {
                     try!(ignore_escape(self));
                 }
                 _ => {
-                    return error(self, ErrorCode::InvalidUnicodeCodePoint);
+                    return peek_error(self, ErrorCode::InvalidUnicodeCodePoint);
                 }
             }
         }
@@ -1097,10 +1018,6 @@ impl<'a> Read<'a> for SliceRead<'a> {
         {
             self.index += 1;
         }
-        if self.index == self.slice.len() {
-            return error(self, ErrorCode::EofWhileParsingString);
-        }
-
         Ok(())
     }
 
@@ -1130,8 +1047,6 @@ impl<'a> StrRead<'a> {
     }
 }
 
-impl<'a> private::Sealed for StrRead<'a> {}
-
 impl<'a> Read<'a> for StrRead<'a> {
     #[inline]
     fn next(&mut self) -> io::Result<Option<u8>> {
@@ -1160,6 +1075,22 @@ impl<'a> Read<'a> for StrRead<'a> {
         self.delegate.byte_offset()
     }
 
+    fn mark(&mut self) -> bool {
+        self.delegate.mark()
+    }
+
+    fn rewind(&mut self) {
+        self.delegate.rewind();
+    }
+
+    fn unmark(&mut self) {
+        self.delegate.unmark();
+    }
+
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        self.delegate.take_indent_tab()
+    }
+
     fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'a, 's, str>> {
         self.delegate
             .parse_double_str_bytes(
@@ -1252,6 +1183,345 @@ impl<'a> Read<'a> for StrRead<'a> {
 
 //////////////////////////////////////////////////////////////////////////////
 
+impl<'a, 'de, R: ?Sized + Read<'de>> Read<'de> for &'a mut R {
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<u8>> {
+        R::next(self)
+    }
+
+    #[inline]
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        R::peek(self)
+    }
+
+    #[inline]
+    fn discard(&mut self) {
+        R::discard(self);
+    }
+
+    fn position(&self) -> Position {
+        R::position(self)
+    }
+
+    fn peek_position(&self) -> Position {
+        R::peek_position(self)
+    }
+
+    fn byte_offset(&self) -> usize {
+        R::byte_offset(self)
+    }
+
+    fn mark(&mut self) -> bool {
+        R::mark(self)
+    }
+
+    fn rewind(&mut self) {
+        R::rewind(self);
+    }
+
+    fn unmark(&mut self) {
+        R::unmark(self);
+    }
+
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        R::take_indent_tab(self)
+    }
+
+    fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        R::parse_double_str(self, scratch)
+    }
+
+    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>> {
+        R::parse_single_str(self, scratch, indent_level)
+    }
+
+    fn parse_none_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        R::parse_none_str(self, scratch)
+    }
+
+    fn parse_member_name<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        R::parse_member_name(self, scratch)
+    }
+
+    fn parse_double_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        R::parse_double_str_raw(self, scratch)
+    }
+
+    fn parse_single_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        indent_level: usize,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        R::parse_single_str_raw(self, scratch, indent_level)
+    }
+
+    fn parse_none_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        R::parse_none_str_raw(self, scratch)
+    }
+
+    fn parse_member_name_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        R::parse_member_name_raw(self, scratch)
+    }
+
+    fn ignore_double_str(&mut self) -> Result<()> {
+        R::ignore_double_str(self)
+    }
+
+    fn ignore_single_str(&mut self) -> Result<()> {
+        R::ignore_single_str(self)
+    }
+
+    fn ignore_none_str(&mut self) -> Result<()> {
+        R::ignore_none_str(self)
+    }
+
+    fn ignore_member_name(&mut self) -> Result<()> {
+        R::ignore_member_name(self)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// Input source that is fed the input a chunk at a time, for the push parser
+/// in `events` and for reading from an `AsyncRead`.
+//
+// Reading past the bytes fed so far looks like the end of the input, but marks
+// the reader as starved so that the parser can rewind to a checkpoint and try
+// again once more input has arrived.
+pub struct ChunkRead {
+    delegate: IoRead<Chunks>,
+    chunks: Rc<RefCell<Pending>>,
+    /// Where the reader was at the call to mark(), if it is remembered.
+    mark: Option<Checkpoint>,
+}
+
+/// The bytes fed to a `ChunkRead`, shared with the `io::Read` it reads from.
+struct Chunks(Rc<RefCell<Pending>>);
+
+struct Pending {
+    /// The input from the last checkpoint on, as far as it has been fed.
+    bytes: Vec<u8>,
+    /// Index of the next byte to read.
+    index: usize,
+    /// Whether all of the input has been fed.
+    finished: bool,
+    /// Whether a read has run out of the input fed so far.
+    starved: bool,
+}
+
+/// Where a `ChunkRead` was, to go back to with `ChunkRead::rewind`.
+#[derive(Clone, Copy)]
+pub struct Checkpoint {
+    index: usize,
+    ch: Option<u8>,
+    lines: iter::Checkpoint,
+}
+
+impl io::Read for Chunks {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let mut pending = self.0.borrow_mut();
+        let index = pending.index;
+        let len = cmp::min(buf.len(), pending.bytes.len() - index);
+        if len == 0 && !buf.is_empty() && !pending.finished {
+            pending.starved = true;
+        }
+        buf[..len].copy_from_slice(&pending.bytes[index..index + len]);
+        pending.index += len;
+        Ok(len)
+    }
+}
+
+// Both handles to the fed bytes are owned by the `ChunkRead`, one of them
+// through its delegate, so they always move to another thread together.
+unsafe impl Send for ChunkRead {}
+
+impl ChunkRead {
+    pub fn new() -> Self {
+        let chunks = Rc::new(RefCell::new(Pending {
+            bytes: Vec::new(),
+            index: 0,
+            finished: false,
+            starved: false,
+        }));
+        ChunkRead {
+            delegate: IoRead::new(Chunks(chunks.clone())),
+            chunks: chunks,
+            mark: None,
+        }
+    }
+
+    /// Appends the next chunk of the input. Only the bytes from the last
+    /// checkpoint on are kept.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        let mut pending = self.chunks.borrow_mut();
+        let index = pending.index;
+        pending.bytes.drain(..index);
+        pending.index = 0;
+        pending.bytes.extend_from_slice(bytes);
+    }
+
+    /// Marks the end of the input.
+    pub fn finish(&mut self) {
+        self.chunks.borrow_mut().finished = true;
+    }
+
+    pub fn checkpoint(&mut self) -> Checkpoint {
+        let mut pending = self.chunks.borrow_mut();
+        pending.starved = false;
+        Checkpoint {
+            index: pending.index,
+            ch: self.delegate.ch,
+            lines: self.delegate.iter.checkpoint(),
+        }
+    }
+
+    /// Goes back to the checkpoint, which has to be the last one.
+    pub fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.chunks.borrow_mut().index = checkpoint.index;
+        self.delegate.ch = checkpoint.ch;
+        self.delegate.iter.rewind(checkpoint.lines);
+    }
+
+    /// Whether a read since the last checkpoint has run out of input.
+    pub fn starved(&self) -> bool {
+        self.chunks.borrow().starved
+    }
+
+    /// The input that has been fed but not read yet, including a peeked byte.
+    pub fn unread(&self) -> Vec<u8> {
+        let pending = self.chunks.borrow();
+        let mut unread: Vec<u8> = self.delegate.ch.into_iter().collect();
+        unread.extend_from_slice(&pending.bytes[pending.index..]);
+        unread
+    }
+}
+
+impl<'de> Read<'de> for ChunkRead {
+    #[inline]
+    fn next(&mut self) -> io::Result<Option<u8>> {
+        self.delegate.next()
+    }
+
+    #[inline]
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        self.delegate.peek()
+    }
+
+    #[inline]
+    fn discard(&mut self) {
+        self.delegate.discard();
+    }
+
+    fn position(&self) -> Position {
+        self.delegate.position()
+    }
+
+    fn peek_position(&self) -> Position {
+        self.delegate.peek_position()
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.delegate.byte_offset()
+    }
+
+    fn mark(&mut self) -> bool {
+        // Unlike a checkpoint, the mark must not hide that the reader was
+        // starved before it.
+        self.mark = Some(Checkpoint {
+            index: self.chunks.borrow().index,
+            ch: self.delegate.ch,
+            lines: self.delegate.iter.checkpoint(),
+        });
+        true
+    }
+
+    fn rewind(&mut self) {
+        if let Some(checkpoint) = self.mark {
+            ChunkRead::rewind(self, checkpoint);
+        }
+    }
+
+    fn unmark(&mut self) {
+        self.mark = None;
+    }
+
+    fn take_indent_tab(&mut self) -> Option<Position> {
+        self.delegate.take_indent_tab()
+    }
+
+    fn parse_double_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        self.delegate.parse_double_str(scratch)
+    }
+
+    fn parse_single_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>, indent_level: usize) -> Result<Reference<'de, 's, str>> {
+        self.delegate.parse_single_str(scratch, indent_level)
+    }
+
+    fn parse_none_str<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        self.delegate.parse_none_str(scratch)
+    }
+
+    fn parse_member_name<'s>(&'s mut self, scratch: &'s mut Vec<u8>) -> Result<Reference<'de, 's, str>> {
+        self.delegate.parse_member_name(scratch)
+    }
+
+    fn parse_double_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        self.delegate.parse_double_str_raw(scratch)
+    }
+
+    fn parse_single_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+        indent_level: usize,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        self.delegate.parse_single_str_raw(scratch, indent_level)
+    }
+
+    fn parse_none_str_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        self.delegate.parse_none_str_raw(scratch)
+    }
+
+    fn parse_member_name_raw<'s>(
+        &'s mut self,
+        scratch: &'s mut Vec<u8>,
+    ) -> Result<Reference<'de, 's, [u8]>> {
+        self.delegate.parse_member_name_raw(scratch)
+    }
+
+    fn ignore_double_str(&mut self) -> Result<()> {
+        self.delegate.ignore_double_str()
+    }
+
+    fn ignore_single_str(&mut self) -> Result<()> {
+        self.delegate.ignore_single_str()
+    }
+
+    fn ignore_none_str(&mut self) -> Result<()> {
+        self.delegate.ignore_none_str()
+    }
+
+    fn ignore_member_name(&mut self) -> Result<()> {
+        self.delegate.ignore_member_name()
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
 const CT: bool = true; // control character \x00...\x1F
 const QU: bool = true; // quote \x22
 const AP: bool = true; // apostrophe \x22
@@ -1319,16 +1589,268 @@ fn peek_or_eof<'de, R: ?Sized + Read<'de>>(read: &mut R) -> Result<u8> {
 
 fn error<'de, R: ?Sized + Read<'de>, T>(read: &R, reason: ErrorCode) -> Result<T> {
     let pos = read.position();
-    Err(Error::syntax(reason, pos.line, pos.column))
+    Err(Error::syntax_at(reason, pos, pos.start..pos.end))
+}
+
+fn peek_error<'de, R: ?Sized + Read<'de>, T>(read: &R, reason: ErrorCode) -> Result<T> {
+    let pos = read.peek_position();
+    Err(Error::syntax_at(reason, pos, pos.start..pos.end))
 }
 
-fn as_str<'de, 's, R: Read<'de>>(read: &R, slice: &'s [u8]) -> Result<&'s str> {
+fn as_str<'de, 's, R: ?Sized + Read<'de>>(read: &R, slice: &'s [u8]) -> Result<&'s str> {
     str::from_utf8(slice).or_else(|_| error(read, ErrorCode::InvalidUnicodeCodePoint))
 }
 
+fn parse_double_str_bytes<'de, 's, R, T, F>(
+    read: &'s mut R,
+    scratch: &'s mut Vec<u8>,
+    validate: bool,
+    result: F,
+) -> Result<T>
+where
+    R: ?Sized + Read<'de>,
+    T: 's,
+    F: FnOnce(&'s R, &'s [u8]) -> Result<T>,
+{
+    debug!(parse_double_str_bytes);
+    loop {
+        let ch = try!(peek_or_eof(read));
+        if validate && ch == b'\n' {
+            // Leave the newline for a recovering parser to resume at.
+            return peek_error(read, ErrorCode::InvalidUnicodeCodePoint);
+        }
+        read.discard();
+        if !ESCAPE_DOUBLE[ch as usize] {
+            scratch.push(ch);
+            continue;
+        }
+        match ch {
+            b'"' => {
+                return result(read, scratch);
+            }
+            b'\\' => {
+                try!(parse_escape(read, scratch));
+            }
+            _ => {
+                if validate {
+                    return error(read, ErrorCode::InvalidUnicodeCodePoint);
+                }
+                scratch.push(ch);
+            }
+        }
+    }
+}
+
+/// Calls `note_indent_tab` after consuming each tab that is skipped as
+/// indentation of a multiline string.
+fn parse_single_str_bytes<'de, 's, R, T, F, N>(
+    read: &'s mut R,
+    scratch: &'s mut Vec<u8>,
+    validate: bool,
+    result: F,
+    indent_level: usize,
+    mut note_indent_tab: N,
+) -> Result<T>
+where
+    R: ?Sized + Read<'de>,
+    T: 's,
+    F: FnOnce(&'s R, &'s [u8]) -> Result<T>,
+    N: FnMut(&mut R),
+{
+    debug!(parse_single_str_bytes);
+
+    let mut beginning = true;
+    let mut multiline = false;
+    let mut in_indent = false;
+    let mut indent_count = 0;
+    let mut was_newline = false;
+
+    loop {
+        let ch = try!(peek_or_eof(read));
+        if ch == b'\n' && !multiline {
+            // Leave the newline for a recovering parser to resume at.
+            return peek_error(read, ErrorCode::UnexpectedNewline);
+        }
+        read.discard();
+
+        // Skip the indentations
+        if multiline && in_indent && indent_count < indent_level && ch == b' ' || ch == b'\t' {
+            if multiline && in_indent && ch == b'\t' {
+                note_indent_tab(read);
+            }
+            indent_count += 1;
+            continue;
+        }
+
+        in_indent = false;
+
+        if !ESCAPE_SINGLE[ch as usize] {
+            beginning = false;
+            was_newline = false;
+            scratch.push(ch);
+            continue;
+        }
+
+        match ch {
+            b'\'' => {
+                if beginning {
+                    // The first apostrophe has already been consumed - we only need to match two more
+                    if let Ok(Some(b'\'')) = read.peek() {
+                        multiline = true;
+
+                        // Consume char
+                        try!(next_or_eof(read));
+
+                        let mut newline = false;
+                        // Skip the beginning newline and whitespace if there is any
+                        loop {
+                            if indent_count >= indent_level {
+                                break;
+                            }
+
+                            match try!(peek_or_eof(read)) {
+                                b'\t' | b'\r' | b' ' => {
+                                    // Consume char
+                                    let ch = try!(next_or_eof(read));
+
+                                    if newline {
+                                        if ch == b'\t' {
+                                            note_indent_tab(read);
+                                        }
+                                        indent_count += 1;
+                                    }
+                                }
+                                b'\n' => {
+                                    if !newline {
+                                        newline = true;
+
+                                        // Consume char
+                                        try!(next_or_eof(read));
+                                    } else {
+                                        break;
+                                    }
+                                }
+                                _ => break,
+                            }
+                        }
+                        indent_count = 0;
+                    } else {
+                        // Scratch is empty at this point
+                        return result(read, scratch);
+                    }
+                } else if multiline {
+                    if let Ok(Some(b'\'')) = read.peek() {
+                        // Consume char
+                        try!(next_or_eof(read));
+
+                        if let Ok(Some(b'\'')) = read.peek() {
+                            // Consume char
+                            try!(next_or_eof(read));
+
+                            // Take off the trailing newline if necessary
+                            if was_newline {
+                                scratch.pop();
+                            }
+
+                            return result(read, scratch);
+                        } else {
+                            scratch.push(b'\'');
+                            scratch.push(b'\'');
+                        }
+                    } else {
+                        scratch.push(b'\'');
+                    }
+                } else {
+                    return result(read, scratch);
+                }
+            }
+
+            b'\\' => if !multiline {
+                try!(parse_escape(read, scratch));
+                was_newline = false;
+            } else {
+                scratch.push(ch);
+                was_newline = false;
+            }
+
+            b'\n' => {
+                scratch.push(ch);
+
+                in_indent = true;
+                was_newline = true;
+                indent_count = 0;
+            }
+            _ => {
+                if validate {
+                    return error(read, ErrorCode::InvalidUnicodeCodePoint);
+                }
+                scratch.push(ch);
+                was_newline = false;
+            }
+        }
+
+        beginning = false;
+    }
+}
+
+fn parse_none_str_bytes<'de, 's, R, T, F>(
+    read: &'s mut R,
+    scratch: &'s mut Vec<u8>,
+    result: F,
+) -> Result<T>
+where
+    R: ?Sized + Read<'de>,
+    T: 's,
+    F: FnOnce(&'s R, &'s [u8]) -> Result<T>,
+{
+    debug!(parse_none_str_bytes);
+    loop {
+        let ch = try!(read.peek().map_err(Error::io));
+        if ch != Some(b'\n') && ch != Some(b'\r') && ch != None {
+            scratch.push(try!(next_or_eof(read)));
+        } else {
+            let mut trailing_whitespace = 0;
+
+            for i in (0..scratch.len()).rev() {
+                if is_whitespace(scratch[i]) {
+                    trailing_whitespace += 1;
+                } else {
+                    break;
+                }
+            }
+
+            let len = scratch.len();
+            scratch.truncate(len - trailing_whitespace);
+
+            return result(read, scratch);
+        }
+    }
+}
+
+fn parse_member_name_bytes<'de, 's, R, T, F>(
+    read: &'s mut R,
+    scratch: &'s mut Vec<u8>,
+    result: F,
+) -> Result<T>
+where
+    R: ?Sized + Read<'de>,
+    T: 's,
+    F: FnOnce(&'s R, &'s [u8]) -> Result<T>,
+{
+    debug!(parse_member_name_bytes);
+    loop {
+        let ch = try!(peek_or_eof(read));
+        if !is_whitespace(ch) && ch != b':' {
+            scratch.push(try!(next_or_eof(read)));
+        } else {
+            return result(read, scratch);
+        }
+    }
+}
+
 /// Parses a JSON escape sequence and appends it into the scratch space. Assumes
 /// the previous byte read was a backslash.
-fn parse_escape<'de, R: Read<'de>>(read: &mut R, scratch: &mut Vec<u8>) -> Result<()> {
+fn parse_escape<'de, R: ?Sized + Read<'de>>(read: &mut R, scratch: &mut Vec<u8>) -> Result<()> {
     debug!(parse_escape);
     let ch = try!(next_or_eof(read));
 
@@ -65,6 +65,17 @@ where
     }
 }
 
+impl<'a, W> Serializer<W, PrettyJsonFormatter<'a>>
+where
+    W: io::Write,
+{
+    /// Creates a new serializer that pretty prints strictly valid JSON.
+    #[inline]
+    pub fn pretty_json(writer: W) -> Self {
+        Serializer::with_formatter(writer, PrettyJsonFormatter::new())
+    }
+}
+
 impl<W, F> Serializer<W, F>
 where
     W: io::Write,
@@ -1618,6 +1629,56 @@ pub struct CompactFormatter;
 
 impl Formatter for CompactFormatter {}
 
+/// Which strings the `PrettyFormatter` always wraps in double quotes, on top of
+/// the ones that cannot be written any other way.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Quotes {
+    /// Only quote keys and strings that would not round-trip without quotes.
+    Minimal,
+    /// Quote every object key.
+    Keys,
+    /// Quote every string value. This is what hjson-js calls `"strings"`, or
+    /// `"always"` in older versions.
+    Strings,
+    /// Quote every object key and every string value.
+    All,
+}
+
+impl Quotes {
+    fn keys(self) -> bool {
+        self == Quotes::Keys || self == Quotes::All
+    }
+
+    fn strings(self) -> bool {
+        self == Quotes::Strings || self == Quotes::All
+    }
+}
+
+impl Default for Quotes {
+    fn default() -> Self {
+        Quotes::Minimal
+    }
+}
+
+/// When the `PrettyFormatter` writes strings that need escaping as `'''`
+/// multiline strings instead of escaped double quoted strings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Multiline {
+    /// Use `'''` whenever the string can be written that way.
+    Std,
+    /// Like `Std`, but strings containing tabs are escaped instead, so that
+    /// editors cannot silently change them by re-indenting the block.
+    NoTabs,
+    /// Never use `'''`.
+    Off,
+}
+
+impl Default for Multiline {
+    fn default() -> Self {
+        Multiline::Std
+    }
+}
+
 /// This structure pretty prints a JSON value to make it human readable.
 #[derive(Clone, Debug)]
 pub struct PrettyFormatter<'a> {
@@ -1626,6 +1687,12 @@ pub struct PrettyFormatter<'a> {
     in_object: bool,
     indent: &'a [u8],
     next_bracket: Option<u8>,
+    root_braces: bool,
+    braceless_root: bool,
+    quotes: Quotes,
+    quoted_multiline: bool,
+    separator: bool,
+    multiline: Multiline,
 }
 
 impl<'a> PrettyFormatter<'a> {
@@ -1642,9 +1709,64 @@ impl<'a> PrettyFormatter<'a> {
             in_object: false,
             indent: indent,
             next_bracket: None,
+            root_braces: true,
+            braceless_root: false,
+            quotes: Quotes::Minimal,
+            quoted_multiline: false,
+            separator: false,
+            multiline: Multiline::Std,
         }
     }
 
+    /// Sets which keys and strings are always written with double quotes.
+    ///
+    /// Defaults to `Quotes::Minimal`.
+    pub fn quotes(mut self, quotes: Quotes) -> Self {
+        self.quotes = quotes;
+        self
+    }
+
+    /// Sets whether strings that need escaping are still written as `'''`
+    /// multiline strings when `quotes` requires string values to be quoted.
+    /// If not, they are written as escaped double quoted strings, which any
+    /// JSON parser accepts.
+    ///
+    /// Defaults to `false`.
+    pub fn quoted_multiline(mut self, quoted_multiline: bool) -> Self {
+        self.quoted_multiline = quoted_multiline;
+        self
+    }
+
+    /// Sets whether a `,` is written after every member and array element but
+    /// the last. A quoteless string would swallow the comma, so strings that
+    /// would otherwise be written without quotes get double quotes instead.
+    ///
+    /// Defaults to `false`.
+    pub fn separator(mut self, separator: bool) -> Self {
+        self.separator = separator;
+        self
+    }
+
+    /// Sets when strings that need escaping are written as `'''` multiline
+    /// strings.
+    ///
+    /// Defaults to `Multiline::Std`.
+    pub fn multiline(mut self, multiline: Multiline) -> Self {
+        self.multiline = multiline;
+        self
+    }
+
+    /// Sets whether braces are written around the root object. Without them
+    /// the members of the root object are written as unindented `key: value`
+    /// lines, which is how hand-written Hjson config files usually look. An
+    /// empty root object is still written as `{}`.
+    ///
+    /// Defaults to `true`.
+    pub fn root_braces(mut self, root_braces: bool) -> Self {
+        self.root_braces = root_braces;
+        self
+    }
+
     fn try_write_bracket<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
     where
         W: io::Write,
@@ -1656,10 +1778,16 @@ impl<'a> PrettyFormatter<'a> {
                 self.in_object = false;
             }
 
-            self.current_indent += 1;
             self.has_value = false;
             self.next_bracket = None;
 
+            if bracket == b'{' && self.current_indent == 0 && !self.root_braces && !self.braceless_root {
+                self.braceless_root = true;
+                return Ok(());
+            }
+
+            self.current_indent += 1;
+
             try!(writer.write_all(&[bracket]));
         }
 
@@ -1888,7 +2016,30 @@ impl<'a> Formatter for PrettyFormatter<'a> {
                 StringKind::Unquoted
             }
         };
-
+        let kind = if self.quotes.strings() {
+            match kind {
+                StringKind::TripleQuoted |
+                StringKind::MultilineTripleQuoted if self.quoted_multiline => kind,
+                _ => StringKind::DoubleQuoted,
+            }
+        } else if self.separator {
+            match kind {
+                StringKind::Unquoted => StringKind::DoubleQuoted,
+                _ => kind,
+            }
+        } else {
+            kind
+        };
+        let kind = match kind {
+            StringKind::TripleQuoted | StringKind::MultilineTripleQuoted => {
+                match self.multiline {
+                    Multiline::Std => kind,
+                    Multiline::NoTabs if !string.contains('\t') => kind,
+                    _ => StringKind::DoubleQuoted,
+                }
+            }
+            _ => kind,
+        };
 
         match kind {
             // Write the string verbatim
@@ -1956,7 +2107,9 @@ impl<'a> Formatter for PrettyFormatter<'a> {
             }
             // Write the string at the proper indentation level
             StringKind::MultilineTripleQuoted => {
-                if self.in_object {
+                // Member values go on their own line, one level deeper.
+                let member_value = self.in_object;
+                if member_value {
                     self.current_indent += 1;
 
                     try!(writer.write_all(b"\n"));
@@ -1983,7 +2136,7 @@ impl<'a> Formatter for PrettyFormatter<'a> {
                         start = i + 1;
                         has_content = false;
                         has_newline = true;
-                    } else if byte == b'\t' || byte == b' ' || byte == b'\r' {
+                    } else {
                         has_content = true;
                     }
                 }
@@ -1997,7 +2150,9 @@ impl<'a> Formatter for PrettyFormatter<'a> {
                 }
 
                 try!(indent(writer, self.current_indent, self.indent));
-                self.current_indent -= 1;
+                if member_value {
+                    self.current_indent -= 1;
+                }
                 writer.write_all(b"'''")
             }
         }
@@ -2008,7 +2163,7 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     where
         W: io::Write,
     {
-        if RE_MEMBER_NONE.is_match(string) {
+        if !self.quotes.keys() && RE_MEMBER_NONE.is_match(string) {
             // Unquoted
             writer.write_all(string.as_bytes())
         } else {
@@ -2084,11 +2239,14 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     }
 
     #[inline]
-    fn begin_array_value<W: ?Sized>(&mut self, writer: &mut W, _first: bool) -> io::Result<()>
+    fn begin_array_value<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
     where
         W: io::Write,
     {
         try!(self.try_write_bracket(writer));
+        if self.separator && !first {
+            try!(writer.write_all(b","));
+        }
 
         self.in_object = false;
         try!(writer.write_all(b"\n"));
@@ -2127,6 +2285,9 @@ impl<'a> Formatter for PrettyFormatter<'a> {
             }
 
             writer.write_all(b"{}")
+        } else if self.braceless_root && self.current_indent == 0 {
+            self.braceless_root = false;
+            Ok(())
         } else {
             self.current_indent -= 1;
 
@@ -2140,11 +2301,17 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     }
 
     #[inline]
-    fn begin_object_key<W: ?Sized>(&mut self, writer: &mut W, _first: bool) -> io::Result<()>
+    fn begin_object_key<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
     where
         W: io::Write,
     {
         try!(self.try_write_bracket(writer));
+        if self.separator && !first {
+            try!(writer.write_all(b","));
+        }
+        if self.braceless_root && self.current_indent == 0 && first {
+            return Ok(());
+        }
         try!(writer.write_all(b"\n"));
         indent(writer, self.current_indent, self.indent)
     }
@@ -2169,6 +2336,187 @@ impl<'a> Formatter for PrettyFormatter<'a> {
     }
 }
 
+/// This structure pretty prints a value as strictly valid, indented JSON.
+#[derive(Clone, Debug)]
+pub struct PrettyJsonFormatter<'a> {
+    current_indent: usize,
+    has_value: bool,
+    indent: &'a [u8],
+}
+
+impl<'a> PrettyJsonFormatter<'a> {
+    /// Construct a pretty printer formatter that defaults to using two spaces for indentation.
+    pub fn new() -> Self {
+        PrettyJsonFormatter::with_indent(b"  ")
+    }
+
+    /// Construct a pretty printer formatter that uses the `indent` string for indentation.
+    pub fn with_indent(indent: &'a [u8]) -> Self {
+        PrettyJsonFormatter {
+            current_indent: 0,
+            has_value: false,
+            indent: indent,
+        }
+    }
+}
+
+impl<'a> Default for PrettyJsonFormatter<'a> {
+    fn default() -> Self {
+        PrettyJsonFormatter::new()
+    }
+}
+
+impl<'a> Formatter for PrettyJsonFormatter<'a> {
+    #[inline]
+    fn write_string<W: ?Sized>(&mut self, writer: &mut W, string: &str) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        // Only escape what JSON requires, as `write_char_escape` also writes
+        // Hjson-only escapes like `\u{1f600}`.
+        static HEX_DIGITS: [u8; 16] = *b"0123456789abcdef";
+
+        try!(writer.write_all(b"\""));
+
+        let bytes = string.as_bytes();
+        let mut start = 0;
+
+        for (i, &byte) in bytes.iter().enumerate() {
+            let escape = match ESCAPE[byte as usize] {
+                0 | UU => continue,
+                escape => escape,
+            };
+
+            if start < i {
+                try!(writer.write_all(&bytes[start..i]));
+            }
+
+            if escape == U {
+                try!(writer.write_all(&[
+                    b'\\',
+                    b'u',
+                    b'0',
+                    b'0',
+                    HEX_DIGITS[(byte >> 4) as usize],
+                    HEX_DIGITS[(byte & 0b00001111) as usize],
+                ]));
+            } else {
+                try!(writer.write_all(&[b'\\', escape]));
+            }
+
+            start = i + 1;
+        }
+
+        if start != bytes.len() {
+            try!(writer.write_all(&bytes[start..]));
+        }
+
+        writer.write_all(b"\"")
+    }
+
+    #[inline]
+    fn begin_array<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.current_indent += 1;
+        self.has_value = false;
+        writer.write_all(b"[")
+    }
+
+    #[inline]
+    fn end_array<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.current_indent -= 1;
+
+        if self.has_value {
+            try!(writer.write_all(b"\n"));
+            try!(indent(writer, self.current_indent, self.indent));
+        }
+
+        writer.write_all(b"]")
+    }
+
+    #[inline]
+    fn begin_array_value<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        if first {
+            try!(writer.write_all(b"\n"));
+        } else {
+            try!(writer.write_all(b",\n"));
+        }
+        indent(writer, self.current_indent, self.indent)
+    }
+
+    #[inline]
+    fn end_array_value<W: ?Sized>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.has_value = true;
+        Ok(())
+    }
+
+    #[inline]
+    fn begin_object<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.current_indent += 1;
+        self.has_value = false;
+        writer.write_all(b"{")
+    }
+
+    #[inline]
+    fn end_object<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.current_indent -= 1;
+
+        if self.has_value {
+            try!(writer.write_all(b"\n"));
+            try!(indent(writer, self.current_indent, self.indent));
+        }
+
+        writer.write_all(b"}")
+    }
+
+    #[inline]
+    fn begin_object_key<W: ?Sized>(&mut self, writer: &mut W, first: bool) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        if first {
+            try!(writer.write_all(b"\n"));
+        } else {
+            try!(writer.write_all(b",\n"));
+        }
+        indent(writer, self.current_indent, self.indent)
+    }
+
+    #[inline]
+    fn begin_object_value<W: ?Sized>(&mut self, writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        writer.write_all(b": ")
+    }
+
+    #[inline]
+    fn end_object_value<W: ?Sized>(&mut self, _writer: &mut W) -> io::Result<()>
+    where
+        W: io::Write,
+    {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
 fn format_escaped_str<W: ?Sized, F: ?Sized>(
     writer: &mut W,
     formatter: &mut F,
@@ -2249,6 +2597,24 @@ where
     Ok(())
 }
 
+/// Serialize the given data structure as pretty-printed, strictly valid JSON
+/// into the IO stream.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+#[inline]
+pub fn to_writer_json_pretty<W, T: ?Sized>(writer: W, value: &T) -> Result<()>
+where
+    W: io::Write,
+    T: ser::Serialize,
+{
+    let mut ser = Serializer::pretty_json(writer);
+    try!(value.serialize(&mut ser));
+    Ok(())
+}
+
 /// Serialize the given data structure as a JSON byte vector.
 ///
 /// # Errors
@@ -2281,6 +2647,23 @@ where
     Ok(writer)
 }
 
+/// Serialize the given data structure as a pretty-printed, strictly valid
+/// JSON byte vector.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+#[inline]
+pub fn to_vec_json_pretty<T: ?Sized>(value: &T) -> Result<Vec<u8>>
+where
+    T: ser::Serialize,
+{
+    let mut writer = Vec::with_capacity(128);
+    try!(to_writer_json_pretty(&mut writer, value));
+    Ok(writer)
+}
+
 /// Serialize the given data structure as a String of JSON.
 ///
 /// # Errors
@@ -2319,6 +2702,26 @@ where
     Ok(string)
 }
 
+/// Serialize the given data structure as a pretty-printed, strictly valid
+/// String of JSON.
+///
+/// # Errors
+///
+/// Serialization can fail if `T`'s implementation of `Serialize` decides to
+/// fail, or if `T` contains a map with non-string keys.
+#[inline]
+pub fn to_string_json_pretty<T: ?Sized>(value: &T) -> Result<String>
+where
+    T: ser::Serialize,
+{
+    let vec = try!(to_vec_json_pretty(value));
+    let string = unsafe {
+        // We do not emit invalid UTF-8.
+        String::from_utf8_unchecked(vec)
+    };
+    Ok(string)
+}
+
 fn indent<W: ?Sized>(wr: &mut W, n: usize, s: &[u8]) -> io::Result<()>
 where
     W: io::Write,
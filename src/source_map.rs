@@ -0,0 +1,297 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Where the values of an Hjson document are.
+//!
+//! A [`SourceMap`] maps the JSON Pointer of every value in a document, as
+//! used by [`Value::pointer`], to the location of the value and of its member
+//! name. It lets code validating a plain struct or a `Value` after
+//! deserialization point at the offending line without changing every field
+//! to a [`Spanned`].
+//!
+//! ```rust
+//! # #[macro_use]
+//! # extern crate serde_derive;
+//! #
+//! # extern crate serde_hjson;
+//! #
+//! #[derive(Deserialize)]
+//! struct Server {
+//!     host: String,
+//!     port: u16,
+//! }
+//!
+//! # fn main() {
+//! let (servers, source_map) = serde_hjson::from_str_with_source_map::<Vec<Server>>("[
+//!     {host: \"example.com\", port: 80}
+//!     {host: \"example.org\", port: 0}
+//! ]").unwrap();
+//!
+//! for (i, server) in servers.iter().enumerate() {
+//!     if server.port == 0 {
+//!         let span = source_map.value(&format!("/{}/port", i)).unwrap();
+//!         let message = format!("config.hjson:{}:{}: invalid port", span.line, span.column);
+//!         assert_eq!(message, "config.hjson:3:33: invalid port");
+//!     }
+//! }
+//! # }
+//! ```
+//!
+//! [`SourceMap`]: struct.SourceMap.html
+//! [`Value::pointer`]: ../enum.Value.html#method.pointer
+//! [`Spanned`]: ../struct.Spanned.html
+
+use std::collections::{BTreeMap, btree_map};
+use std::io;
+use std::str;
+
+use serde::de;
+
+use de::{from_slice, from_str};
+use error::{Error, ErrorCode, Result};
+use syntax::{self, Child, NodeKind, TokenKind};
+
+/// The locations of all values in an Hjson document, by JSON Pointer.
+#[derive(Clone, Debug)]
+pub struct SourceMap {
+    locations: BTreeMap<String, Location>,
+}
+
+/// Where a value and its member name are in a document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    /// The member name, as written including any quotes. Array elements and
+    /// the root value have none.
+    pub key: Option<Span>,
+    /// The value.
+    pub value: Span,
+}
+
+/// A range of the input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Span {
+    /// Byte offset of the start.
+    pub start: usize,
+    /// Byte offset just past the end.
+    pub end: usize,
+    /// Line of the start, starting at 1.
+    pub line: usize,
+    /// Column of the start, starting at 1.
+    pub column: usize,
+}
+
+impl SourceMap {
+    /// Looks up the location of a value by its JSON Pointer.
+    ///
+    /// If an object has several members with the same name, the last one is
+    /// found, like it would end up in a `Value`.
+    pub fn get(&self, pointer: &str) -> Option<&Location> {
+        self.locations.get(pointer)
+    }
+
+    /// Looks up the span of a value by its JSON Pointer.
+    pub fn value(&self, pointer: &str) -> Option<&Span> {
+        self.get(pointer).map(|location| &location.value)
+    }
+
+    /// Looks up the span of the member name of a value by its JSON Pointer.
+    pub fn key(&self, pointer: &str) -> Option<&Span> {
+        self.get(pointer).and_then(|location| location.key.as_ref())
+    }
+
+    /// Iterates over the JSON Pointers and locations of all values, ordered by
+    /// pointer.
+    pub fn iter(&self) -> Iter {
+        Iter { iter: self.locations.iter() }
+    }
+
+    /// The number of values in the document.
+    pub fn len(&self) -> usize {
+        self.locations.len()
+    }
+
+    /// Returns true if the source map contains no locations.
+    pub fn is_empty(&self) -> bool {
+        self.locations.is_empty()
+    }
+
+    /// Builds the source map of a document that has been deserialized
+    /// successfully.
+    fn new(text: &str) -> Self {
+        let mut builder = Builder {
+            lines: Some(0)
+                .into_iter()
+                .chain(text.match_indices('\n').map(|(i, _)| i + 1))
+                .collect(),
+            locations: BTreeMap::new(),
+        };
+        if let Some(value) = syntax::parse(text).value() {
+            builder.add(String::new(), None, value);
+        }
+        SourceMap { locations: builder.locations }
+    }
+}
+
+impl<'a> IntoIterator for &'a SourceMap {
+    type Item = (&'a String, &'a Location);
+    type IntoIter = Iter<'a>;
+
+    fn into_iter(self) -> Iter<'a> {
+        self.iter()
+    }
+}
+
+/// An iterator over the entries of a `SourceMap`.
+pub struct Iter<'a> {
+    iter: btree_map::Iter<'a, String, Location>,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = (&'a String, &'a Location);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+struct Builder {
+    /// Byte offsets of the starts of all lines.
+    lines: Vec<usize>,
+    locations: BTreeMap<String, Location>,
+}
+
+impl Builder {
+    fn span(&self, start: usize, end: usize) -> Span {
+        let line = match self.lines.binary_search(&start) {
+            Ok(line) => line,
+            Err(line) => line - 1,
+        };
+        Span {
+            start: start,
+            end: end,
+            line: line + 1,
+            column: start - self.lines[line] + 1,
+        }
+    }
+
+    fn add(&mut self, pointer: String, key: Option<Span>, value: &Child) {
+        let node = match *value {
+            Child::Node(ref node) => node,
+            Child::Token(ref token) => {
+                let location = Location {
+                    key: key,
+                    value: self.span(token.start, token.end()),
+                };
+                self.locations.insert(pointer, location);
+                return;
+            }
+        };
+
+        let mut end = node.start();
+        match node.kind() {
+            NodeKind::Object => {
+                let members: Vec<_> = node.members()
+                    .filter_map(|member| match (member.name(), member.value()) {
+                        (Some(name), Some(value)) => Some((syntax::member_name(name), name, value)),
+                        _ => None,
+                    })
+                    .collect();
+                for (i, &(ref name, token, value)) in members.iter().enumerate() {
+                    end = value.end();
+                    // Like in a `Value`, the last of several members with the
+                    // same name wins.
+                    if members[i + 1..].iter().any(|later| later.0 == *name) {
+                        continue;
+                    }
+                    let escaped = name.replace('~', "~0").replace('/', "~1");
+                    let span = self.span(token.start, token.end());
+                    self.add(format!("{}/{}", pointer, escaped), Some(span), value);
+                }
+            }
+            NodeKind::Array => {
+                for (i, element) in node.elements().enumerate() {
+                    self.add(format!("{}/{}", pointer, i), None, element);
+                }
+            }
+            _ => {}
+        }
+
+        // A root object without braces ends with its last value, not with the
+        // comments after it.
+        match node.children().first() {
+            Some(&Child::Token(ref token)) if token.kind == TokenKind::LeftBrace ||
+                token.kind == TokenKind::LeftBracket => end = node.end(),
+            _ => {}
+        }
+        let location = Location {
+            key: key,
+            value: self.span(node.start(), end),
+        };
+        self.locations.insert(pointer, location);
+    }
+}
+
+/// Deserializes an instance of type `T` from a string of Hjson text, along
+/// with the locations of all values in it.
+///
+/// # Errors
+///
+/// Fails in the same cases as `from_str`.
+pub fn from_str_with_source_map<'a, T>(s: &'a str) -> Result<(T, SourceMap)>
+where
+    T: de::Deserialize<'a>,
+{
+    let value = try!(from_str(s));
+    Ok((value, SourceMap::new(s)))
+}
+
+/// Deserializes an instance of type `T` from bytes of Hjson text, along with
+/// the locations of all values in it.
+///
+/// # Errors
+///
+/// Fails in the same cases as `from_slice`, and if the input is not valid
+/// UTF-8, even if the invalid bytes are within a comment.
+pub fn from_slice_with_source_map<'a, T>(v: &'a [u8]) -> Result<(T, SourceMap)>
+where
+    T: de::Deserialize<'a>,
+{
+    let value = try!(from_slice(v));
+    match str::from_utf8(v) {
+        Ok(s) => Ok((value, SourceMap::new(s))),
+        Err(err) => {
+            let valid = &v[..err.valid_up_to()];
+            let line = valid.iter().filter(|&&ch| ch == b'\n').count() + 1;
+            let column = valid.iter().rev().take_while(|&&ch| ch != b'\n').count() + 1;
+            Err(Error::syntax(ErrorCode::InvalidUnicodeCodePoint, line, column))
+        }
+    }
+}
+
+/// Deserializes an instance of type `T` from an IO stream of Hjson, along with
+/// the locations of all values in it.
+///
+/// Unlike `from_reader`, this reads the whole stream into memory first.
+///
+/// # Errors
+///
+/// Fails in the same cases as `from_slice_with_source_map`, and if reading
+/// from the stream fails.
+pub fn from_reader_with_source_map<R, T>(mut rdr: R) -> Result<(T, SourceMap)>
+where
+    R: io::Read,
+    T: de::DeserializeOwned,
+{
+    let mut bytes = Vec::new();
+    try!(rdr.read_to_end(&mut bytes).map_err(Error::io));
+    from_slice_with_source_map(&bytes)
+}
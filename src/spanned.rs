@@ -0,0 +1,195 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A wrapper recording where a value was found in the input.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::marker::PhantomData;
+use std::ops::Range;
+
+use serde::de::{self, Deserialize, Deserializer, MapAccess, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+// The `Deserializer` recognizes a struct with this name and these fields and
+// hands out the position of the value along with it.
+pub const NAME: &str = "$__serde_hjson_private_Spanned";
+pub const START: &str = "$__serde_hjson_private_start";
+pub const LINE: &str = "$__serde_hjson_private_line";
+pub const COLUMN: &str = "$__serde_hjson_private_column";
+pub const VALUE: &str = "$__serde_hjson_private_value";
+pub const END: &str = "$__serde_hjson_private_end";
+pub const FIELDS: &[&str] = &[START, LINE, COLUMN, VALUE, END];
+
+/// A deserialized value along with its location in the input.
+///
+/// Only the Hjson `Deserializer` knows where values are, so `Spanned` can not
+/// be deserialized from anything else. That includes a `Value` and the objects
+/// buffered under `DuplicateKeys::KeepLast` and `DuplicateKeys::Collect`.
+///
+/// ```rust
+/// # #[macro_use]
+/// # extern crate serde_derive;
+/// #
+/// # extern crate serde_hjson;
+/// #
+/// use serde_hjson::Spanned;
+///
+/// #[derive(Deserialize)]
+/// struct Server {
+///     host: String,
+///     port: Spanned<u16>,
+/// }
+///
+/// # fn main() {
+/// let server: Server = serde_hjson::from_str("
+///     host: localhost
+///     port: 80
+/// ").unwrap();
+///
+/// assert_eq!(*server.port.get_ref(), 80);
+/// assert_eq!((server.port.line(), server.port.column()), (3, 11));
+/// assert_eq!(server.port.span(), 31..33);
+/// # }
+/// ```
+///
+/// Comparisons and hashing only look at the value, not at its location.
+#[derive(Clone, Debug)]
+pub struct Spanned<T> {
+    start: usize,
+    end: usize,
+    line: usize,
+    column: usize,
+    value: T,
+}
+
+impl<T> Spanned<T> {
+    /// Byte offset of the start of the value.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset just past the end of the value.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// Byte range of the value.
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Line of the start of the value, starting at 1.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// Column of the start of the value, starting at 1.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+
+    /// Returns a reference to the value.
+    pub fn get_ref(&self) -> &T {
+        &self.value
+    }
+
+    /// Returns a mutable reference to the value.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.value
+    }
+
+    /// Consumes the `Spanned`, returning the value.
+    pub fn into_inner(self) -> T {
+        self.value
+    }
+}
+
+impl<T: PartialEq> PartialEq for Spanned<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.value == other.value
+    }
+}
+
+impl<T: Eq> Eq for Spanned<T> {}
+
+impl<T: PartialOrd> PartialOrd for Spanned<T> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.value.partial_cmp(&other.value)
+    }
+}
+
+impl<T: Ord> Ord for Spanned<T> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.value.cmp(&other.value)
+    }
+}
+
+impl<T: Hash> Hash for Spanned<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.value.hash(state)
+    }
+}
+
+impl<T: Serialize> Serialize for Spanned<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.value.serialize(serializer)
+    }
+}
+
+impl<'de, T: Deserialize<'de>> Deserialize<'de> for Spanned<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Spanned<T>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct SpannedVisitor<T>(PhantomData<T>);
+
+        impl<'de, T: Deserialize<'de>> Visitor<'de> for SpannedVisitor<T> {
+            type Value = Spanned<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a spanned value")
+            }
+
+            fn visit_map<V>(self, mut visitor: V) -> Result<Spanned<T>, V::Error>
+            where
+                V: MapAccess<'de>,
+            {
+                let start = try!(next_field(&mut visitor, START));
+                let line = try!(next_field(&mut visitor, LINE));
+                let column = try!(next_field(&mut visitor, COLUMN));
+                let value = try!(next_field(&mut visitor, VALUE));
+                let end = try!(next_field(&mut visitor, END));
+                Ok(Spanned {
+                    start: start,
+                    end: end,
+                    line: line,
+                    column: column,
+                    value: value,
+                })
+            }
+        }
+
+        deserializer.deserialize_struct(NAME, FIELDS, SpannedVisitor(PhantomData))
+    }
+}
+
+fn next_field<'de, V, T>(visitor: &mut V, field: &'static str) -> Result<T, V::Error>
+where
+    V: MapAccess<'de>,
+    T: Deserialize<'de>,
+{
+    match try!(visitor.next_key::<&str>()) {
+        Some(key) if key == field => visitor.next_value(),
+        _ => Err(de::Error::custom("spanned values are only supported by serde_hjson")),
+    }
+}
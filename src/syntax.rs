@@ -0,0 +1,615 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! The tokens and the concrete syntax tree of an Hjson document.
+//!
+//! [`tokenize`] splits a document into [`Token`]s the way the `Deserializer`
+//! reads it, including the whitespace and comments it skips, so that the text
+//! of the tokens adds up to the whole document. It tells member names from
+//! quoteless strings, and numbers and literals from quoteless strings that
+//! merely start like one, which depends on what comes before and after them.
+//!
+//! [`parse`] arranges the same tokens into a tree of objects, arrays and
+//! members, which also adds up to the whole document.
+//!
+//! Neither ever fails. Input that is not valid Hjson still comes out as tokens
+//! and nodes, with what the `Deserializer` cannot make sense of, like
+//! unterminated strings and comments, as `TokenKind::Error`.
+//!
+//! ```rust
+//! use serde_hjson::syntax::{self, TokenKind};
+//!
+//! let tokens: Vec<_> = syntax::tokenize("port: 80 # default\n")
+//!     .filter(|token| !token.kind.is_trivia())
+//!     .map(|token| (token.kind, token.text))
+//!     .collect();
+//!
+//! assert_eq!(tokens, [
+//!     (TokenKind::MemberName, "port"),
+//!     (TokenKind::Colon, ":"),
+//!     (TokenKind::Number, "80"),
+//! ]);
+//! ```
+//!
+//! [`tokenize`]: fn.tokenize.html
+//! [`Token`]: struct.Token.html
+//! [`parse`]: fn.parse.html
+
+use std::fmt::{self, Display};
+use std::iter::Peekable;
+use std::ops::Range;
+use std::{slice, vec};
+
+use de::{from_str, lex};
+
+/// Splits an Hjson document into tokens.
+pub fn tokenize(text: &str) -> Tokenizer {
+    let mut lexed = lex(text);
+    lexed.sort_by(|a, b| a.1.start.cmp(&b.1.start).then(b.1.end.cmp(&a.1.end)));
+    Tokenizer {
+        text: text,
+        pos: 0,
+        lexed: lexed.into_iter().peekable(),
+    }
+}
+
+/// Parses an Hjson document into a concrete syntax tree. The root node is a
+/// `NodeKind::Document`.
+///
+/// ```rust
+/// use serde_hjson::syntax::{self, NodeKind};
+///
+/// let text = "// servers\n[\n  {host: \"a\", port: 80}\n]\n";
+/// let document = syntax::parse(text);
+/// assert_eq!(document.to_string(), text);
+///
+/// let array = document.value().unwrap().as_node().unwrap();
+/// assert_eq!(array.kind(), NodeKind::Array);
+///
+/// let object = array.elements().next().unwrap().as_node().unwrap();
+/// let names: Vec<_> = object.members().map(|member| member.name().unwrap().text).collect();
+/// assert_eq!(names, ["host", "port"]);
+/// ```
+pub fn parse(text: &str) -> Node {
+    let mut builder = Builder { tokens: tokenize(text).peekable() };
+    builder.document()
+}
+
+// Not public API. Should be pub(crate).
+//
+// The name of a member as the deserializer reads it from a `MemberName` token,
+// without quotes and escapes.
+#[doc(hidden)]
+pub fn member_name(token: &Token) -> String {
+    match token.text.as_bytes().first() {
+        Some(&b'"') | Some(&b'\'') => from_str(token.text).unwrap_or_default(),
+        _ => String::from(token.text),
+    }
+}
+
+/// A piece of an Hjson document.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Token<'a> {
+    /// What the token is.
+    pub kind: TokenKind,
+    /// The text of the token, as written in the document.
+    pub text: &'a str,
+    /// Byte offset of the start of the token.
+    pub start: usize,
+}
+
+impl<'a> Token<'a> {
+    /// Byte offset just past the end of the token.
+    pub fn end(&self) -> usize {
+        self.start + self.text.len()
+    }
+
+    /// The byte range of the token in the document.
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end()
+    }
+}
+
+/// What a token is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum TokenKind {
+    /// Spaces and tabs.
+    Whitespace,
+    /// A line break, `\n` or `\r\n`.
+    Newline,
+    /// A `#` or `//` comment up to the end of the line, or a `/* */` comment.
+    Comment,
+    /// `{`
+    LeftBrace,
+    /// `}`
+    RightBrace,
+    /// `[`
+    LeftBracket,
+    /// `]`
+    RightBracket,
+    /// `:`
+    Colon,
+    /// `,`
+    Comma,
+    /// The name of an object member, quoted or not.
+    MemberName,
+    /// A string without quotes, which runs to the end of the line. Whitespace
+    /// at the end of the line is not part of it.
+    QuotelessString,
+    /// A string in `"` quotes.
+    DoubleQuotedString,
+    /// A string in `'` quotes.
+    SingleQuotedString,
+    /// A string in `'''` quotes.
+    MultilineString,
+    /// A number.
+    Number,
+    /// `true`, `false` or `null`.
+    Literal,
+    /// Input that is not valid Hjson, like a string or comment that is not
+    /// terminated.
+    Error,
+}
+
+impl TokenKind {
+    /// Whether the token is whitespace, a newline or a comment, which carry no
+    /// data.
+    pub fn is_trivia(&self) -> bool {
+        match *self {
+            TokenKind::Whitespace | TokenKind::Newline | TokenKind::Comment => true,
+            _ => false,
+        }
+    }
+
+    /// Whether the token is a string, number or literal value.
+    pub fn is_scalar(&self) -> bool {
+        match *self {
+            TokenKind::QuotelessString |
+            TokenKind::DoubleQuotedString |
+            TokenKind::SingleQuotedString |
+            TokenKind::MultilineString |
+            TokenKind::Number |
+            TokenKind::Literal => true,
+            _ => false,
+        }
+    }
+}
+
+/// An iterator over the tokens of an Hjson document, created by `tokenize`.
+pub struct Tokenizer<'a> {
+    text: &'a str,
+    pos: usize,
+    /// The comments, member names and values the deserializer found, in order
+    /// of their start and with the longest first where several start at once.
+    lexed: Peekable<vec::IntoIter<(TokenKind, Range<usize>)>>,
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let start = self.pos;
+        if start == self.text.len() {
+            return None;
+        }
+
+        // Leave out tokens inside of the previous one.
+        while let Some((_, range)) = self.lexed.peek() {
+            if range.start >= start && !range.is_empty() {
+                break;
+            }
+            self.lexed.next();
+        }
+        let kind = match self.lexed.peek().cloned() {
+            Some((kind, range)) if range.start == start => {
+                self.lexed.next();
+                self.pos = range.end;
+                match kind {
+                    TokenKind::QuotelessString => string_kind(&self.text[range]),
+                    kind => kind,
+                }
+            }
+            next => {
+                let end = next.map_or(self.text.len(), |(_, range)| range.start);
+                self.between(end)
+            }
+        };
+        Some(Token {
+            kind: kind,
+            text: &self.text[start..self.pos],
+            start: start,
+        })
+    }
+}
+
+impl<'a> Tokenizer<'a> {
+    /// Reads a token from the text between the tokens the deserializer found,
+    /// which ends at `end` at the latest. It holds only whitespace and
+    /// punctuation, unless the text is not valid Hjson.
+    fn between(&mut self, end: usize) -> TokenKind {
+        let bytes = &self.text.as_bytes()[..end];
+        let start = self.pos;
+        self.pos += 1;
+        match bytes[start] {
+            b'\n' => TokenKind::Newline,
+            b'\r' if bytes.get(start + 1) == Some(&b'\n') => {
+                self.pos += 1;
+                TokenKind::Newline
+            }
+            b' ' | b'\t' | b'\r' => {
+                self.pos = start + whitespace_len(&bytes[start..]);
+                TokenKind::Whitespace
+            }
+            b'{' => TokenKind::LeftBrace,
+            b'}' => TokenKind::RightBrace,
+            b'[' => TokenKind::LeftBracket,
+            b']' => TokenKind::RightBracket,
+            b':' => TokenKind::Colon,
+            b',' => TokenKind::Comma,
+            _ => {
+                while let Some(&ch) = bytes.get(self.pos) {
+                    match ch {
+                        b' ' | b'\t' | b'\r' | b'\n' | b'{' | b'}' | b'[' | b']' | b':' | b',' => break,
+                        _ => self.pos += 1,
+                    }
+                }
+                TokenKind::Error
+            }
+        }
+    }
+}
+
+/// What a string the deserializer found is, which its quotes tell.
+fn string_kind(text: &str) -> TokenKind {
+    if text.starts_with("'''") {
+        TokenKind::MultilineString
+    } else if text.starts_with('\'') {
+        TokenKind::SingleQuotedString
+    } else if text.starts_with('"') {
+        TokenKind::DoubleQuotedString
+    } else {
+        TokenKind::QuotelessString
+    }
+}
+
+fn whitespace_len(bytes: &[u8]) -> usize {
+    let mut len = 0;
+    loop {
+        match bytes.get(len) {
+            Some(&b' ') | Some(&b'\t') => len += 1,
+            Some(&b'\r') if bytes.get(len + 1) != Some(&b'\n') => len += 1,
+            _ => return len,
+        }
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+
+/// A node of the concrete syntax tree of an Hjson document, created by
+/// `parse`.
+///
+/// The tokens and nodes that make up a node are its children. All of them
+/// together, including whitespace and comments, add up to the text of the
+/// node, which is what its `Display` implementation writes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Node<'a> {
+    kind: NodeKind,
+    start: usize,
+    end: usize,
+    children: Vec<Child<'a>>,
+}
+
+/// What a node is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum NodeKind {
+    /// The whole document: the root value along with the whitespace and
+    /// comments around it.
+    Document,
+    /// An object, whose members are `Member` nodes. The root object may be
+    /// missing its braces.
+    Object,
+    /// An array.
+    Array,
+    /// A member of an object: its name, the `:` and the value.
+    Member,
+}
+
+/// A child of a node.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Child<'a> {
+    /// An object, array or member.
+    Node(Node<'a>),
+    /// A token.
+    Token(Token<'a>),
+}
+
+impl<'a> Node<'a> {
+    /// What the node is.
+    pub fn kind(&self) -> NodeKind {
+        self.kind
+    }
+
+    /// Byte offset of the start of the node.
+    pub fn start(&self) -> usize {
+        self.start
+    }
+
+    /// Byte offset just past the end of the node.
+    pub fn end(&self) -> usize {
+        self.end
+    }
+
+    /// The byte range of the node in the document.
+    pub fn span(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// The tokens and nodes the node is made of, in order.
+    pub fn children(&self) -> &[Child<'a>] {
+        &self.children
+    }
+
+    /// All tokens of the node and of the nodes inside of it, in order.
+    pub fn tokens<'n>(&'n self) -> Tokens<'a, 'n> {
+        Tokens { stack: vec![self.children.iter()] }
+    }
+
+    /// The value of a document or member, which is a node for an object or
+    /// array and a token otherwise.
+    pub fn value(&self) -> Option<&Child<'a>> {
+        let mut children = self.children.iter();
+        if self.kind == NodeKind::Member {
+            // Skip the member name and the `:`.
+            if !children.any(|child| child.is_token(TokenKind::Colon)) {
+                return None;
+            }
+        }
+        children.find(|child| child.is_value())
+    }
+
+    /// The name of a member.
+    pub fn name(&self) -> Option<&Token<'a>> {
+        match self.children.first() {
+            Some(&Child::Token(ref token)) if token.kind == TokenKind::MemberName => Some(token),
+            _ => None,
+        }
+    }
+
+    /// The members of an object.
+    pub fn members<'n>(&'n self) -> Members<'a, 'n> {
+        Members { children: self.children.iter() }
+    }
+
+    /// The elements of an array.
+    pub fn elements<'n>(&'n self) -> Elements<'a, 'n> {
+        Elements { children: self.children.iter() }
+    }
+}
+
+impl<'a> Display for Node<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for token in self.tokens() {
+            try!(f.write_str(token.text));
+        }
+        Ok(())
+    }
+}
+
+impl<'a> Child<'a> {
+    /// The node, if this is one.
+    pub fn as_node(&self) -> Option<&Node<'a>> {
+        match *self {
+            Child::Node(ref node) => Some(node),
+            Child::Token(_) => None,
+        }
+    }
+
+    /// The token, if this is one.
+    pub fn as_token(&self) -> Option<&Token<'a>> {
+        match *self {
+            Child::Node(_) => None,
+            Child::Token(ref token) => Some(token),
+        }
+    }
+
+    /// Byte offset of the start of the child.
+    pub fn start(&self) -> usize {
+        match *self {
+            Child::Node(ref node) => node.start,
+            Child::Token(ref token) => token.start,
+        }
+    }
+
+    /// Byte offset just past the end of the child.
+    pub fn end(&self) -> usize {
+        match *self {
+            Child::Node(ref node) => node.end,
+            Child::Token(ref token) => token.end(),
+        }
+    }
+
+    fn is_token(&self, kind: TokenKind) -> bool {
+        match *self {
+            Child::Token(ref token) => token.kind == kind,
+            Child::Node(_) => false,
+        }
+    }
+
+    /// Whether this is an object, an array or a scalar value.
+    fn is_value(&self) -> bool {
+        match *self {
+            Child::Node(ref node) => node.kind == NodeKind::Object || node.kind == NodeKind::Array,
+            Child::Token(ref token) => token.kind.is_scalar(),
+        }
+    }
+}
+
+/// An iterator over the tokens of a node, created by `Node::tokens`.
+pub struct Tokens<'a: 'n, 'n> {
+    stack: Vec<slice::Iter<'n, Child<'a>>>,
+}
+
+impl<'a, 'n> Iterator for Tokens<'a, 'n> {
+    type Item = &'n Token<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let child = match self.stack.last_mut() {
+                Some(children) => children.next(),
+                None => return None,
+            };
+            match child {
+                Some(&Child::Token(ref token)) => return Some(token),
+                Some(&Child::Node(ref node)) => self.stack.push(node.children.iter()),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
+    }
+}
+
+/// An iterator over the members of an object, created by `Node::members`.
+pub struct Members<'a: 'n, 'n> {
+    children: slice::Iter<'n, Child<'a>>,
+}
+
+impl<'a, 'n> Iterator for Members<'a, 'n> {
+    type Item = &'n Node<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        for child in &mut self.children {
+            if let Child::Node(ref node) = *child {
+                if node.kind == NodeKind::Member {
+                    return Some(node);
+                }
+            }
+        }
+        None
+    }
+}
+
+/// An iterator over the elements of an array, created by `Node::elements`.
+pub struct Elements<'a: 'n, 'n> {
+    children: slice::Iter<'n, Child<'a>>,
+}
+
+impl<'a, 'n> Iterator for Elements<'a, 'n> {
+    type Item = &'n Child<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.children.find(|child| child.is_value())
+    }
+}
+
+/// Arranges the tokens from a `Tokenizer` into nodes. Every `{` and `[` starts
+/// a node, and every `}` and `]` ends one, like the tokenizer keeps track of
+/// them.
+struct Builder<'a> {
+    tokens: Peekable<Tokenizer<'a>>,
+}
+
+impl<'a> Builder<'a> {
+    fn peek(&mut self) -> Option<TokenKind> {
+        self.tokens.peek().map(|token| token.kind)
+    }
+
+    fn document(&mut self) -> Node<'a> {
+        let mut children = Vec::new();
+        while let Some(kind) = self.peek() {
+            if kind.is_trivia() {
+                children.push(Child::Token(self.tokens.next().unwrap()));
+            } else {
+                break;
+            }
+        }
+
+        if self.peek() == Some(TokenKind::MemberName) {
+            let start = children.last().map_or(0, Child::end);
+            children.push(Child::Node(self.container(NodeKind::Object, start, Vec::new())));
+        }
+        while self.peek().is_some() {
+            let child = self.child();
+            children.push(child);
+        }
+        node(NodeKind::Document, 0, children)
+    }
+
+    /// The next token, or the node it starts.
+    fn child(&mut self) -> Child<'a> {
+        let token = self.tokens.next().unwrap();
+        let kind = match token.kind {
+            TokenKind::LeftBrace => NodeKind::Object,
+            TokenKind::LeftBracket => NodeKind::Array,
+            _ => return Child::Token(token),
+        };
+        Child::Node(self.container(kind, token.start, vec![Child::Token(token)]))
+    }
+
+    /// The rest of an array or object up to and including the closing bracket,
+    /// or up to the end of the document.
+    fn container(&mut self, kind: NodeKind, start: usize, mut children: Vec<Child<'a>>) -> Node<'a> {
+        while let Some(next) = self.peek() {
+            match next {
+                TokenKind::RightBrace | TokenKind::RightBracket => {
+                    children.push(Child::Token(self.tokens.next().unwrap()));
+                    break;
+                }
+                TokenKind::MemberName => {
+                    let member = self.member();
+                    children.push(Child::Node(member));
+                }
+                _ => {
+                    let child = self.child();
+                    children.push(child);
+                }
+            }
+        }
+        node(kind, start, children)
+    }
+
+    fn member(&mut self) -> Node<'a> {
+        let name = self.tokens.next().unwrap();
+        let start = name.start;
+        let mut children = vec![Child::Token(name)];
+        while self.peek() == Some(TokenKind::Whitespace) || self.peek() == Some(TokenKind::Comment) {
+            children.push(Child::Token(self.tokens.next().unwrap()));
+        }
+        if self.peek() != Some(TokenKind::Colon) {
+            return node(NodeKind::Member, start, children);
+        }
+
+        children.push(Child::Token(self.tokens.next().unwrap()));
+        while let Some(next) = self.peek() {
+            if next.is_trivia() {
+                children.push(Child::Token(self.tokens.next().unwrap()));
+            } else {
+                if next.is_scalar() || next == TokenKind::LeftBrace ||
+                    next == TokenKind::LeftBracket
+                {
+                    let value = self.child();
+                    children.push(value);
+                }
+                break;
+            }
+        }
+        node(NodeKind::Member, start, children)
+    }
+}
+
+fn node(kind: NodeKind, start: usize, children: Vec<Child>) -> Node {
+    let end = children.last().map_or(start, Child::end);
+    Node {
+        kind: kind,
+        start: start,
+        end: end,
+        children: children,
+    }
+}
@@ -324,13 +324,17 @@ impl<'de> EnumAccess<'de> for EnumDeserializer {
     where
         V: DeserializeSeed<'de>,
     {
+        let visitor = VariantDeserializer {
+            variant: self.variant.clone(),
+            value: self.value,
+        };
         let variant = self.variant.into_deserializer();
-        let visitor = VariantDeserializer { value: self.value };
         seed.deserialize(variant).map(|v| (v, visitor))
     }
 }
 
 struct VariantDeserializer {
+    variant: String,
     value: Option<Value>,
 }
 
@@ -348,8 +352,9 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         T: DeserializeSeed<'de>,
     {
+        let variant = self.variant;
         match self.value {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|err| err.prepend_key(&variant)),
             None => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"newtype variant"),),
         }
     }
@@ -358,9 +363,11 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         V: Visitor<'de>,
     {
+        let variant = self.variant;
         match self.value {
             Some(Value::Array(v)) => {
                 serde::Deserializer::deserialize_any(SeqDeserializer::new(v), visitor)
+                    .map_err(|err| err.prepend_key(&variant))
             }
             Some(other) => Err(serde::de::Error::invalid_type(other.unexpected(), &"tuple variant"),),
             None => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"tuple variant"),),
@@ -375,9 +382,11 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
     where
         V: Visitor<'de>,
     {
+        let variant = self.variant;
         match self.value {
             Some(Value::Object(v)) => {
                 serde::Deserializer::deserialize_any(MapDeserializer::new(v), visitor)
+                    .map_err(|err| err.prepend_key(&variant))
             }
             Some(other) => Err(serde::de::Error::invalid_type(other.unexpected(), &"struct variant"),),
             _ => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"struct variant"),),
@@ -387,11 +396,15 @@ impl<'de> VariantAccess<'de> for VariantDeserializer {
 
 struct SeqDeserializer {
     iter: vec::IntoIter<Value>,
+    index: usize,
 }
 
 impl SeqDeserializer {
     fn new(vec: Vec<Value>) -> Self {
-        SeqDeserializer { iter: vec.into_iter() }
+        SeqDeserializer {
+            iter: vec.into_iter(),
+            index: 0,
+        }
     }
 }
 
@@ -432,7 +445,11 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value).map(Some).map_err(|err| err.prepend_index(index))
+            }
             None => Ok(None),
         }
     }
@@ -447,6 +464,7 @@ impl<'de> SeqAccess<'de> for SeqDeserializer {
 
 struct MapDeserializer {
     iter: <Map<String, Value> as IntoIterator>::IntoIter,
+    key: String,
     value: Option<Value>,
 }
 
@@ -454,6 +472,7 @@ impl MapDeserializer {
     fn new(map: Map<String, Value>) -> Self {
         MapDeserializer {
             iter: map.into_iter(),
+            key: String::new(),
             value: None,
         }
     }
@@ -468,6 +487,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
     {
         match self.iter.next() {
             Some((key, value)) => {
+                self.key.clone_from(&key);
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer { key: Cow::Owned(key) };
                 seed.deserialize(key_de).map(Some)
@@ -481,7 +501,7 @@ impl<'de> MapAccess<'de> for MapDeserializer {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|err| err.prepend_key(&self.key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
@@ -635,12 +655,16 @@ impl<'de> EnumAccess<'de> for EnumRefDeserializer<'de> {
         V: DeserializeSeed<'de>,
     {
         let variant = self.variant.into_deserializer();
-        let visitor = VariantRefDeserializer { value: self.value };
+        let visitor = VariantRefDeserializer {
+            variant: self.variant,
+            value: self.value,
+        };
         seed.deserialize(variant).map(|v| (v, visitor))
     }
 }
 
 struct VariantRefDeserializer<'de> {
+    variant: &'de str,
     value: Option<&'de Value>,
 }
 
@@ -659,7 +683,7 @@ impl<'de> VariantAccess<'de> for VariantRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.value {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|err| err.prepend_key(&self.variant)),
             None => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"newtype variant"),),
         }
     }
@@ -671,6 +695,7 @@ impl<'de> VariantAccess<'de> for VariantRefDeserializer<'de> {
         match self.value {
             Some(&Value::Array(ref v)) => {
                 serde::Deserializer::deserialize_any(SeqRefDeserializer::new(v), visitor)
+                    .map_err(|err| err.prepend_key(self.variant))
             }
             Some(other) => Err(serde::de::Error::invalid_type(other.unexpected(), &"tuple variant"),),
             None => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"tuple variant"),),
@@ -688,6 +713,7 @@ impl<'de> VariantAccess<'de> for VariantRefDeserializer<'de> {
         match self.value {
             Some(&Value::Object(ref v)) => {
                 serde::Deserializer::deserialize_any(MapRefDeserializer::new(v), visitor)
+                    .map_err(|err| err.prepend_key(self.variant))
             }
             Some(other) => Err(serde::de::Error::invalid_type(other.unexpected(), &"struct variant"),),
             _ => Err(serde::de::Error::invalid_type(Unexpected::UnitVariant, &"struct variant"),),
@@ -697,11 +723,15 @@ impl<'de> VariantAccess<'de> for VariantRefDeserializer<'de> {
 
 struct SeqRefDeserializer<'de> {
     iter: slice::Iter<'de, Value>,
+    index: usize,
 }
 
 impl<'de> SeqRefDeserializer<'de> {
     fn new(slice: &'de [Value]) -> Self {
-        SeqRefDeserializer { iter: slice.iter() }
+        SeqRefDeserializer {
+            iter: slice.iter(),
+            index: 0,
+        }
     }
 }
 
@@ -742,7 +772,11 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.iter.next() {
-            Some(value) => seed.deserialize(value).map(Some),
+            Some(value) => {
+                let index = self.index;
+                self.index += 1;
+                seed.deserialize(value).map(Some).map_err(|err| err.prepend_index(index))
+            }
             None => Ok(None),
         }
     }
@@ -757,6 +791,7 @@ impl<'de> SeqAccess<'de> for SeqRefDeserializer<'de> {
 
 struct MapRefDeserializer<'de> {
     iter: <&'de Map<String, Value> as IntoIterator>::IntoIter,
+    key: &'de str,
     value: Option<&'de Value>,
 }
 
@@ -764,6 +799,7 @@ impl<'de> MapRefDeserializer<'de> {
     fn new(map: &'de Map<String, Value>) -> Self {
         MapRefDeserializer {
             iter: map.into_iter(),
+            key: "",
             value: None,
         }
     }
@@ -778,6 +814,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
     {
         match self.iter.next() {
             Some((key, value)) => {
+                self.key = key;
                 self.value = Some(value);
                 let key_de = MapKeyDeserializer { key: Cow::Borrowed(&**key) };
                 seed.deserialize(key_de).map(Some)
@@ -791,7 +828,7 @@ impl<'de> MapAccess<'de> for MapRefDeserializer<'de> {
         T: DeserializeSeed<'de>,
     {
         match self.value.take() {
-            Some(value) => seed.deserialize(value),
+            Some(value) => seed.deserialize(value).map_err(|err| err.prepend_key(&self.key)),
             None => Err(serde::de::Error::custom("value is missing")),
         }
     }
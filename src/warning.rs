@@ -0,0 +1,128 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Input that is valid Hjson but probably not what was meant.
+//!
+//! A `Deserializer` records [`Warning`]s once `collect_warnings` has been
+//! called on it, and the `from_*_with_warnings` functions return them along
+//! with the value. They never make deserialization fail.
+//!
+//! ```rust
+//! use serde_hjson::Value;
+//! use serde_hjson::warning::WarningKind;
+//!
+//! let (_, warnings) = serde_hjson::from_str_with_warnings::<Value>("
+//! version: 1.0.0
+//! title: Introduction # draft
+//! ").unwrap();
+//!
+//! assert_eq!(warnings[0].kind(), &WarningKind::QuotelessLiteral);
+//! assert_eq!((warnings[0].line(), warnings[0].column()), (2, 10));
+//! assert_eq!(
+//!     warnings[1].to_string(),
+//!     "`#` and `//` do not start comments in quoteless strings at line 3 column 21"
+//! );
+//! ```
+//!
+//! [`Warning`]: struct.Warning.html
+
+use std::fmt::{self, Display};
+
+/// A suspicious part of the input, along with where it is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Warning {
+    kind: WarningKind,
+    line: usize,
+    column: usize,
+}
+
+/// What is suspicious about the input.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WarningKind {
+    /// A quoteless string that starts like a number or like `true`, `false`
+    /// or `null`, such as `1.0.0` or `true story`, so that it may have been
+    /// meant as one.
+    QuotelessLiteral,
+
+    /// Whitespace at the end of a quoteless string, which is not part of the
+    /// string.
+    TrailingWhitespace,
+
+    /// A tab in the indentation of a `'''` multiline string. Tabs count as a
+    /// single column, so the string may keep more or less indentation than it
+    /// seems to.
+    TabInMultilineIndent,
+
+    /// A member name that differs only in case from an earlier one in the same
+    /// object, which is held here.
+    KeyCaseMismatch(String),
+
+    /// A `#` or `//` in a quoteless string, where it is part of the string
+    /// rather than the start of a comment.
+    CommentInQuotelessString,
+
+    #[doc(hidden)]
+    __Nonexhaustive,
+}
+
+impl Warning {
+    // Not public API. Should be pub(crate).
+    #[doc(hidden)]
+    pub fn new(kind: WarningKind, line: usize, column: usize) -> Self {
+        Warning {
+            kind: kind,
+            line: line,
+            column: column,
+        }
+    }
+
+    /// What is suspicious about the input.
+    pub fn kind(&self) -> &WarningKind {
+        &self.kind
+    }
+
+    /// One-based line number at which the suspicious input starts.
+    pub fn line(&self) -> usize {
+        self.line
+    }
+
+    /// One-based column number at which the suspicious input starts, counted
+    /// in bytes.
+    pub fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl Display for WarningKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            WarningKind::QuotelessLiteral => {
+                f.write_str("quoteless string starts like a number or literal")
+            }
+            WarningKind::TrailingWhitespace => {
+                f.write_str("trailing whitespace is not part of the quoteless string")
+            }
+            WarningKind::TabInMultilineIndent => {
+                f.write_str("tab in the indentation of a multiline string")
+            }
+            WarningKind::KeyCaseMismatch(ref earlier) => {
+                write!(f, "key differs only in case from `{}`", earlier)
+            }
+            WarningKind::CommentInQuotelessString => {
+                f.write_str("`#` and `//` do not start comments in quoteless strings")
+            }
+            WarningKind::__Nonexhaustive => f.write_str("suspicious input"),
+        }
+    }
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} at line {} column {}", self.kind, self.line, self.column)
+    }
+}
@@ -0,0 +1,239 @@
+#![cfg(feature = "async")]
+
+extern crate futures;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_hjson;
+
+use std::collections::VecDeque;
+use std::io;
+use std::marker::PhantomData;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use futures::executor::{block_on, block_on_stream};
+use futures::io::{AsyncRead, AsyncWrite};
+use futures::{FutureExt, StreamExt};
+
+use serde_hjson::{AsyncStreamDeserializer, Deserializer, Value};
+
+/// Reads chunks of the input, and has none ready in between them, like a
+/// socket.
+struct Chunked {
+    chunks: VecDeque<Vec<u8>>,
+    ready: bool,
+}
+
+impl Chunked {
+    fn new(input: &str, size: usize) -> Self {
+        Chunked {
+            chunks: input.as_bytes().chunks(size).map(|chunk| chunk.to_vec()).collect(),
+            ready: false,
+        }
+    }
+}
+
+impl AsyncRead for Chunked {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        match self.chunks.pop_front() {
+            Some(mut chunk) => {
+                let len = chunk.len().min(buf.len());
+                buf[..len].copy_from_slice(&chunk[..len]);
+                if len < chunk.len() {
+                    self.chunks.push_front(chunk.split_off(len));
+                }
+                Poll::Ready(Ok(len))
+            }
+            None => Poll::Ready(Ok(0)),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct Message {
+    id: u64,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+    motd: String,
+}
+
+const CONFIG: &'static str = "\
+# The web server
+host: example.com
+port: 8080 // the default
+motd:
+  '''
+  Welcome
+  '''
+";
+
+#[test]
+fn test_from_async_reader() {
+    let expected = serde_hjson::from_str::<Server>(CONFIG).unwrap();
+    for &size in &[1, 2, 7, 1000] {
+        let server = block_on(serde_hjson::from_async_reader::<_, Server>(Chunked::new(CONFIG, size)));
+        assert_eq!(server.unwrap(), expected);
+    }
+
+    for &input in &[
+        "host: a\nport: 99999\nmotd: x",
+        "[1, 2",
+        "{} {}",
+        "host: a\nport: 1",
+        "{host: a, port: 99999, ]",
+        "{\"host\" ]",
+        "# only a comment\n",
+        "",
+    ] {
+        let expected = serde_hjson::from_reader::<_, Server>(input.as_bytes()).unwrap_err();
+        let err = block_on(serde_hjson::from_async_reader::<_, Server>(Chunked::new(input, 3))).unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string());
+    }
+}
+
+#[test]
+fn test_async_stream_deserializer() {
+    let input = "{\"a\": [1, 2]} \"b\" 3\n'''\n  multi\n  line\n  ''' true // comment\n[{}, null] quoteless\n";
+    let expected: Vec<_> = Deserializer::from_reader(input.as_bytes())
+        .into_iter::<Value>()
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(expected.len(), 7);
+
+    for &size in &[1, 2, 5, 1000] {
+        let stream = AsyncStreamDeserializer::<_, Value>::new(Chunked::new(input, size));
+        let values: Vec<_> = block_on_stream(stream).map(Result::unwrap).collect();
+        assert_eq!(values, expected);
+    }
+
+    // Errors are the same as with `from_reader`, also after the first line.
+    let input = "{id: 1}\n// two\n{id: -2}\n{id: 3}";
+    let expected: Vec<_> = Deserializer::from_reader(input.as_bytes())
+        .into_iter::<Message>()
+        .map(|result| result.map(|message| message.id).map_err(|err| err.to_string()))
+        .take(2)
+        .collect();
+    assert!(expected[1].is_err());
+    for &size in &[1, 3, 1000] {
+        let stream = AsyncStreamDeserializer::<_, Message>::new(Chunked::new(input, size));
+        let values: Vec<_> = block_on_stream(stream)
+            .map(|result| result.map(|message| message.id).map_err(|err| err.to_string()))
+            .collect();
+        assert_eq!(values, expected);
+    }
+}
+
+#[test]
+fn test_async_stream_deserializer_partial() {
+    // The stream ends after an error.
+    let mut stream = AsyncStreamDeserializer::<_, Value>::new(&b"{\"id\": 1}\n{\"id\" ]\n[2]"[..]);
+    assert_eq!(block_on(stream.next()).unwrap().unwrap(), json!({"id": 1}));
+    assert!(block_on(stream.next()).unwrap().is_err());
+    assert!(block_on(stream.next()).is_none());
+
+    let mut stream = AsyncStreamDeserializer::<_, Value>::new(Chunked::new("[1] [2", 4));
+    assert!(stream.next().now_or_never().is_none());
+    assert_eq!(block_on(stream.next()).unwrap().unwrap(), json!([1]));
+    assert_eq!(stream.byte_offset(), 3);
+    assert!(block_on(stream.next()).unwrap().unwrap_err().is_eof());
+}
+
+#[test]
+fn test_async_not_static() {
+    fn assert_send<T: Send>(_: &T) {}
+
+    // The value does not have to be `'static`.
+    #[derive(Deserialize)]
+    struct Message<'a> {
+        id: u64,
+        #[serde(skip)]
+        tag: PhantomData<&'a str>,
+    }
+    let input = String::from("{id: 1}");
+    let future = serde_hjson::from_async_reader::<_, Message>(input.as_bytes());
+    assert_send(&future);
+    let message = block_on(future).unwrap();
+    assert_eq!((message.id, message.tag), (1, PhantomData));
+
+    let stream = AsyncStreamDeserializer::<_, Message>::new(input.as_bytes());
+    assert_send(&stream);
+    assert_eq!(block_on_stream(stream).map(|message| message.unwrap().id).collect::<Vec<_>>(), [1]);
+}
+
+/// Repeats its input without end, a little at a time.
+struct Endless(&'static [u8], usize);
+
+impl AsyncRead for Endless {
+    fn poll_read(mut self: Pin<&mut Self>, _: &mut Context, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let index = self.1 % self.0.len();
+        buf[0] = self.0[index];
+        self.1 += 1;
+        Poll::Ready(Ok(1))
+    }
+}
+
+#[test]
+fn test_async_stream_deserializer_endless() {
+    let stream = AsyncStreamDeserializer::<_, Value>::new(Endless(b"[1, {a: 2}] ", 0));
+    let values: Vec<_> = block_on(stream.take(3).map(Result::unwrap).collect());
+    assert_eq!(values, [json!([1, {"a": 2}]), json!([1, {"a": 2}]), json!([1, {"a": 2}])]);
+}
+
+#[test]
+fn test_to_async_writer() {
+    let server = Server {
+        host: "example.com".to_owned(),
+        port: 8080,
+        motd: "Welcome".to_owned(),
+    };
+    let mut output = Vec::new();
+    block_on(serde_hjson::to_async_writer(&mut output, &server)).unwrap();
+    assert_eq!(output, serde_hjson::to_vec(&server).unwrap());
+
+    let values: Vec<_> = (0..5000).map(|i| format!("value {}", i)).collect();
+    let mut writer = Slow {
+        output: Vec::new(),
+        ready: false,
+    };
+    block_on(serde_hjson::to_async_writer(&mut writer, &values)).unwrap();
+    assert_eq!(writer.output, serde_hjson::to_vec(&values).unwrap());
+}
+
+/// Takes a little of the output at a time, and is not ready in between.
+struct Slow {
+    output: Vec<u8>,
+    ready: bool,
+}
+
+impl AsyncWrite for Slow {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<io::Result<usize>> {
+        if !self.ready {
+            self.ready = true;
+            cx.waker().wake_by_ref();
+            return Poll::Pending;
+        }
+        self.ready = false;
+        let len = buf.len().min(100);
+        self.output.extend_from_slice(&buf[..len]);
+        Poll::Ready(Ok(len))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _: &mut Context) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
@@ -1,13 +1,16 @@
 // #[macro_use]
 extern crate serde_hjson;
 extern crate difference;
+extern crate serde;
 extern crate regex;
 
 use std::fs::File;
 use std::io::prelude::*;
 use std::io;
 use std::path::Path;
-use serde_hjson::Value;
+use serde::Serialize;
+use serde_hjson::{Serializer, Value};
+use serde_hjson::ser::{Multiline, PrettyFormatter, Quotes};
 use regex::Regex;
 
 fn get_content(name: &str) -> io::Result<String> {
@@ -73,6 +76,24 @@ macro_rules! test {
                 }
             }
             #[test]
+            fn match_stringify_json() {
+                let name = stringify!($v);
+
+                let test_content = get_test_content(name).unwrap();
+                let data: Value = serde_hjson::from_str(&test_content).unwrap();
+
+                let (rjson, _) = get_result_content(name).unwrap();
+                let actual_json = serde_hjson::to_string_json_pretty(&data).unwrap();
+
+                if rjson != actual_json {
+                    println!("{}", difference::Changeset::new(&rjson, &actual_json, "\n"));
+                    println!("\nExpected:\n{:?}", rjson);
+                    println!("\nGot:\n{:?}", actual_json);
+
+                    panic!();
+                }
+            }
+            #[test]
             fn try_parse_io() {
                 let name = stringify!($v);
 
@@ -130,6 +151,24 @@ macro_rules! test {
                 }
             }
             #[test]
+            fn match_stringify_json() {
+                let name = stringify!($v);
+
+                let test_content = get_test_content(name).unwrap();
+                let data: Value = serde_hjson::from_str(&test_content).unwrap();
+
+                let (rjson, _) = get_result_content(name).unwrap();
+                let actual_json = $fix(serde_hjson::to_string_json_pretty(&data).unwrap());
+
+                if rjson != actual_json {
+                    println!("{}", difference::Changeset::new(&rjson, &actual_json, "\n"));
+                    println!("\nExpected:\n{:?}", rjson);
+                    println!("\nGot:\n{:?}", actual_json);
+
+                    panic!();
+                }
+            }
+            #[test]
             fn try_parse_io() {
                 let name = stringify!($v);
 
@@ -177,7 +216,7 @@ macro_rules! test_failure {
 
 fn std_fix(json: String) -> String {
     let re = Regex::new(r"(?m)(?P<d>\d)\.0(?P<x>,?)$").unwrap();
-    String::from(re.replace_all(&json, "$d$s"))
+    String::from(re.replace_all(&json, "$d$x"))
 }
 
 fn fix_pass1(json: String) -> String {
@@ -263,3 +302,54 @@ test!(stringify1);
 test!(strings);
 test!(strings2);
 test!(trail);
+
+fn match_stringify_with(name: &str, data: &Value, formatter: PrettyFormatter) {
+    let (_, rhjson) = get_result_content(name).unwrap();
+
+    let mut writer = Vec::new();
+    data.serialize(&mut Serializer::with_formatter(&mut writer, formatter)).unwrap();
+    let actual_hjson = String::from_utf8(writer).unwrap();
+
+    if rhjson != actual_hjson {
+        println!("{}", difference::Changeset::new(&rhjson, &actual_hjson, "\n"));
+        println!("\nExpected:\n{:?}", rhjson);
+        println!("\nGot:\n{:?}", actual_hjson);
+
+        panic!();
+    }
+}
+
+macro_rules! test_with {
+    ($v: ident, $name: expr, $formatter: expr) => {
+        #[test]
+        fn $v() {
+            let name = $name;
+
+            let test_content = get_test_content(name).unwrap();
+            let data: Value = serde_hjson::from_str(&test_content).unwrap();
+            match_stringify_with(name, &data, $formatter);
+
+            let test_file = get_test_content_file(name).unwrap();
+            let data: Value = serde_hjson::from_reader(test_file).unwrap();
+            match_stringify_with(name, &data, $formatter);
+        }
+    };
+}
+
+mod extra {
+    use super::*;
+
+    test_with!(notabs, "extra/notabs", PrettyFormatter::new().multiline(Multiline::NoTabs));
+    test_with!(root, "extra/root", PrettyFormatter::new());
+    test_with!(separator, "extra/separator", PrettyFormatter::new().separator(true));
+}
+
+mod stringify {
+    use super::*;
+
+    test_with!(quotes_all, "stringify/quotes_all", PrettyFormatter::new().quotes(Quotes::All));
+    test_with!(quotes_always, "stringify/quotes_always", PrettyFormatter::new().quotes(Quotes::Strings));
+    test_with!(quotes_keys, "stringify/quotes_keys", PrettyFormatter::new().quotes(Quotes::Keys));
+    test_with!(quotes_strings, "stringify/quotes_strings", PrettyFormatter::new().quotes(Quotes::Strings));
+    test_with!(quotes_strings_ml, "stringify/quotes_strings_ml", PrettyFormatter::new().quotes(Quotes::Strings));
+}
@@ -0,0 +1,928 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+#[macro_use]
+extern crate serde_hjson;
+
+use std::collections::BTreeMap;
+use std::fmt::Debug;
+use std::ops::Range;
+
+use serde::de::{Deserialize, IgnoredAny};
+use serde_hjson::{Deserializer, DuplicateKeys, Profile, Spanned, Value};
+use serde_hjson::error::ErrorKind;
+use serde_hjson::warning::WarningKind;
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config {
+    name: String,
+    database: Database,
+}
+
+#[test]
+fn test_parse_braceless_root_struct() {
+    let s = "
+        # a config file without root braces
+        name: example
+        database: {
+            host: 127.0.0.1
+            port: 5432
+        }
+    ";
+    let expected = Config {
+        name: "example".to_owned(),
+        database: Database {
+            host: "127.0.0.1".to_owned(),
+            port: 5432,
+        },
+    };
+
+    assert_eq!(serde_hjson::from_str::<Config>(s).unwrap(), expected);
+    assert_eq!(serde_hjson::from_slice::<Config>(s.as_bytes()).unwrap(), expected);
+    assert_eq!(serde_hjson::from_reader::<_, Config>(s.as_bytes()).unwrap(), expected);
+}
+
+#[test]
+fn test_parse_braceless_root_map() {
+    let map: BTreeMap<String, Value> = serde_hjson::from_str("'a b': 1, c: [true]\nd: text").unwrap();
+    assert_eq!(map.len(), 3);
+    assert_eq!(map["a b"], Value::from(1));
+    assert_eq!(map["c"], Value::Array(vec![Value::Bool(true)]));
+    assert_eq!(map["d"], Value::from("text"));
+
+    let map: BTreeMap<u32, String> = serde_hjson::from_str("1: one\n2: two").unwrap();
+    assert_eq!(map[&1], "one");
+    assert_eq!(map[&2], "two");
+}
+
+#[test]
+fn test_parse_root_values() {
+    assert_eq!(serde_hjson::from_str::<Value>("123").unwrap(), Value::from(123));
+    assert_eq!(serde_hjson::from_str::<Value>("true # yes").unwrap(), Value::Bool(true));
+    assert_eq!(serde_hjson::from_str::<Value>("null").unwrap(), Value::Null);
+    assert_eq!(serde_hjson::from_str::<Value>("\"text\"").unwrap(), Value::from("text"));
+    assert_eq!(
+        serde_hjson::from_str::<Value>("key word: text").unwrap(),
+        Value::from("key word: text")
+    );
+
+    // A single line that is not an object without braces is a string.
+    for &(s, ref expected) in &[
+        ("http://example.com", Value::from("http://example.com")),
+        ("http://example.com # home\n", Value::from("http://example.com # home")),
+        ("a:", Value::from("a:")),
+        ("a: 1}", Value::from("a: 1}")),
+        ("C:\\path", json!({"C": "\\path"})),
+        ("C:\\path // not a comment", json!({"C": "\\path // not a comment"})),
+    ] {
+        assert_eq!(serde_hjson::from_str::<Value>(s).unwrap(), *expected, "{:?}", s);
+        assert_eq!(serde_hjson::from_reader::<_, Value>(s.as_bytes()).unwrap(), *expected, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_parse_braceless_root_errors() {
+    assert!(serde_hjson::from_str::<Value>("a: 1}\nb: 2").is_err());
+    assert!(serde_hjson::from_str::<Value>("a: [1] b: 2\nc: 3").is_err());
+    assert!(serde_hjson::from_str::<Value>("a: 1,,b: 2\nc: 3").is_err());
+    assert!(serde_hjson::from_reader::<_, Value>(&b"a: 1}\nb: 2"[..]).is_err());
+}
+
+#[test]
+fn test_parse_comment_after_value() {
+    let expected: Value = serde_hjson::from_str("{\"a\": \"x\", \"b\": [1]}").unwrap();
+    assert_eq!(
+        serde_hjson::from_str::<Value>("{\n  a: \"x\" // note\n  b: [1] # note\n}").unwrap(),
+        expected
+    );
+    assert_eq!(
+        serde_hjson::from_str::<Value>("a: '''x''' /* note */ // note\nb: [1]").unwrap(),
+        expected
+    );
+}
+
+fn test_profile_err(profile: Profile, errors: &[(&str, &str)]) {
+    for &(s, err) in errors {
+        let from_str = serde_hjson::from_str_with::<Value>(s, profile).unwrap_err().to_string();
+        let from_slice = serde_hjson::from_slice_with::<Value>(s.as_bytes(), profile).unwrap_err().to_string();
+        assert_eq!(from_slice, from_str);
+        // IoRead reports slightly different columns, so only compare the message.
+        let from_reader = serde_hjson::from_reader_with::<_, Value>(s.as_bytes(), profile).unwrap_err().to_string();
+        assert_eq!(from_reader.split(" at ").next(), from_str.split(" at ").next());
+        assert_eq!(from_str, err, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_parse_json_profile() {
+    let json = Profile::json();
+    let s = r#"{"a": [1, 2.5, "x", true, null], "b": {}}"#;
+    assert_eq!(
+        serde_hjson::from_str_with::<Value>(s, json).unwrap(),
+        serde_hjson::from_str::<Value>(s).unwrap()
+    );
+    assert_eq!(serde_hjson::from_str_with::<Value>(" 1 ", json).unwrap(), Value::from(1));
+    assert_eq!(serde_hjson::from_str_with::<Value>("\"\"", json).unwrap(), Value::from(""));
+
+    test_profile_err(json, &[
+        ("[1] # one", "comments are not allowed at line 1 column 5"),
+        ("[1 /* one */]", "comments are not allowed at line 1 column 5"),
+        ("[abc]", "quoteless strings are not allowed at line 1 column 2"),
+        ("{a: 1}", "quoteless strings are not allowed at line 1 column 2"),
+        ("a: 1", "quoteless strings are not allowed at line 1 column 1"),
+        ("['a']", "single quoted strings are not allowed at line 1 column 2"),
+        ("{'a': 1}", "single quoted strings are not allowed at line 1 column 2"),
+        ("['''a''']", "multiline strings are not allowed at line 1 column 2"),
+        ("[1\n2]", "expected `,` between values at line 2 column 1"),
+        ("{\"a\": 1\n\"b\": 2}", "expected `,` between values at line 2 column 1"),
+        ("[1, 2,]", "trailing commas are not allowed at line 1 column 7"),
+        ("{\"a\": 1,}", "trailing commas are not allowed at line 1 column 9"),
+    ]);
+}
+
+#[test]
+fn test_parse_jsonc_profile() {
+    let jsonc = Profile::jsonc();
+    let s = "{\n  // comment\n  \"a\": [1, 2,], /* b */\n}";
+    let value = serde_hjson::from_str_with::<Value>(s, jsonc).unwrap();
+    assert_eq!(value["a"], Value::Array(vec![Value::from(1), Value::from(2)]));
+
+    test_profile_err(jsonc, &[
+        ("[a]", "quoteless strings are not allowed at line 1 column 2"),
+        ("[1\n2]", "expected `,` between values at line 2 column 1"),
+    ]);
+}
+
+#[test]
+fn test_parse_custom_profile() {
+    let profile = Profile { multiline_strings: false, ..Profile::hjson() };
+    assert_eq!(
+        serde_hjson::from_str_with::<Value>("a: text\nb: 'x'", profile).unwrap()["b"],
+        Value::from("x")
+    );
+    test_profile_err(profile, &[("a: '''x'''", "multiline strings are not allowed at line 1 column 4")]);
+
+    let profile = Profile { omitted_root_braces: false, ..Profile::hjson() };
+    assert_eq!(serde_hjson::from_str_with::<Value>("a: 1", profile).unwrap(), Value::from("a: 1"));
+}
+
+fn nested(depth: usize) -> String {
+    let mut s = String::new();
+    for _ in 0..depth {
+        s.push('[');
+    }
+    for _ in 0..depth {
+        s.push(']');
+    }
+    s
+}
+
+fn test_parse_nested<'a, T>(cases: &[(&'a str, Option<usize>, bool)])
+where
+    T: Deserialize<'a>,
+{
+    for &(s, limit, ok) in cases {
+        let mut de = Deserializer::from_str(s);
+        match limit {
+            Some(limit) => de.set_recursion_limit(limit),
+            None => de.disable_recursion_limit(),
+        }
+        let result = T::deserialize(&mut de).and_then(|_| de.end());
+        assert_eq!(result.is_ok(), ok, "{:?} with limit {:?}", s, limit);
+    }
+}
+
+#[test]
+fn test_recursion_limit() {
+    assert!(serde_hjson::from_str::<Value>(&nested(128)).is_ok());
+    assert_eq!(
+        serde_hjson::from_str::<Value>(&nested(129)).unwrap_err().to_string(),
+        "recursion limit exceeded at line 1 column 129"
+    );
+
+    test_parse_nested::<Value>(&[
+        (&nested(2), Some(2), true),
+        (&nested(3), Some(2), false),
+        ("a: {b: [1]}", Some(3), true),
+        ("a: {b: [1]}", Some(2), false),
+        ("1", Some(0), true),
+        ("[]", Some(0), false),
+        (&nested(300), Some(300), true),
+        (&nested(300), None, true),
+    ]);
+    test_parse_nested::<IgnoredAny>(&[(&nested(3), Some(2), false)]);
+}
+
+#[cfg(feature = "unbounded_depth")]
+#[test]
+fn test_disable_recursion_limit() {
+    // Dropping a `Value` this deep would overflow the stack by itself.
+    test_parse_nested::<IgnoredAny>(&[(&nested(100_000), None, true)]);
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Server {
+    host: String,
+    port: u16,
+}
+
+fn test_parse_duplicates<'a, T>(policy: DuplicateKeys, cases: &[(&'a str, Result<T, &str>)])
+where
+    T: Deserialize<'a> + PartialEq + Debug,
+{
+    for &(s, ref expected) in cases {
+        let mut de = Deserializer::from_str(s);
+        de.set_duplicate_keys(policy);
+        let result = T::deserialize(&mut de).and_then(|value| de.end().map(|()| value));
+        match *expected {
+            Ok(ref expected) => assert_eq!(result.unwrap(), *expected, "{:?}", s),
+            Err(expected) => assert_eq!(result.unwrap_err().to_string(), expected, "{:?}", s),
+        }
+    }
+}
+
+const DUPLICATES: &'static str = "
+{
+  host: example.com
+  port: 80
+  port: 8080
+}";
+
+#[test]
+fn test_duplicate_keys_default() {
+    let map: BTreeMap<String, Value> = serde_hjson::from_str(DUPLICATES).unwrap();
+    assert_eq!(map["port"], Value::from(8080));
+    assert!(serde_hjson::from_str::<Server>(DUPLICATES).is_err());
+}
+
+#[test]
+fn test_duplicate_keys_error() {
+    let expected = "duplicate key `port` (first defined at line 4 column 3) at line 5 column 3";
+    test_parse_duplicates::<Server>(DuplicateKeys::Error, &[(DUPLICATES, Err(expected))]);
+    let s = r#"{"a": {"a": 1}, "b": [{"a": 2}]}"#;
+    test_parse_duplicates::<Value>(DuplicateKeys::Error, &[
+        (DUPLICATES, Err(expected)),
+        ("a: 1\n'a': 2", Err("duplicate key `a` (first defined at line 1 column 1) at line 2 column 1")),
+        (s, Ok(json!({"a": {"a": 1}, "b": [{"a": 2}]}))),
+    ]);
+}
+
+#[test]
+fn test_duplicate_keys_keep_first() {
+    test_parse_duplicates::<Server>(DuplicateKeys::KeepFirst, &[
+        (DUPLICATES, Ok(Server { host: "example.com".to_owned(), port: 80 })),
+    ]);
+    test_parse_duplicates::<Value>(DuplicateKeys::KeepFirst, &[
+        ("a: 1\nb: 2\na: {c: [3]}\nb: 4", Ok(json!({"a": 1, "b": 2}))),
+    ]);
+}
+
+#[test]
+fn test_duplicate_keys_keep_last() {
+    test_parse_duplicates::<Server>(DuplicateKeys::KeepLast, &[
+        (DUPLICATES, Ok(Server { host: "example.com".to_owned(), port: 8080 })),
+    ]);
+    test_parse_duplicates::<BTreeMap<u32, u32>>(DuplicateKeys::KeepLast, &[
+        ("{1: 1, 2: 2, 1: 3}", Ok(vec![(1, 3), (2, 2)].into_iter().collect())),
+    ]);
+}
+
+#[test]
+fn test_duplicate_keys_collect() {
+    test_parse_duplicates::<Value>(DuplicateKeys::Collect, &[
+        (DUPLICATES, Ok(json!({"host": "example.com", "port": [80, 8080]}))),
+        ("a: [1]\na: {b: 2, b: 3}", Ok(json!({"a": [[1], {"b": [2, 3]}]}))),
+    ]);
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Hosts {
+        host: Vec<String>,
+    }
+    test_parse_duplicates::<Hosts>(DuplicateKeys::Collect, &[
+        ("host: a\nhost: b", Ok(Hosts { host: vec!["a".to_owned(), "b".to_owned()] })),
+    ]);
+}
+
+#[derive(Debug, Deserialize)]
+struct SpannedConfig {
+    name: Spanned<String>,
+    port: Spanned<u16>,
+    hosts: Spanned<Vec<String>>,
+    debug: Option<Spanned<bool>>,
+    notes: Spanned<String>,
+}
+
+fn spans(config: &SpannedConfig) -> Vec<(usize, usize, Range<usize>)> {
+    vec![
+        (config.name.line(), config.name.column(), config.name.span()),
+        (config.port.line(), config.port.column(), config.port.span()),
+        (config.hosts.line(), config.hosts.column(), config.hosts.span()),
+        (config.notes.line(), config.notes.column(), config.notes.span()),
+    ]
+}
+
+#[test]
+fn test_spanned() {
+    let s = "{
+  name: quoteless name  
+  port: 8080 # comment
+  hosts: [\"a\", 'b']
+  debug: true
+  notes:
+    '''
+    first
+    second
+    '''
+}";
+    let from_str: SpannedConfig = serde_hjson::from_str(s).unwrap();
+    let from_slice: SpannedConfig = serde_hjson::from_slice(s.as_bytes()).unwrap();
+    let from_reader: SpannedConfig = serde_hjson::from_reader(s.as_bytes()).unwrap();
+
+    let expected = spans(&from_str);
+    assert_eq!(spans(&from_slice), expected);
+    assert_eq!(spans(&from_reader), expected);
+
+    let texts: Vec<_> = expected
+        .into_iter()
+        .map(|(line, column, span)| (line, column, &s[span]))
+        .collect();
+    assert_eq!(
+        texts,
+        vec![
+            (2, 9, "quoteless name"),
+            (3, 9, "8080"),
+            (4, 10, "[\"a\", 'b']"),
+            (7, 5, "'''\n    first\n    second\n    '''"),
+        ]
+    );
+    assert_eq!(from_str.name.get_ref(), "quoteless name");
+    assert_eq!(*from_str.port.get_ref(), 8080);
+    assert_eq!(from_str.debug.map(Spanned::into_inner), Some(true));
+    assert_eq!(from_str.notes.into_inner(), "first\nsecond");
+}
+
+#[test]
+fn test_spanned_root() {
+    let s = "\n# comment\nname: x\nport: 1\nhosts: []\nnotes: y \n";
+    let config: Spanned<SpannedConfig> = serde_hjson::from_str(s).unwrap();
+    assert_eq!((config.line(), config.column()), (3, 1));
+    assert_eq!(&s[config.span()], "name: x\nport: 1\nhosts: []\nnotes: y");
+
+    let value: Spanned<Value> = serde_hjson::from_str("  [1, 2] ").unwrap();
+    assert_eq!(value.span(), 2..8);
+    assert_eq!(value.into_inner(), Value::Array(vec![Value::from(1), Value::from(2)]));
+
+    assert!(serde_hjson::from_value::<Spanned<u16>>(Value::from(1)).is_err());
+}
+
+#[derive(Debug, Deserialize)]
+struct Tls {
+    #[allow(dead_code)]
+    port: u16,
+}
+
+#[derive(Debug, Deserialize)]
+struct TlsServer {
+    #[allow(dead_code)]
+    host: String,
+    #[allow(dead_code)]
+    tls: Option<Tls>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Servers {
+    #[allow(dead_code)]
+    servers: Vec<TlsServer>,
+}
+
+#[derive(Debug, Deserialize)]
+enum Action {
+    Exec(String),
+    Listen { host: String, port: u16 },
+    Pair(u8, u8),
+}
+
+fn test_error_paths<'a, T>(errors: &[(&'a str, Option<&str>, &str)])
+where
+    T: Deserialize<'a> + Debug,
+{
+    for &(s, path, message) in errors {
+        let err = serde_hjson::from_str::<T>(s).unwrap_err();
+        assert_eq!(err.path(), path, "{:?}", s);
+        assert_eq!(err.to_string(), message, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_error_path() {
+    let s = r#"
+servers: [
+  {host: "a"}
+  {host: "b", tls: {port: 443}}
+  {
+    host: c
+    tls: {
+      port: https
+    }
+  }
+]"#;
+    test_error_paths::<Servers>(&[
+        (
+            s,
+            Some("servers[2].tls.port"),
+            "servers[2].tls.port: invalid type: string \"https\", expected u16 at line 8 column 17",
+        ),
+        (
+            "servers: [{\"tls\": {\"port\": 1}}]",
+            Some("servers[0]"),
+            "servers[0]: missing field `host` at line 1 column 30",
+        ),
+        // Errors outside of any object or array have no path.
+        ("x", None, "invalid type: string \"x\", expected struct Servers at line 1 column 1"),
+        (
+            "servers: [{host: \"a\",,}]",
+            None,
+            "invalid type: string \"servers: [{host: \\\"a\\\",,}]\", expected struct Servers \
+             at line 1 column 24",
+        ),
+        // Syntax errors have a path too, but only data errors show it.
+        (
+            "servers: [\n  {host: \"a\"}\n  {host: \"b\\q\"}\n]",
+            Some("servers[1].host"),
+            "invalid escape at line 3 column 13",
+        ),
+        (
+            "{servers: [{host: \"a\", tls: {port: 1,,}}]}",
+            Some("servers[0].tls"),
+            "extra comma at line 1 column 38",
+        ),
+        (
+            "{servers: [{host: \"a\"}, {tls: {port: 1 x: 2}}]}",
+            Some("servers[1].tls.port"),
+            "unexpected character at line 1 column 39",
+        ),
+    ]);
+
+    // The path is the same when the members are read ahead of time.
+    let mut de = Deserializer::from_str(s);
+    de.set_duplicate_keys(DuplicateKeys::KeepLast);
+    let err = Servers::deserialize(&mut de).unwrap_err();
+    assert_eq!(err.path(), Some("servers[2].tls.port"));
+
+    test_error_paths::<BTreeMap<String, Vec<u8>>>(&[(
+        "{\"odd key\": [1, 2, 300]}",
+        Some("[\"odd key\"][2]"),
+        "[\"odd key\"][2]: invalid value: integer `300`, expected u8 at line 1 column 22",
+    )]);
+    test_error_paths::<BTreeMap<String, Servers>>(&[(
+        "{\"odd key\": {\"servers\": 1}}",
+        Some("[\"odd key\"].servers"),
+        "[\"odd key\"].servers: invalid type: integer `1`, expected a sequence at line 1 column 25",
+    )]);
+    test_error_paths::<Vec<Action>>(&[
+        (
+            "[{Exec: \"ls\"}, {Listen: {\"host\": \"a\", \"port\": -1}}]",
+            Some("[1].Listen.port"),
+            "[1].Listen.port: invalid value: integer `-1`, expected u16 at line 1 column 48",
+        ),
+        (
+            "[{Pair: [1, 256]}]",
+            Some("[0].Pair[1]"),
+            "[0].Pair[1]: invalid value: integer `256`, expected u8 at line 1 column 15",
+        ),
+        (
+            "[{Move: 1}]",
+            Some("[0]"),
+            "[0]: unknown variant `Move`, expected one of `Exec`, `Listen`, `Pair` at line 1 column 6",
+        ),
+    ]);
+}
+
+#[test]
+fn test_error_kind() {
+    let kinds: Vec<ErrorKind> = (0..1000).filter_map(ErrorKind::from_code).collect();
+    assert_eq!(kinds.len(), 34);
+    for kind in kinds {
+        assert_eq!(ErrorKind::from_code(kind.code()), Some(kind));
+        assert!(kind.explanation().ends_with('.'));
+    }
+
+    let kind = |s: &str, profile: Profile| {
+        serde_hjson::from_str_with::<Value>(s, profile).unwrap_err().kind()
+    };
+    assert_eq!(kind("[1, 2", Profile::hjson()), ErrorKind::EofWhileParsingList);
+    assert_eq!(kind("a: 'b\nc'", Profile::hjson()), ErrorKind::UnexpectedNewline);
+    assert_eq!(kind("{a: 1} x", Profile::hjson()), ErrorKind::TrailingCharacters);
+    assert_eq!(kind("[1, 2,]", Profile::json()), ErrorKind::TrailingCommaNotAllowed);
+    assert_eq!(kind("[1 // one\n]", Profile::json()), ErrorKind::CommentNotAllowed);
+
+    let err = serde_hjson::from_str::<Server>("host: a\nport: b").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::Message);
+    assert_eq!(err.kind().code(), 1);
+    assert!(err.is_data());
+}
+
+fn test_error_ranges<T>(errors: &[(&str, &str)])
+where
+    T: serde::de::DeserializeOwned + Debug,
+{
+    for &(s, expected) in errors {
+        let err = serde_hjson::from_str::<T>(s).unwrap_err();
+        let range = err.byte_range().unwrap();
+        let from_reader = serde_hjson::from_reader::<_, T>(s.as_bytes()).unwrap_err();
+        assert_eq!(from_reader.byte_range(), Some(range.clone()));
+        assert_eq!(&s[range], expected, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_error_columns() {
+    let err = serde_hjson::from_str::<Value>("{\"größe\" 1}").unwrap_err();
+    assert_eq!((err.line(), err.column()), (1, 12));
+    assert_eq!((err.char_column(), err.utf16_column()), (10, 10));
+
+    let err = serde_hjson::from_str::<Value>("{\"😀\" 1}").unwrap_err();
+    assert_eq!((err.column(), err.char_column(), err.utf16_column()), (9, 6, 7));
+
+    let err = serde_hjson::from_str::<Value>("a: 1\nb: \"ü\\q\"").unwrap_err();
+    assert_eq!((err.line(), err.column(), err.char_column()), (2, 8, 7));
+
+    // The range spans the offending value or character.
+    test_error_ranges::<Value>(&[
+        ("[1, 2,,]", ","),
+        ("{\"a\": \"\\q\"}", "q"),
+        ("[1] é", "é"),
+        ("[1, 2", ""),
+    ]);
+    test_error_ranges::<Server>(&[
+        ("host: a\nport: 99999  # comment", "99999"),
+        ("host: a\nport: höhe", "höhe"),
+        ("host: a\nport: {}", "{}"),
+        ("{\n  host: a\n}", "{\n  host: a\n}"),
+        ("host: a", "host: a"),
+    ]);
+    test_error_ranges::<Vec<u8>>(&[("[1, 2, 300]", "300")]);
+
+    // Control characters in strings after non-ASCII text, at the same place
+    // whatever the input is read from.
+    for &(s, columns) in &[
+        ("{a: \"é\nb\"}", (8, 7, 7)),
+        ("{a: 'é\nb'}", (8, 7, 7)),
+        ("[\"😀\u{1}\"]", (7, 4, 5)),
+        ("['😀\u{1}']", (7, 4, 5)),
+        ("host: a\nport: 1\nmotd: \"é\nb\"", (10, 9, 9)),
+    ] {
+        let err = serde_hjson::from_str::<Server>(s).unwrap_err();
+        let from_reader = serde_hjson::from_reader::<_, Server>(s.as_bytes()).unwrap_err();
+        assert_eq!(from_reader.byte_range(), err.byte_range());
+        assert_eq!((err.column(), err.char_column(), err.utf16_column()), columns, "{:?}", s);
+        assert_eq!(
+            (from_reader.line(), from_reader.column(), from_reader.char_column(), from_reader.utf16_column()),
+            (err.line(), err.column(), err.char_column(), err.utf16_column())
+        );
+        test_error_ranges::<Value>(&[(s, &s[from_reader.byte_range().unwrap()])]);
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Listener {
+    #[allow(dead_code)]
+    host: String,
+    #[allow(dead_code)]
+    port: u16,
+}
+
+#[test]
+fn test_unknown_field_suggestion() {
+    let s = "host: a\nprot: 1";
+    let err = serde_hjson::from_str::<Listener>(s).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownField);
+    assert!(err.is_data());
+    assert_eq!(err.suggestion(), Some("port"));
+    assert_eq!(&s[err.byte_range().unwrap()], "prot");
+    assert_eq!(
+        err.to_string(),
+        "unknown field `prot`, expected `host` or `port` (did you mean `port`?) \
+         at line 2 column 4"
+    );
+
+    test_error_ranges::<Listener>(&[
+        ("{\n  'Port': 1\n  host: a\n}", "'Port'"),
+        ("{\"prot\": 1, host: a\n}", "\"prot\""),
+        ("\"prot\": 1", "\"prot\""),
+    ]);
+
+    // Names that are too different get no suggestion.
+    let err = serde_hjson::from_str::<Listener>("hostname: a").unwrap_err();
+    assert_eq!(err.suggestion(), None);
+    assert_eq!(
+        err.to_string(),
+        "unknown field `hostname`, expected `host` or `port` at line 1 column 8"
+    );
+
+    let err = serde_hjson::from_str::<Vec<Action>>("[{Exce: \"ls\"}]").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownVariant);
+    assert_eq!(err.suggestion(), Some("Exec"));
+    assert_eq!(err.path(), Some("[0]"));
+    test_error_ranges::<Vec<Action>>(&[("[\"Piar\"]", "\"Piar\"")]);
+
+    // Unit variants are usually quoteless.
+    #[derive(Debug, PartialEq, Deserialize)]
+    enum Level {
+        Debug,
+        Info,
+        Warn,
+    }
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Logging {
+        level: Level,
+    }
+    assert_eq!(
+        serde_hjson::from_str::<Logging>("level: Info").unwrap(),
+        Logging { level: Level::Info }
+    );
+    assert_eq!(
+        serde_hjson::from_str::<Vec<Level>>("[\n  Debug\n  'Warn'\n]").unwrap(),
+        [Level::Debug, Level::Warn]
+    );
+    let err = serde_hjson::from_str::<Logging>("level: Wran").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::UnknownVariant);
+    assert_eq!(err.suggestion(), Some("Warn"));
+    assert_eq!(err.path(), Some("level"));
+    assert_eq!(
+        err.to_string(),
+        "level: unknown variant `Wran`, expected one of `Debug`, `Info`, `Warn` (did you mean `Warn`?) \
+         at line 1 column 11"
+    );
+    test_error_ranges::<Logging>(&[("level: Wran", "Wran"), ("{\n  level: Inof\n}", "Inof")]);
+}
+
+fn test_parse_ignored<T>(
+    policy: Option<DuplicateKeys>,
+    cases: &[(&str, &[(&str, &str, usize, usize)])],
+)
+where
+    T: serde::de::DeserializeOwned,
+{
+    for &(s, expected) in cases {
+        let mut from_str = Vec::new();
+        match policy {
+            Some(policy) => {
+                let mut de = Deserializer::from_str(s);
+                de.set_duplicate_keys(policy);
+                de.collect_ignored();
+                T::deserialize(&mut de).unwrap();
+                for (path, span) in de.take_ignored() {
+                    from_str.push((path, &s[span.start..span.end], span.line, span.column));
+                }
+            }
+            None => {
+                serde_hjson::from_str_with_ignored::<T, _>(s, |path, span| {
+                    from_str.push((path.to_owned(), &s[span.start..span.end], span.line, span.column));
+                }).unwrap();
+                let mut from_reader = Vec::new();
+                serde_hjson::from_reader_with_ignored::<_, T, _>(s.as_bytes(), |path, span| {
+                    from_reader.push((path.to_owned(), &s[span.start..span.end], span.line, span.column));
+                }).unwrap();
+                assert_eq!(from_reader, from_str);
+            }
+        }
+        let from_str: Vec<_> = from_str
+            .iter()
+            .map(|&(ref path, name, line, column)| (&**path, name, line, column))
+            .collect();
+        assert_eq!(from_str, expected, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_ignored() {
+    let s = r#"
+name: app
+database: {
+  host: db
+  port: 5432
+  "pool size": 4
+}
+logging: {level: "info"}
+version: 1.0.0
+mode: fast"#;
+    test_parse_ignored::<Config>(None, &[(s, &[
+        ("database[\"pool size\"]", "\"pool size\"", 6, 3),
+        ("logging", "logging", 8, 1),
+        ("version", "version", 9, 1),
+        ("mode", "mode", 10, 1),
+    ])]);
+
+    let s = r#"{
+  servers: [
+    {host: "a", tls: {port: 443, cert: "a.pem"}}
+    {host: "b", weight: 2}
+  ]
+}"#;
+    test_parse_ignored::<Servers>(None, &[(s, &[
+        ("servers[0].tls.cert", "cert", 3, 34),
+        ("servers[1].weight", "weight", 4, 17),
+    ])]);
+    test_parse_ignored::<Vec<Action>>(None, &[
+        ("[{Listen: {host: \"a\", port: 1, backlog: 8}}]", &[("[0].Listen.backlog", "backlog", 1, 32)]),
+    ]);
+
+    // Members that are deserialized are not reported, whatever their type.
+    test_parse_ignored::<Value>(None, &[(s, &[])]);
+    test_parse_ignored::<BTreeMap<String, Vec<Value>>>(None, &[(s, &[])]);
+
+    let mut de = Deserializer::from_str("host: a\nport: 1\nextra: [1]");
+    let _ = Server::deserialize(&mut de).unwrap();
+    assert!(de.ignored().is_empty());
+
+    // Members of objects read ahead of time for the duplicate key policies
+    // are reported when they are skipped as well.
+    let s = "a: {host: 'x', port: 1, extra: [2]}\nb: {host: 'y', port: 2}";
+    for &policy in &[DuplicateKeys::KeepLast, DuplicateKeys::Collect] {
+        test_parse_ignored::<Server>(Some(policy), &[
+            (
+                "host: a\nextra: {x: 1}\nport: 1\nextra: 2\nnested: [{a: 1, b: 2}]",
+                &[("extra", "extra", 4, 1), ("nested", "nested", 5, 1)],
+            ),
+        ]);
+        test_parse_ignored::<Vec<Server>>(Some(policy), &[
+            ("[{host: 'a', port: 1, extra: 3}]", &[("[0].extra", "extra", 1, 23)]),
+        ]);
+        test_parse_ignored::<BTreeMap<String, Server>>(Some(policy), &[(s, &[("a.extra", "extra", 1, 25)])]);
+        test_parse_ignored::<BTreeMap<String, Value>>(Some(policy), &[(s, &[])]);
+    }
+
+    // Skipped values are read like kept ones, so a word that is not a literal
+    // or a number is the start of a quoteless string.
+    for value in &["1.0.0", "true story", "-foo", "01", "1.", "nullable", "1 # one", "[1.0.0\n-x\n2]"] {
+        let s = format!("host: a\nextra: {}\nport: 1", value);
+        test_parse_ignored::<Server>(None, &[(&s, &[("extra", "extra", 2, 1)])]);
+    }
+}
+
+fn test_parse_recovering(cases: &[(&str, Value, &[&str], &[&str])]) {
+    for &(s, ref value, errors, placeholders) in cases {
+        let recovered = serde_hjson::from_str_recovering(s);
+        let from_slice = serde_hjson::from_slice_recovering(s.as_bytes());
+        let from_reader = serde_hjson::from_reader_recovering(s.as_bytes());
+        assert_eq!(from_slice.value, recovered.value);
+        assert_eq!(from_reader.value, recovered.value);
+        assert_eq!(from_reader.placeholders, recovered.placeholders);
+        let recovered_errors: Vec<_> = recovered.errors.iter().map(ToString::to_string).collect();
+        assert_eq!(recovered_errors, errors, "{:?}", s);
+        assert_eq!(recovered.placeholders, placeholders, "{:?}", s);
+        assert_eq!(recovered.value, *value, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_recovering() {
+    test_parse_recovering(&[
+        (
+            "{
+  servers: [
+    {host: 'a', port: 80,,}
+    {host: 'b
+    port: 443}
+  ]
+  debug: true
+}",
+            json!({"servers": [{"host": "a", "port": 80}, {"host": null, "port": 443}], "debug": true}),
+            &["extra comma at line 3 column 26", "unexpected newline character at line 4 column 14"],
+            &["/servers/1/host"],
+        ),
+        // Placeholders can be told apart from nulls in the input.
+        (
+            "a: null\nb: [null, 'x\n]\nc/~: \"\\q\"\nc/~: 1\nd: [1, {e: ",
+            json!({"a": null, "b": [null, null], "c/~": 1, "d": [1, {"e": null}]}),
+            &[
+                "unexpected newline character at line 2 column 13",
+                "invalid escape at line 4 column 8",
+                "EOF while parsing a value at line 6 column 11",
+                "EOF while parsing an object at line 6 column 11",
+                "EOF while parsing a list at line 6 column 11",
+            ],
+            &["/b/1", "/d/1/e"],
+        ),
+        // Root objects without braces, stray closing brackets and EOF.
+        (
+            "a: 1}\nb: {x: 1]\n",
+            json!({"a": 1, "b": {"x": 1}}),
+            &[
+                "trailing characters at line 1 column 5",
+                "expected `,` or `}` at line 2 column 9",
+                "EOF while parsing an object at line 3 column 0",
+            ],
+            &[],
+        ),
+        ("[1, 2] x", json!([1, 2]), &["trailing characters at line 1 column 8"], &[]),
+        ("{a: [1, 2], b: 'c'}", json!({"a": [1, 2], "b": "c"}), &[], &[]),
+        ("a: 1\nb: [true]", json!({"a": 1, "b": [true]}), &[], &[]),
+        ("\"str\"", json!("str"), &[], &[]),
+        ("hello world", json!("hello world"), &[], &[]),
+        ("[]", json!([]), &[], &[]),
+    ]);
+}
+
+#[test]
+fn test_recovering_agrees() {
+    // Valid input gives the same value and no errors, and invalid input at
+    // least one error.
+    for s in &[
+        "1:/\r",
+        "\ta:/\r\n]",
+        "a: /\nb: 1",
+        "a: / x",
+        "a: /# x",
+        "[/\n1]",
+        "[}\n]",
+        "[1\n}a\n]",
+        "[1, }]",
+        "{]1: 1}",
+        "{a: 1, ]b: 2}",
+        "[1 }",
+        "{a: [1, 2}",
+        "{a: 1 ]}",
+        "a: 1\n]",
+    ] {
+        let recovered = serde_hjson::from_str_recovering(s);
+        match serde_hjson::from_str::<Value>(s) {
+            Ok(value) => {
+                assert_eq!(recovered.errors.len(), 0, "{:?}", s);
+                assert_eq!(recovered.value, value, "{:?}", s);
+            }
+            Err(_) => assert!(!recovered.errors.is_empty(), "{:?}", s),
+        }
+    }
+}
+
+fn test_parse_warnings<T>(cases: &[(&str, &[(WarningKind, usize, usize)])])
+where
+    T: serde::de::DeserializeOwned,
+{
+    for &(s, expected) in cases {
+        let (_, from_str) = serde_hjson::from_str_with_warnings::<T>(s).unwrap();
+        let (_, from_reader) = serde_hjson::from_reader_with_warnings::<_, T>(s.as_bytes()).unwrap();
+        assert_eq!(from_reader, from_str);
+        let from_str: Vec<_> = from_str
+            .into_iter()
+            .map(|warning| (warning.kind().clone(), warning.line(), warning.column()))
+            .collect();
+        assert_eq!(from_str, expected, "{:?}", s);
+    }
+}
+
+#[test]
+fn test_warnings() {
+    let s = "
+name: 2.0 beta   
+database: {
+  host: db  # primary
+  port: 5432
+  Host: db2
+}
+notes:
+  \t\t'''
+  \t\tfirst
+  \t\tsecond
+  \t\t'''
+url: http://example.com
+";
+    test_parse_warnings::<Value>(&[
+        (s, &[
+            (WarningKind::QuotelessLiteral, 2, 7),
+            (WarningKind::TrailingWhitespace, 2, 15),
+            (WarningKind::CommentInQuotelessString, 4, 13),
+            (WarningKind::KeyCaseMismatch("host".to_owned()), 6, 3),
+            (WarningKind::TabInMultilineIndent, 10, 3),
+        ]),
+        ("true story", &[(WarningKind::QuotelessLiteral, 1, 1)]),
+        ("a: tomato\nb: trueish\nc: C# and F#", &[]),
+        ("{a: 1, a: 2}", &[]),
+        ("[-foo\n\"1.0.0\"]", &[]),
+    ]);
+
+    // Quoteless strings are expected where a struct has a string.
+    test_parse_warnings::<Config>(&[("name: 1.0\ndatabase: {\nhost: true story\nport: 1\n}", &[])]);
+
+    let mut de = Deserializer::from_str("a: 1.0.0");
+    let _ = Value::deserialize(&mut de).unwrap();
+    assert!(de.warnings().is_empty());
+
+    // Strings are still borrowed from the input.
+    let s = "\"name\": 2.0 beta   \n\"quote\": 'x # y'\n\"notes\": '''text'''";
+    let (map, warnings) = serde_hjson::from_str_with_warnings::<BTreeMap<&str, &str>>(s).unwrap();
+    assert_eq!(map["name"], "2.0 beta");
+    assert_eq!(map["quote"], "x # y");
+    assert_eq!(map["notes"], "text");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(*warnings[0].kind(), WarningKind::TrailingWhitespace);
+}
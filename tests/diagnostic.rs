@@ -0,0 +1,113 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_hjson;
+
+use serde_hjson::Value;
+use serde_hjson::diagnostic::Diagnostic;
+
+#[derive(Debug, Deserialize)]
+struct Server {
+    #[allow(dead_code)]
+    host: String,
+    #[allow(dead_code)]
+    port: u16,
+}
+
+fn render<T>(s: &str) -> String
+where
+    T: serde::de::DeserializeOwned + std::fmt::Debug,
+{
+    let err = serde_hjson::from_str::<T>(s).unwrap_err();
+    Diagnostic::new(&err, s).file_name("config.hjson").to_string()
+}
+
+#[test]
+fn test_diagnostic_syntax_error() {
+    assert_eq!(
+        render::<Value>("# servers\n[\n  {host: \"a\"}\n  {host: \"b\" port: 80}\n]"),
+        "\
+error[E0201]: expected `,` or `]`
+ --> config.hjson:4:14
+  |
+4 |   {host: \"b\" port: 80}
+  |              ^
+"
+    );
+
+    // Tabs are expanded in the source line and the underline alike.
+    assert_eq!(
+        render::<Value>("a: [\n\t1,,\n]"),
+        "\
+error[E0213]: extra comma
+ --> config.hjson:2:4
+  |
+2 |     1,,
+  |       ^
+"
+    );
+}
+
+#[test]
+fn test_diagnostic_data_error() {
+    let s = "host: example.com\nport: eighty // http\n";
+    assert_eq!(
+        render::<Server>(s),
+        "\
+error[E0001]: port: invalid type: string \"eighty // http\", expected u16
+ --> config.hjson:2:7
+  |
+2 | port: eighty // http
+  |       ^^^^^^^^^^^^^^
+  = help: quoteless strings end at the end of the line, so `#` and `//` are part of the \
+string; wrap the string in quotes to follow it with a comment
+"
+    );
+
+    // Errors without a location only have the message.
+    let err = serde_hjson::from_value::<Server>(Value::Null).unwrap_err();
+    assert_eq!(
+        Diagnostic::new(&err, "").to_string(),
+        "error[E0001]: invalid type: null, expected struct Server\n"
+    );
+}
+
+#[test]
+fn test_diagnostic_hints() {
+    let s = "servers: [\n  {host: example.com, port: 80}\n]\n";
+    let err = serde_hjson::from_str::<Value>(s).unwrap_err();
+    assert_eq!(
+        Diagnostic::new(&err, s).hint(),
+        Some(
+            "quoteless strings end at the end of the line, including any `,`, `]` or `}`; \
+             wrap the string in quotes to end it earlier"
+        )
+    );
+
+    let s = "a: 'multiple\nlines'";
+    let err = serde_hjson::from_str::<Value>(s).unwrap_err();
+    assert_eq!(
+        Diagnostic::new(&err, s).hint(),
+        Some("use a `'''` multiline string for text spanning several lines")
+    );
+
+    let s = "[1, 2,,]";
+    let err = serde_hjson::from_str::<Value>(s).unwrap_err();
+    assert_eq!(Diagnostic::new(&err, s).hint(), None);
+}
+
+#[test]
+fn test_diagnostic_color() {
+    let s = "[1, 2,,]";
+    let err = serde_hjson::from_str::<Value>(s).unwrap_err();
+    assert_eq!(
+        Diagnostic::new(&err, s).color(true).to_string(),
+        "\
+\x1b[1;31merror[E0213]\x1b[0m\x1b[1m: extra comma\x1b[0m
+ \x1b[1;34m-->\x1b[0m 1:7
+  \x1b[1;34m|\x1b[0m
+\x1b[1;34m1 |\x1b[0m [1, 2,,]
+  \x1b[1;34m|\x1b[0m       \x1b[1;31m^\x1b[0m
+"
+    );
+}
@@ -0,0 +1,228 @@
+extern crate serde_hjson;
+
+use serde_hjson::Value;
+use serde_hjson::edit::Document;
+
+fn edit<F>(text: &str, f: F) -> String
+where
+    F: FnOnce(&mut Document),
+{
+    let mut doc: Document = text.parse().unwrap();
+    f(&mut doc);
+    doc.to_string()
+}
+
+const CONFIG: &'static str = "\
+# Release settings
+name: \"server\" // the binary name
+version: 1.2.3
+
+/* Listen addresses */
+listen: [
+  127.0.0.1:8080
+  0.0.0.0:8443
+]
+
+database: {
+  host: 'localhost'
+  port: 5432, # default
+}
+";
+
+#[test]
+fn test_edit_unchanged() {
+    let doc: Document = CONFIG.parse().unwrap();
+    assert_eq!(doc.as_str(), CONFIG);
+    assert_eq!(doc.get("/database/port"), Some(&Value::from(5432)));
+    assert_eq!(doc.get("/listen/1"), Some(&Value::from("0.0.0.0:8443")));
+    assert_eq!(doc.get("/missing"), None);
+}
+
+#[test]
+fn test_edit_set() {
+    let text = edit(CONFIG, |doc| {
+        doc.set("/version", &"1.2.4").unwrap();
+        doc.set("/name", &"proxy").unwrap();
+        doc.set("/database/port", &6543).unwrap();
+    });
+    assert_eq!(
+        text,
+        CONFIG
+            .replace("version: 1.2.3", "version: 1.2.4")
+            .replace("\"server\"", "\"proxy\"")
+            .replace("port: 5432", "port: 6543")
+    );
+}
+
+#[test]
+fn test_edit_set_quoted() {
+    // Quoted strings stay quoted, in the same quotes, unless they turn into a
+    // multiline string.
+    let cases: &[(&str, Value, &str)] = &[
+        ("version: \"1.2.3\"\n", Value::from("1.2.4"), "version: \"1.2.4\"\n"),
+        ("host: 'localhost'\n", Value::from("db"), "host: 'db'\n"),
+        ("name: \"a\"\n", Value::from("say \"hi\""), "name: \"say \\\"hi\\\"\"\n"),
+        ("name: 'a'\n", Value::from("it's \"a\""), "name: 'it\\'s \"a\"'\n"),
+        ("[\"a\", 'b']", Value::from("c"), "[\"a\", 'c']"),
+        ("port: '5432'\n", Value::from(6543), "port: 6543\n"),
+    ];
+    for &(before, ref value, after) in cases {
+        let pointer = if before.starts_with('[') {
+            String::from("/1")
+        } else {
+            format!("/{}", &before[..before.find(':').unwrap()])
+        };
+        let mut doc: Document = before.parse().unwrap();
+        doc.set(&pointer, value).unwrap();
+        assert_eq!(doc.as_str(), after, "{:?}", before);
+        let doc: Document = after.parse().unwrap();
+        assert_eq!(doc.get(&pointer), Some(value), "{:?}", before);
+    }
+}
+
+#[test]
+fn test_edit_set_structure() {
+    let text = edit(CONFIG, |doc| {
+        doc.set("/listen", &vec!["0.0.0.0:80"]).unwrap();
+        doc.set("/database/host", &"db\nhost").unwrap();
+    });
+    assert_eq!(
+        text,
+        "\
+# Release settings
+name: \"server\" // the binary name
+version: 1.2.3
+
+/* Listen addresses */
+listen: [
+  0.0.0.0:80
+]
+
+database: {
+  host:
+    '''
+    db
+    host
+    '''
+  port: 5432, # default
+}
+"
+    );
+}
+
+#[test]
+fn test_edit_insert() {
+    let text = edit(CONFIG, |doc| {
+        doc.insert("/listen/-", &"localhost:8080").unwrap();
+        doc.insert("/listen/0", &"0.0.0.0:80").unwrap();
+        doc.insert("/database/user", &"admin").unwrap();
+        doc.insert("/debug mode", &false).unwrap();
+    });
+    assert_eq!(
+        text,
+        "\
+# Release settings
+name: \"server\" // the binary name
+version: 1.2.3
+
+/* Listen addresses */
+listen: [
+  \"0.0.0.0:80\"
+  127.0.0.1:8080
+  0.0.0.0:8443
+  localhost:8080
+]
+
+database: {
+  host: 'localhost'
+  port: 5432, # default
+  user: admin,
+}
+\"debug mode\": false
+"
+    );
+}
+
+#[test]
+fn test_edit_remove() {
+    let mut doc: Document = CONFIG.parse().unwrap();
+    assert_eq!(doc.remove("/version").unwrap(), Value::from("1.2.3"));
+    assert_eq!(doc.remove("/listen/0").unwrap(), Value::from("127.0.0.1:8080"));
+    assert_eq!(doc.remove("/database/port").unwrap(), Value::from(5432));
+    assert_eq!(
+        doc.to_string(),
+        "\
+# Release settings
+name: \"server\" // the binary name
+
+/* Listen addresses */
+listen: [
+  0.0.0.0:8443
+]
+
+database: {
+  host: 'localhost'
+}
+"
+    );
+}
+
+#[test]
+fn test_edit_json() {
+    let text = edit("{\n  \"a\": 1,\n  \"b\": [1, 2]\n}", |doc| {
+        doc.insert("/c", &3).unwrap();
+        doc.insert("/b/-", &3).unwrap();
+        doc.remove("/b/0").unwrap();
+    });
+    assert_eq!(text, "{\n  \"a\": 1,\n  \"b\": [2, 3],\n  \"c\": 3\n}");
+
+    let text = edit("{\n  \"a\": 1,\n  \"b\": 2\n}", |doc| {
+        doc.remove("/b").unwrap();
+    });
+    assert_eq!(text, "{\n  \"a\": 1\n}");
+
+    let text = edit(r#"{"a": {}, "b": [1, 2, 3]}"#, |doc| {
+        doc.insert("/a/x", &"y").unwrap();
+        doc.remove("/b/2").unwrap();
+        doc.remove("/b/0").unwrap();
+    });
+    assert_eq!(text, r#"{"a": {x: "y"}, "b": [2]}"#);
+}
+
+#[test]
+fn test_edit_missing() {
+    let mut doc: Document = CONFIG.parse().unwrap();
+    assert_eq!(
+        doc.set("/database/user", &"admin").unwrap_err().to_string(),
+        "no value at `/database/user`"
+    );
+    assert_eq!(
+        doc.insert("/listen/5", &"localhost").unwrap_err().to_string(),
+        "no value at `/listen/5`"
+    );
+    assert_eq!(
+        doc.remove("/name/0").unwrap_err().to_string(),
+        "no value at `/name/0`"
+    );
+    assert_eq!(doc.as_str(), CONFIG);
+}
+
+#[test]
+fn test_edit_crlf() {
+    let text = edit("a: 1\r\nb: [\r\n  1\r\n  2\r\n]\r\n", |doc| {
+        doc.insert("/c", &"x\ny").unwrap();
+        doc.insert("/b/-", &3).unwrap();
+        doc.insert("/b/0", &0).unwrap();
+        doc.remove("/a").unwrap();
+    });
+    assert_eq!(text, "b: [\r\n  0\r\n  1\r\n  2\r\n  3\r\n]\r\nc: \"x\\ny\"\r\n");
+}
+
+#[test]
+fn test_edit_stray_slash() {
+    let text = edit("x:1\n/\n", |doc| doc.insert("/y", &2).unwrap());
+    assert_eq!(text, "x:1\ny: 2\n/\n");
+
+    let text = edit("{a: b\n/}", |doc| doc.insert("/c", &1).unwrap());
+    assert_eq!(text, "{a: b\nc: 1\n/}");
+}
@@ -0,0 +1,390 @@
+#[macro_use]
+extern crate serde_hjson;
+
+use std::fs;
+use std::path::Path;
+
+use serde_hjson::de::Read;
+use serde_hjson::events::{Event, Parser, PushParser};
+use serde_hjson::{Map, Result, Value};
+
+fn events(text: &str) -> Vec<String> {
+    let mut parser = Parser::from_str(text);
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event() {
+            Ok(Some((event, span))) => {
+                events.push(format!("{:?} {}..{}", event, span.start, span.end));
+            }
+            Ok(None) => return events,
+            Err(err) => {
+                events.push(format!("error: {}", err));
+                return events;
+            }
+        }
+    }
+}
+
+/// Builds a `Value` from the events, skipping comments.
+fn build<'de, R>(parser: &mut Parser<R>) -> Result<Value>
+where
+    R: Read<'de>,
+{
+    // The objects and arrays being built along with their member names.
+    let mut stack = Vec::new();
+    let mut key = None;
+    loop {
+        let value = match try!(parser.next_event()).unwrap().0 {
+            Event::Comment(_) => continue,
+            Event::Key(k) => {
+                key = Some(k.to_owned());
+                continue;
+            }
+            Event::StartObject => {
+                stack.push((Value::Object(Map::new()), key.take()));
+                continue;
+            }
+            Event::StartArray => {
+                stack.push((Value::Array(Vec::new()), key.take()));
+                continue;
+            }
+            Event::EndObject | Event::EndArray => {
+                let (value, k) = stack.pop().unwrap();
+                key = k;
+                value
+            }
+            Event::String(s) => Value::String(s.to_owned()),
+            Event::Number(n) => Value::Number(n),
+            Event::Bool(b) => Value::Bool(b),
+            Event::Null => Value::Null,
+            Event::__Nonexhaustive => unreachable!(),
+        };
+        match stack.last_mut() {
+            Some(&mut (Value::Array(ref mut array), _)) => array.push(value),
+            Some(&mut (Value::Object(ref mut object), _)) => {
+                object.insert(key.take().unwrap(), value);
+            }
+            Some(_) => unreachable!(),
+            None => {
+                while let Some((event, _)) = try!(parser.next_event()) {
+                    match event {
+                        Event::Comment(_) => {}
+                        event => panic!("unexpected {:?}", event),
+                    }
+                }
+                return Ok(value);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_events() {
+    let text = "\
+// Release settings
+name: \"server\" # the binary name
+version: 1.2.3
+ports: [80, 443,]
+flags: { debug: false, trace: null }
+motd:
+  '''
+  Welcome
+  '''
+";
+    assert_eq!(
+        events(text),
+        [
+            "Comment(\"// Release settings\") 0..19",
+            "StartObject 20..20",
+            "Key(\"name\") 20..24",
+            "String(\"server\") 26..34",
+            "Comment(\"# the binary name\") 35..52",
+            "Key(\"version\") 53..60",
+            "String(\"1.2.3\") 62..67",
+            "Key(\"ports\") 68..73",
+            "StartArray 75..76",
+            "Number(Number(80)) 76..78",
+            "Number(Number(443)) 80..83",
+            "EndArray 84..85",
+            "Key(\"flags\") 86..91",
+            "StartObject 93..94",
+            "Key(\"debug\") 95..100",
+            "Bool(false) 102..107",
+            "Key(\"trace\") 109..114",
+            "Null 116..120",
+            "EndObject 121..122",
+            "Key(\"motd\") 123..127",
+            "String(\"Welcome\") 131..150",
+            "EndObject 151..151",
+        ]
+    );
+}
+
+#[test]
+fn test_events_literals() {
+    assert_eq!(
+        events("[1 // one\n-2.5e3 /* x */, true story\n1.\nhttp://example.com\n]"),
+        [
+            "StartArray 0..1",
+            "Number(Number(1)) 1..2",
+            "Comment(\"// one\") 3..9",
+            "Number(Number(-2500.0)) 10..16",
+            "Comment(\"/* x */\") 17..24",
+            "String(\"true story\") 26..36",
+            "String(\"1.\") 37..39",
+            "String(\"http://example.com\") 40..58",
+            "EndArray 59..60",
+        ]
+    );
+    let text = "key: b\r\nc: true \r\nd: x y\r\n";
+    assert_eq!(
+        events(text),
+        [
+            "StartObject 0..0",
+            "Key(\"key\") 0..3",
+            "String(\"b\") 5..6",
+            "Key(\"c\") 8..9",
+            "Bool(true) 11..15",
+            "Key(\"d\") 18..19",
+            "String(\"x y\") 21..24",
+            "EndObject 26..26",
+        ]
+    );
+    assert_eq!(build(&mut Parser::from_str(text)).unwrap(), serde_hjson::from_str::<Value>(text).unwrap());
+    assert_eq!(events("null # nothing"), ["Null 0..4", "Comment(\"# nothing\") 5..14"]);
+    assert_eq!(events("1, 2"), ["String(\"1, 2\") 0..4"]);
+    assert_eq!(events("http://example.com"), ["String(\"http://example.com\") 0..18"]);
+    let mut parser = PushParser::new();
+    parser.feed(b"http://example.com");
+    parser.finish();
+    assert_eq!(parser.next_event().unwrap().unwrap().0, Event::String("http://example.com".into()));
+    assert_eq!(
+        events("\"a b\" : x"),
+        ["StartObject 0..0", "Key(\"a b\") 0..5", "String(\"x\") 8..9", "EndObject 9..9"]
+    );
+}
+
+#[test]
+fn test_events_errors() {
+    assert_eq!(
+        events("[1, 2 3]"),
+        [
+            "StartArray 0..1",
+            "Number(Number(1)) 1..2",
+            "String(\"2 3]\") 4..8",
+            "error: EOF while parsing a list at line 1 column 8",
+        ]
+    );
+    assert_eq!(
+        events("{\"a\": \"b\" c: 1}"),
+        [
+            "StartObject 0..1",
+            "Key(\"a\") 1..4",
+            "String(\"b\") 6..9",
+            "error: expected `,` or `}` at line 1 column 11",
+        ]
+    );
+    assert_eq!(events("[,]"), ["StartArray 0..1", "error: extra comma at line 1 column 2"]);
+    assert_eq!(events("a: 1\n}"), [
+        "StartObject 0..0",
+        "Key(\"a\") 0..1",
+        "Number(Number(1)) 3..4",
+        "error: trailing characters at line 2 column 1",
+    ]);
+    assert_eq!(events(""), ["error: EOF while parsing a value at line 1 column 0"]);
+}
+
+#[test]
+fn test_events_positions() {
+    let text = "{a: 1,\n  é: [true, 'x']}\n# end";
+    let expected = [(1, 1), (1, 2), (1, 5), (2, 3), (2, 7), (2, 8), (2, 14), (2, 17), (2, 18), (3, 1)];
+
+    fn positions<'de, R: Read<'de>>(mut parser: Parser<R>) -> Vec<(usize, usize)> {
+        let mut positions = Vec::new();
+        while let Some((_, span)) = parser.next_event().unwrap() {
+            positions.push((span.line, span.column));
+        }
+        positions
+    }
+    assert_eq!(positions(Parser::from_str(text)), expected);
+    assert_eq!(positions(Parser::from_reader(text.as_bytes())), expected);
+
+    let mut parser = PushParser::new();
+    let mut pushed = Vec::new();
+    for chunk in text.as_bytes().chunks(2) {
+        parser.feed(chunk);
+        while let Some((_, span)) = parser.next_event().unwrap() {
+            pushed.push((span.line, span.column));
+        }
+    }
+    parser.finish();
+    while let Some((_, span)) = parser.next_event().unwrap() {
+        pushed.push((span.line, span.column));
+    }
+    assert_eq!(pushed, expected);
+}
+
+#[test]
+fn test_events_match_value() {
+    for entry in fs::read_dir("tests/cases").unwrap() {
+        let path = entry.unwrap().path();
+        if !path.to_string_lossy().contains("_test.") {
+            continue;
+        }
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => continue,
+        };
+
+        // The same document with CRLF line endings.
+        let crlf = text.replace("\r\n", "\n").replace('\n', "\r\n");
+        for text in &[text, crlf] {
+            let expected = serde_hjson::from_str::<Value>(text);
+            let actual = build(&mut Parser::from_str(text));
+            assert_same(actual, expected, &path);
+
+            let expected = serde_hjson::from_reader::<_, Value>(text.as_bytes());
+            let actual = build(&mut Parser::from_reader(text.as_bytes()));
+            assert_same(actual, expected, &path);
+        }
+    }
+}
+
+/// Feeds `text` in chunks of `size` bytes, reading the events that are complete
+/// before each chunk.
+fn push_events(text: &str, size: usize) -> Vec<String> {
+    let mut parser = PushParser::new();
+    let mut chunks = text.as_bytes().chunks(size);
+    let mut finished = false;
+    let mut events = Vec::new();
+    loop {
+        match parser.next_event() {
+            Ok(Some((event, span))) => events.push(format!("{:?} {:?}", event, span)),
+            Ok(None) if finished => return events,
+            Ok(None) => {
+                match chunks.next() {
+                    Some(chunk) => parser.feed(chunk),
+                    None => {
+                        parser.finish();
+                        finished = true;
+                    }
+                }
+            }
+            Err(err) => {
+                events.push(format!("error: {} {:?}", err, err.byte_range()));
+                return events;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_push_events() {
+    let mut parser = PushParser::new();
+    let mut events = Vec::new();
+    for chunk in &["a: '''\n  multi", "line\n  ''' # com", "ment\nb: tr"] {
+        parser.feed(chunk.as_bytes());
+        while let Some((event, _)) = parser.next_event().unwrap() {
+            events.push(format!("{:?}", event));
+        }
+    }
+    assert_eq!(
+        events,
+        [
+            "StartObject",
+            "Key(\"a\")",
+            "String(\"multiline\")",
+            "Comment(\"# comment\")",
+            "Key(\"b\")",
+        ]
+    );
+
+    // `true` might be the start of a quoteless string until the input ends.
+    parser.feed(b"ue");
+    assert!(parser.next_event().unwrap().is_none());
+    parser.finish();
+    assert_eq!(parser.next_event().unwrap().unwrap().0, Event::Bool(true));
+    assert_eq!(parser.next_event().unwrap().unwrap().0, Event::EndObject);
+    assert!(parser.next_event().unwrap().is_none());
+}
+
+#[test]
+fn test_push_values() {
+    let mut parser = PushParser::stream();
+    let mut values = Vec::new();
+    for chunk in &["{\"a\": [1, {\"b\"", ": null}]}\n\"x", "\" 2", "0\n[] // end\n"] {
+        parser.feed(chunk.as_bytes());
+        while let Some(value) = parser.next_value().unwrap() {
+            values.push(value);
+        }
+    }
+    parser.finish();
+    assert!(parser.next_value().unwrap().is_none());
+    assert_eq!(values, [json!({"a": [1, {"b": null}]}), json!("x"), json!(20), json!([])]);
+
+    // Values split at every byte of CRLF input match `from_str`.
+    let docs = ["{\r\n  key: b\r\n  c: true \r\n}", "[x y\r\n1\r\n]", "true # yes"];
+    let text = docs.join("\r\n");
+    let mut parser = PushParser::stream();
+    let mut values = Vec::new();
+    for byte in text.as_bytes() {
+        parser.feed(&[*byte]);
+        while let Some(value) = parser.next_value().unwrap() {
+            values.push(value);
+        }
+    }
+    parser.finish();
+    while let Some(value) = parser.next_value().unwrap() {
+        values.push(value);
+    }
+    let expected: Vec<Value> = docs.iter().map(|doc| serde_hjson::from_str(doc).unwrap()).collect();
+    assert_eq!(values, expected);
+
+    let mut parser = PushParser::stream();
+    parser.feed(b"[1] ]");
+    assert_eq!(parser.next_value().unwrap(), Some(json!([1])));
+    assert!(parser.next_value().is_err());
+}
+
+#[test]
+fn test_push_match_pull() {
+    for entry in fs::read_dir("tests/cases").unwrap() {
+        let path = entry.unwrap().path();
+        if !path.is_file() {
+            continue;
+        }
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(_) => continue,
+        };
+
+        // The same document with CRLF line endings.
+        let crlf = text.replace("\r\n", "\n").replace('\n', "\r\n");
+        for text in &[text, crlf] {
+            let mut parser = Parser::from_reader(text.as_bytes());
+            let mut expected = Vec::new();
+            loop {
+                match parser.next_event() {
+                    Ok(Some((event, span))) => expected.push(format!("{:?} {:?}", event, span)),
+                    Ok(None) => break,
+                    Err(err) => {
+                        expected.push(format!("error: {} {:?}", err, err.byte_range()));
+                        break;
+                    }
+                }
+            }
+
+            for &size in &[1, 3, 64, text.len() + 1] {
+                let actual = push_events(text, size);
+                assert_eq!(actual, expected, "{} in chunks of {}", path.display(), size);
+            }
+        }
+    }
+}
+
+fn assert_same(actual: Result<Value>, expected: Result<Value>, path: &Path) {
+    match expected {
+        Ok(expected) => assert_eq!(actual.unwrap(), expected, "{}", path.display()),
+        Err(_) => assert!(actual.is_err(), "{}", path.display()),
+    }
+}
@@ -0,0 +1,166 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_hjson;
+
+use std::io;
+
+use serde::de::Deserialize;
+use serde_hjson::de::{Position, Read};
+use serde_hjson::{Deserializer, Value};
+
+/// Input in pieces, like the chunks of a rope. Only the required methods of
+/// `Read` are implemented.
+struct Pieces<'a> {
+    pieces: Vec<&'a [u8]>,
+    piece: usize,
+    index: usize,
+    /// Byte offset of the next byte.
+    offset: usize,
+}
+
+impl<'a> Pieces<'a> {
+    fn new(input: &'a str, size: usize) -> Self {
+        Pieces {
+            pieces: input.as_bytes().chunks(size).collect(),
+            piece: 0,
+            index: 0,
+            offset: 0,
+        }
+    }
+
+    fn bytes(&self) -> Vec<u8> {
+        self.pieces.concat()
+    }
+
+    /// Position of the byte before `offset`, counted like `IoRead` does for
+    /// ASCII input.
+    fn position_of(&self, offset: usize) -> Position {
+        let bytes = self.bytes();
+        let offset = offset.min(bytes.len());
+        let mut line = 1;
+        let mut column = 0;
+        for &ch in &bytes[..offset] {
+            if ch == b'\n' {
+                line += 1;
+                column = 0;
+            } else {
+                column += 1;
+            }
+        }
+        Position {
+            line: line,
+            column: column,
+            char_column: column,
+            utf16_column: column,
+            start: if column == 0 { offset } else { offset - 1 },
+            end: offset,
+        }
+    }
+}
+
+impl<'a, 'de> Read<'de> for Pieces<'a> {
+    fn next(&mut self) -> io::Result<Option<u8>> {
+        let ch = try!(self.peek());
+        if ch.is_some() {
+            self.discard();
+        }
+        Ok(ch)
+    }
+
+    fn peek(&mut self) -> io::Result<Option<u8>> {
+        while self.piece < self.pieces.len() && self.index == self.pieces[self.piece].len() {
+            self.piece += 1;
+            self.index = 0;
+        }
+        Ok(self.pieces.get(self.piece).map(|piece| piece[self.index]))
+    }
+
+    fn discard(&mut self) {
+        self.index += 1;
+        self.offset += 1;
+    }
+
+    fn position(&self) -> Position {
+        self.position_of(self.offset)
+    }
+
+    fn peek_position(&self) -> Position {
+        if self.bytes().get(self.offset) != Some(&b'\n') {
+            return self.position_of(self.offset + 1);
+        }
+        // A newline is at the end of its own line.
+        let mut position = self.position_of(self.offset);
+        position.column += 1;
+        position.char_column += 1;
+        position.utf16_column += 1;
+        position.start = self.offset;
+        position.end = self.offset + 1;
+        position
+    }
+
+    fn byte_offset(&self) -> usize {
+        self.offset
+    }
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Config {
+    name: String,
+    motd: String,
+    ports: Vec<u16>,
+    tags: Vec<String>,
+}
+
+#[test]
+fn test_custom_read() {
+    let input = "\
+# a config file in pieces
+name: \"ex\\u0061mple\"
+motd:
+  '''
+  Hello
+    World
+  '''
+ports: [80, 443]
+tags: [
+  'single'
+  quoteless string
+]
+ignored: { \"a\": 'b', c: 1 }
+";
+    let expected = Config {
+        name: "example".to_owned(),
+        motd: "Hello\n  World".to_owned(),
+        ports: vec![80, 443],
+        tags: vec!["single".to_owned(), "quoteless string".to_owned()],
+    };
+    assert_eq!(serde_hjson::from_str::<Config>(input).unwrap(), expected);
+
+    for &size in &[1, 2, 7, input.len()] {
+        let mut de = Deserializer::new(Pieces::new(input, size));
+        assert_eq!(Config::deserialize(&mut de).unwrap(), expected);
+        de.end().unwrap();
+
+        let mut de = Deserializer::new(Pieces::new(input, size));
+        let value = Value::deserialize(&mut de).unwrap();
+        assert_eq!(value, serde_hjson::from_str::<Value>(input).unwrap());
+    }
+}
+
+#[test]
+fn test_custom_read_errors() {
+    for &input in &[
+        "{\"a\": \"b\nc\"}",
+        "{a: 'b\n'}",
+        "{a: \"\\x\"}",
+        "[1, 2",
+        "{a: 1} b",
+    ] {
+        let expected = serde_hjson::from_reader::<_, Value>(input.as_bytes()).unwrap_err();
+        let mut de = Deserializer::new(Pieces::new(input, 3));
+        let err = Value::deserialize(&mut de).and_then(|_| de.end()).unwrap_err();
+        assert_eq!(err.to_string(), expected.to_string(), "{:?}", input);
+        assert_eq!(err.byte_range(), expected.byte_range(), "{:?}", input);
+    }
+}
@@ -0,0 +1,211 @@
+extern crate serde;
+#[macro_use]
+extern crate serde_derive;
+extern crate serde_hjson;
+
+use serde::Serialize;
+use serde_hjson::Serializer;
+use serde_hjson::ser::{Multiline, PrettyFormatter, Quotes};
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Database {
+    host: String,
+    port: u16,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Config {
+    name: String,
+    tags: Vec<String>,
+    database: Database,
+    notes: String,
+}
+
+fn to_string_with<T: Serialize>(value: &T, formatter: PrettyFormatter) -> String {
+    let mut writer = Vec::new();
+    value
+        .serialize(&mut Serializer::with_formatter(&mut writer, formatter))
+        .unwrap();
+    String::from_utf8(writer).unwrap()
+}
+
+#[test]
+fn test_write_braceless_root() {
+    let config = Config {
+        name: "example".to_owned(),
+        tags: vec!["a".to_owned(), "b".to_owned()],
+        database: Database {
+            host: "127.0.0.1".to_owned(),
+            port: 5432,
+        },
+        notes: "first line\nsecond line".to_owned(),
+    };
+    let expected = "\
+name: example
+tags:
+[
+  a
+  b
+]
+database:
+{
+  host: 127.0.0.1
+  port: 5432
+}
+notes:
+  '''
+  first line
+  second line
+  '''";
+
+    let hjson = to_string_with(&config, PrettyFormatter::new().root_braces(false));
+    assert_eq!(hjson, expected);
+    assert_eq!(serde_hjson::from_str::<Config>(&hjson).unwrap(), config);
+}
+
+#[test]
+fn test_write_braceless_root_non_objects() {
+    let formatter = || PrettyFormatter::new().root_braces(false);
+
+    let empty: serde_hjson::Map<String, serde_hjson::Value> = serde_hjson::Map::new();
+    assert_eq!(to_string_with(&empty, formatter()), "{}");
+    assert_eq!(to_string_with(&vec![1, 2], formatter()), "[\n  1\n  2\n]");
+    assert_eq!(to_string_with(&5, formatter()), "5");
+}
+
+#[test]
+fn test_write_quoted_multiline() {
+    let mut map = serde_hjson::Map::new();
+    map.insert("text".to_owned(), serde_hjson::Value::from("foo\nbar"));
+
+    let formatter = PrettyFormatter::new().quotes(Quotes::Strings);
+    assert_eq!(to_string_with(&map, formatter), "{\n  text: \"foo\\nbar\"\n}");
+
+    let formatter = PrettyFormatter::new()
+        .quotes(Quotes::Strings)
+        .quoted_multiline(true);
+    assert_eq!(
+        to_string_with(&map, formatter),
+        "{\n  text:\n    '''\n    foo\n    bar\n    '''\n}"
+    );
+}
+
+#[test]
+fn test_write_multiline_outside_object() {
+    let text = "foo\nbar".to_owned();
+    assert_eq!(
+        to_string_with(&text, PrettyFormatter::new()),
+        "'''\nfoo\nbar\n'''"
+    );
+    assert_eq!(
+        to_string_with(&vec![text.clone(), text], PrettyFormatter::new()),
+        "[\n  '''\n  foo\n  bar\n  '''\n  '''\n  foo\n  bar\n  '''\n]"
+    );
+}
+
+#[test]
+fn test_write_multiline_lines() {
+    // Every line keeps its text, with or without whitespace in it, and empty
+    // lines are not indented.
+    let text = "first\nsecond line\n\n  third\n".to_owned();
+    let mut map = serde_hjson::Map::new();
+    map.insert("text".to_owned(), serde_hjson::Value::from(text.clone()));
+    let hjson = to_string_with(&map, PrettyFormatter::new());
+    assert_eq!(
+        hjson,
+        "{\n  text:\n    '''\n    first\n    second line\n\n      third\n\n    '''\n}"
+    );
+    assert_eq!(serde_hjson::from_str::<serde_hjson::Value>(&hjson).unwrap()["text"], text);
+}
+
+#[test]
+fn test_write_separator() {
+    let value: serde_hjson::Value = serde_hjson::from_str(
+        r#"{
+            a: text
+            b: [1, {c: null}]
+            d: quote"d
+            e:
+                '''
+                multi
+                line
+                '''
+        }"#,
+    ).unwrap();
+    let expected = r#"{
+  a: "text",
+  b:
+  [
+    1,
+    {
+      c: null
+    }
+  ],
+  d: "quote\"d",
+  e:
+    '''
+    multi
+    line
+    '''
+}"#;
+
+    let hjson = to_string_with(&value, PrettyFormatter::new().separator(true));
+    assert_eq!(hjson, expected);
+    assert_eq!(serde_hjson::from_str::<serde_hjson::Value>(&hjson).unwrap(), value);
+}
+
+#[test]
+fn test_write_multiline_policy() {
+    let mut map = serde_hjson::Map::new();
+    map.insert("a".to_owned(), serde_hjson::Value::from("foo\nbar"));
+    map.insert("b".to_owned(), serde_hjson::Value::from("foo\n\tbar"));
+
+    let formatter = PrettyFormatter::new().multiline(Multiline::Std);
+    assert_eq!(
+        to_string_with(&map, formatter),
+        "{\n  a:\n    '''\n    foo\n    bar\n    '''\n  b:\n    '''\n    foo\n    \tbar\n    '''\n}"
+    );
+
+    let formatter = PrettyFormatter::new().multiline(Multiline::NoTabs);
+    assert_eq!(
+        to_string_with(&map, formatter),
+        "{\n  a:\n    '''\n    foo\n    bar\n    '''\n  b: \"foo\\n\\tbar\"\n}"
+    );
+
+    let formatter = PrettyFormatter::new().multiline(Multiline::Off);
+    assert_eq!(
+        to_string_with(&map, formatter),
+        "{\n  a: \"foo\\nbar\"\n  b: \"foo\\n\\tbar\"\n}"
+    );
+}
+
+#[test]
+fn test_write_json_pretty() {
+    let value: serde_hjson::Value = serde_hjson::from_str(
+        r#"{
+            a: [1, 2.5, true, null, [], {}]
+            b: "tab\tquote\"emoji😀\u0001"
+        }"#,
+    ).unwrap();
+    let expected = r#"{
+  "a": [
+    1,
+    2.5,
+    true,
+    null,
+    [],
+    {}
+  ],
+  "b": "tab\tquote\"emoji😀\u0001"
+}"#;
+
+    assert_eq!(serde_hjson::to_string_json_pretty(&value).unwrap(), expected);
+    assert_eq!(
+        serde_hjson::to_vec_json_pretty(&value).unwrap(),
+        expected.as_bytes()
+    );
+
+    let mut writer = Vec::new();
+    serde_hjson::to_writer_json_pretty(&mut writer, &value).unwrap();
+    assert_eq!(writer, expected.as_bytes());
+}
@@ -100,7 +100,7 @@ macro_rules! test {
 
 fn std_fix(json: String) -> String {
     let re = Regex::new(r"(?m)(?P<d>\d)\.0(?P<x>,?)$").unwrap();
-    String::from(re.replace_all(&json, "$d$s"))
+    String::from(re.replace_all(&json, "$d$x"))
 }
 
 fn fix_pass1(json: String) -> String {
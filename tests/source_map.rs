@@ -0,0 +1,122 @@
+extern crate serde_hjson;
+
+use serde_hjson::Value;
+use serde_hjson::source_map::{Location, SourceMap, Span};
+
+const CONFIG: &'static str = "\
+# servers
+servers: [
+  {
+    host: example.com
+    'port': 80 // http
+  }
+  {\"host\": \"example.org\", \"tls\": {\"cert/path\": '/etc/cert'}}
+]
+debug: true
+";
+
+fn text<'a>(s: &'a str, span: &Span) -> &'a str {
+    &s[span.start..span.end]
+}
+
+fn check_same(s: &str, source_map: &SourceMap) {
+    let (_, from_slice) = serde_hjson::from_slice_with_source_map::<Value>(s.as_bytes())
+        .unwrap();
+    let (_, from_reader) = serde_hjson::from_reader_with_source_map::<_, Value>(s.as_bytes())
+        .unwrap();
+    let expected: Vec<_> = source_map.iter().collect();
+    assert_eq!(from_slice.iter().collect::<Vec<_>>(), expected);
+    assert_eq!(from_reader.iter().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_source_map() {
+    let (value, source_map) = serde_hjson::from_str_with_source_map::<Value>(CONFIG).unwrap();
+    check_same(CONFIG, &source_map);
+
+    // Every value in the document is in the source map.
+    let pointers: Vec<_> = source_map.iter().map(|(pointer, _)| pointer.as_str()).collect();
+    assert_eq!(
+        pointers,
+        vec![
+            "",
+            "/debug",
+            "/servers",
+            "/servers/0",
+            "/servers/0/host",
+            "/servers/0/port",
+            "/servers/1",
+            "/servers/1/host",
+            "/servers/1/tls",
+            "/servers/1/tls/cert~1path",
+        ]
+    );
+    for pointer in pointers {
+        assert!(value.pointer(pointer).is_some());
+    }
+
+    let port = source_map.get("/servers/0/port").unwrap();
+    assert_eq!(text(CONFIG, &port.value), "80");
+    assert_eq!(text(CONFIG, port.key.as_ref().unwrap()), "'port'");
+    assert_eq!((port.key.unwrap().line, port.key.unwrap().column), (5, 5));
+    assert_eq!((port.value.line, port.value.column), (5, 13));
+
+    assert_eq!(text(CONFIG, source_map.value("/servers/0/host").unwrap()), "example.com");
+    assert_eq!(text(CONFIG, source_map.key("/servers/1/tls").unwrap()), "\"tls\"");
+    assert_eq!(
+        text(CONFIG, source_map.value("/servers/1/tls/cert~1path").unwrap()),
+        "'/etc/cert'"
+    );
+    assert_eq!(
+        text(CONFIG, source_map.value("/servers/1").unwrap()),
+        "{\"host\": \"example.org\", \"tls\": {\"cert/path\": '/etc/cert'}}"
+    );
+    assert_eq!(source_map.key("/servers/1"), None);
+
+    // The root object without braces spans from its first member to the end
+    // of its last value.
+    let root = source_map.get("").unwrap();
+    assert_eq!(root.key, None);
+    assert_eq!(text(CONFIG, &root.value), &CONFIG[10..CONFIG.len() - 1]);
+    assert_eq!((root.value.line, root.value.column), (2, 1));
+}
+
+#[test]
+fn test_source_map_duplicate_keys() {
+    let s = "{a: {x: 1}, a: {y: 2}}";
+    let (value, source_map) = serde_hjson::from_str_with_source_map::<Value>(s).unwrap();
+    assert_eq!(source_map.len(), 3);
+    assert_eq!(
+        source_map.get("/a"),
+        Some(&Location {
+            key: Some(Span { start: 12, end: 13, line: 1, column: 13 }),
+            value: Span { start: 15, end: 21, line: 1, column: 16 },
+        })
+    );
+    assert_eq!(text(s, source_map.value("/a/y").unwrap()), "2");
+    assert!(value.pointer("/a/x").is_none());
+    assert!(source_map.get("/a/x").is_none());
+}
+
+#[test]
+fn test_source_map_scalars() {
+    for &(s, expected) in &[
+        ("  42  ", "42"),
+        ("# comment\nquoteless string  \n", "quoteless string"),
+        ("'''\n  multi\n  line\n  ''' # comment", "'''\n  multi\n  line\n  '''"),
+    ] {
+        let (_, source_map) = serde_hjson::from_str_with_source_map::<Value>(s).unwrap();
+        check_same(s, &source_map);
+        assert_eq!(source_map.len(), 1);
+        assert_eq!(text(s, source_map.value("").unwrap()), expected);
+    }
+}
+
+#[test]
+fn test_source_map_errors() {
+    let err = serde_hjson::from_str_with_source_map::<Value>("[1, 2\n").unwrap_err();
+    assert_eq!(err.to_string(), "EOF while parsing a list at line 2 column 0");
+
+    let err = serde_hjson::from_slice_with_source_map::<Value>(b"a: 1\n# \xff\n").unwrap_err();
+    assert_eq!(err.to_string(), "invalid unicode code point at line 2 column 3");
+}
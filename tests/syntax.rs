@@ -0,0 +1,195 @@
+extern crate serde_hjson;
+
+use std::fs;
+
+use serde_hjson::syntax::{self, Child, NodeKind, TokenKind};
+
+fn tokens(text: &str) -> Vec<(TokenKind, &str)> {
+    syntax::tokenize(text)
+        .filter(|token| !token.kind.is_trivia())
+        .map(|token| (token.kind, token.text))
+        .collect()
+}
+
+const CONFIG: &'static str = "\
+# Release settings
+name: \"server\" // the binary name
+version: 1.2.3
+
+/* Listen addresses */
+listen: [
+  127.0.0.1:8080
+  8443, true
+]
+
+database: {
+  host: 'localhost'
+  port: 5432, # default
+  motd:
+    '''
+    Welcome
+    '''
+}
+";
+
+#[test]
+fn test_tokenize() {
+    use serde_hjson::syntax::TokenKind::*;
+
+    assert_eq!(
+        tokens(CONFIG),
+        [
+            (MemberName, "name"),
+            (Colon, ":"),
+            (DoubleQuotedString, "\"server\""),
+            (MemberName, "version"),
+            (Colon, ":"),
+            (QuotelessString, "1.2.3"),
+            (MemberName, "listen"),
+            (Colon, ":"),
+            (LeftBracket, "["),
+            (QuotelessString, "127.0.0.1:8080"),
+            (Number, "8443"),
+            (Comma, ","),
+            (Literal, "true"),
+            (RightBracket, "]"),
+            (MemberName, "database"),
+            (Colon, ":"),
+            (LeftBrace, "{"),
+            (MemberName, "host"),
+            (Colon, ":"),
+            (SingleQuotedString, "'localhost'"),
+            (MemberName, "port"),
+            (Colon, ":"),
+            (Number, "5432"),
+            (Comma, ","),
+            (MemberName, "motd"),
+            (Colon, ":"),
+            (MultilineString, "'''\n    Welcome\n    '''"),
+            (RightBrace, "}"),
+        ]
+    );
+
+    let trivia: Vec<_> = syntax::tokenize("a: 1 // one\r\n\t/* x */")
+        .filter(|token| token.kind.is_trivia())
+        .map(|token| (token.kind, token.span()))
+        .collect();
+    assert_eq!(
+        trivia,
+        [
+            (Whitespace, 2..3),
+            (Whitespace, 4..5),
+            (Comment, 5..11),
+            (Newline, 11..13),
+            (Whitespace, 13..14),
+            (Comment, 14..21),
+        ]
+    );
+}
+
+#[test]
+fn test_tokenize_quoteless() {
+    use serde_hjson::syntax::TokenKind::*;
+
+    assert_eq!(tokens("a: true story"), [(MemberName, "a"), (Colon, ":"), (QuotelessString, "true story")]);
+    assert_eq!(tokens("a: 1 b  "), [(MemberName, "a"), (Colon, ":"), (QuotelessString, "1 b")]);
+    assert_eq!(tokens("a: 1.\n"), [(MemberName, "a"), (Colon, ":"), (QuotelessString, "1.")]);
+    assert_eq!(tokens("a: 1 # c"), [(MemberName, "a"), (Colon, ":"), (Number, "1")]);
+    assert_eq!(tokens("a: x, y # z"), [(MemberName, "a"), (Colon, ":"), (QuotelessString, "x, y # z")]);
+    assert_eq!(tokens("[01\n-x\n1e5,null]"), [
+        (LeftBracket, "["),
+        (QuotelessString, "01"),
+        (QuotelessString, "-x"),
+        (Number, "1e5"),
+        (Comma, ","),
+        (Literal, "null"),
+        (RightBracket, "]"),
+    ]);
+    assert_eq!(tokens("{a:b}"), [(LeftBrace, "{"), (MemberName, "a"), (Colon, ":"), (QuotelessString, "b}")]);
+    assert_eq!(tokens("\"a\"\n: 1"), [(MemberName, "\"a\""), (Colon, ":"), (Number, "1")]);
+    assert_eq!(tokens("a b"), [(QuotelessString, "a b")]);
+    assert_eq!(tokens("http://example.com"), [(QuotelessString, "http://example.com")]);
+    assert_eq!(tokens("{'''k''': 1}"), [
+        (LeftBrace, "{"),
+        (MemberName, "'''k'''"),
+        (Colon, ":"),
+        (Number, "1"),
+        (RightBrace, "}"),
+    ]);
+}
+
+#[test]
+fn test_tokenize_errors() {
+    use serde_hjson::syntax::TokenKind::*;
+
+    assert_eq!(tokens("a: \"b\nc: 1"), [
+        (MemberName, "a"),
+        (Colon, ":"),
+        (Error, "\"b"),
+        (MemberName, "c"),
+        (Colon, ":"),
+        (Number, "1"),
+    ]);
+    assert_eq!(tokens("[1] /* x"), [(LeftBracket, "["), (Number, "1"), (RightBracket, "]"), (Error, "/* x")]);
+    assert_eq!(tokens("a: '''x\nb"), [(MemberName, "a"), (Colon, ":"), (Error, "'''x\nb")]);
+}
+
+#[test]
+fn test_parse() {
+    let document = syntax::parse(CONFIG);
+    assert_eq!(document.kind(), NodeKind::Document);
+    assert_eq!(document.span(), 0..CONFIG.len());
+
+    let root = document.value().and_then(Child::as_node).unwrap();
+    assert_eq!(root.kind(), NodeKind::Object);
+    assert!(!root.to_string().starts_with('#'));
+
+    let names: Vec<_> = root.members().map(|member| member.name().unwrap().text).collect();
+    assert_eq!(names, ["name", "version", "listen", "database"]);
+
+    let listen = root.members().nth(2).unwrap();
+    assert_eq!(listen.to_string(), "listen: [\n  127.0.0.1:8080\n  8443, true\n]");
+    let array = listen.value().and_then(Child::as_node).unwrap();
+    assert_eq!(array.kind(), NodeKind::Array);
+    let elements: Vec<_> = array
+        .elements()
+        .map(|element| element.as_token().unwrap().text)
+        .collect();
+    assert_eq!(elements, ["127.0.0.1:8080", "8443", "true"]);
+
+    let database = root.members().nth(3).unwrap().value().and_then(Child::as_node).unwrap();
+    let port = database.members().nth(1).unwrap();
+    assert_eq!(port.value().and_then(Child::as_token).unwrap().text, "5432");
+    assert_eq!(&CONFIG[port.span()], "port: 5432");
+}
+
+#[test]
+fn test_lossless() {
+    let mut samples = vec![
+        CONFIG.to_owned(),
+        "a: \"b\nc: [1, {d: 2\n".to_owned(),
+        "}]{:,\r\n'''".to_owned(),
+        "  // only a comment\r\n".to_owned(),
+        String::new(),
+    ];
+    for entry in fs::read_dir("tests/cases").unwrap() {
+        let path = entry.unwrap().path();
+        if path.is_file() {
+            samples.push(fs::read_to_string(path).unwrap());
+        }
+    }
+
+    for text in &samples {
+        let mut end = 0;
+        for token in syntax::tokenize(text) {
+            assert_eq!(token.start, end);
+            assert!(!token.text.is_empty());
+            end = token.end();
+        }
+        assert_eq!(end, text.len());
+
+        let document = syntax::parse(text);
+        assert_eq!(document.to_string(), *text);
+        assert_eq!(document.tokens().count(), syntax::tokenize(text).count());
+    }
+}